@@ -0,0 +1,23 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use evmodin::{AnalyzedCode, MAX_CODE_SIZE};
+
+fn analysis(c: &mut Criterion) {
+    // Arbitrary bytes: a mix of PUSHes, JUMPDESTs and other opcodes.
+    let code = (0..MAX_CODE_SIZE)
+        .map(|i| (i * 97 % 251) as u8)
+        .collect::<Vec<_>>();
+    let data = AnalyzedCode::analyze(code.clone()).to_bytes();
+
+    c.bench_function("analyze", |b| {
+        b.iter(|| AnalyzedCode::analyze(black_box(&code[..])))
+    });
+    c.bench_function("from_bytes", |b| {
+        b.iter(|| AnalyzedCode::from_bytes(black_box(&data), false).unwrap())
+    });
+    c.bench_function("from_bytes_verify", |b| {
+        b.iter(|| AnalyzedCode::from_bytes(black_box(&data), true).unwrap())
+    });
+}
+
+criterion_group!(benches, analysis);
+criterion_main!(benches);
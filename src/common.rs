@@ -1,3 +1,4 @@
+use crate::opcode::OpCode;
 use bytes::Bytes;
 use ethereum_types::*;
 use serde::Serialize;
@@ -37,8 +38,15 @@ pub enum Revision {
     /// [The London revision.](https://github.com/ethereum/eth1.0-specs/blob/master/network-upgrades/mainnet-upgrades/london.md)
     London = 9,
 
+    /// [The Paris revision.](https://github.com/ethereum/execution-specs/blob/master/network-upgrades/mainnet-upgrades/paris.md)
+    /// The Merge.
+    Paris = 10,
+
     /// The Shanghai revision.
-    Shanghai = 10,
+    Shanghai = 11,
+
+    /// The Cancun revision.
+    Cancun = 12,
 }
 
 impl Revision {
@@ -54,17 +62,26 @@ impl Revision {
             Self::Istanbul,
             Self::Berlin,
             Self::London,
+            Self::Paris,
             Self::Shanghai,
+            Self::Cancun,
         ]
     }
 
     pub const fn latest() -> Self {
-        Self::Shanghai
+        Self::Cancun
     }
 
     pub const fn len() -> usize {
         Self::latest() as usize + 1
     }
+
+    /// Whether SSTORE uses net gas metering (EIP-1283, EIP-2200).
+    ///
+    /// Introduced in Constantinople, reverted in Petersburg and reintroduced in Istanbul.
+    pub const fn sstore_gas_metering(self) -> bool {
+        matches!(self, Self::Constantinople) || self as u8 >= Self::Istanbul as u8
+    }
 }
 
 /// Message status code.
@@ -154,6 +171,132 @@ pub enum StatusCode {
     InternalError(String),
 }
 
+impl std::error::Error for StatusCode {}
+
+impl StatusCode {
+    /// Status for a failed host method: the status itself if the host has returned one,
+    /// such as `Err(StatusCode::InsufficientBalance.into())`, otherwise
+    /// `StatusCode::InternalError` with the error message.
+    pub fn from_host_error(error: anyhow::Error) -> Self {
+        match error.downcast::<Self>() {
+            Ok(status) => status,
+            Err(error) => Self::InternalError(error.to_string()),
+        }
+    }
+}
+
+/// EOF container validation error.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum EofError {
+    /// The container does not start with the `0xEF00` prefix.
+    #[strum(serialize = "invalid prefix")]
+    InvalidPrefix,
+
+    /// The container version is not supported.
+    #[strum(serialize = "unknown version")]
+    UnknownVersion,
+
+    /// A section header is cut short.
+    #[strum(serialize = "incomplete section size")]
+    IncompleteSectionSize,
+
+    /// A section header declares a section of zero size.
+    #[strum(serialize = "zero section size")]
+    ZeroSectionSize,
+
+    /// An unknown section kind has been encountered.
+    #[strum(serialize = "unknown section id")]
+    UnknownSectionId,
+
+    /// The container has no code section, or it is not the first one.
+    #[strum(serialize = "code section missing")]
+    CodeSectionMissing,
+
+    /// More than one code section has been declared.
+    #[strum(serialize = "multiple code sections")]
+    MultipleCodeSections,
+
+    /// More than one data section has been declared.
+    #[strum(serialize = "multiple data sections")]
+    MultipleDataSections,
+
+    /// The section headers are not terminated.
+    #[strum(serialize = "section headers not terminated")]
+    SectionHeadersNotTerminated,
+
+    /// The total size of sections does not match the container size.
+    #[strum(serialize = "invalid section bodies size")]
+    InvalidSectionBodiesSize,
+
+    /// The code section contains an undefined instruction.
+    #[strum(serialize = "undefined instruction")]
+    UndefinedInstruction,
+
+    /// The code section ends in the middle of PUSH immediate data.
+    #[strum(serialize = "truncated immediate")]
+    TruncatedImmediate,
+}
+
+/// Instruction counts of analyzed code, see `AnalyzedCode::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AnalysisStats {
+    /// Instructions, including a PUSH truncated by the end of code.
+    pub instructions: usize,
+    /// Valid jump destinations.
+    pub jumpdests: usize,
+    /// Bytes of PUSH immediates present in code.
+    pub push_bytes: usize,
+}
+
+/// Code analysis error.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum AnalysisError {
+    /// A PUSH instruction at `pc` is not followed by all of its immediate bytes.
+    #[strum(serialize = "truncated push")]
+    TruncatedPush { pc: usize },
+
+    /// A PUSH instruction at `pc` feeds a JUMP or JUMPI with a destination which is not a JUMPDEST.
+    #[strum(serialize = "invalid jump destination")]
+    InvalidJumpdest { pc: usize },
+
+    /// Code is larger than [`MAX_CODE_SIZE`](crate::MAX_CODE_SIZE).
+    #[strum(serialize = "oversize code")]
+    OversizeCode { size: usize },
+
+    /// EOF container is malformed.
+    #[strum(serialize = "invalid EOF container")]
+    Eof(EofError),
+}
+
+/// Error decoding [`AnalyzedCode`](crate::AnalyzedCode) serialized with `to_bytes`.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The encoding is shorter than its header or than the lengths it declares.
+    #[strum(serialize = "truncated")]
+    Truncated,
+
+    /// The encoding has been produced by an unsupported format version.
+    #[strum(serialize = "unsupported version")]
+    UnsupportedVersion { version: u8 },
+
+    /// The checksum does not match the contents.
+    #[strum(serialize = "checksum mismatch")]
+    ChecksumMismatch,
+
+    /// Bytes are left over after the checksum.
+    #[strum(serialize = "trailing bytes")]
+    TrailingBytes,
+
+    /// A jump destination is marked at `pc`, but there is no JUMPDEST there, or `pc` is
+    /// outside of code.
+    #[strum(serialize = "invalid jump destination")]
+    InvalidJumpdest { pc: usize },
+
+    /// Jump destinations differ from a fresh analysis of the code.
+    #[strum(serialize = "analysis mismatch")]
+    AnalysisMismatch,
+}
+
 /// The kind of call-like instruction.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CallKind {
@@ -187,6 +330,9 @@ pub struct Message {
     pub sender: Address,
 
     /// Message input data.
+    ///
+    /// Ignored for `CallKind::Create` and `CallKind::Create2`, where CALLDATA instructions
+    /// see empty input. Constructor arguments are appended to init code instead.
     pub input_data: Bytes,
 
     /// The amount of Ether transferred with the message.
@@ -199,6 +345,35 @@ pub struct Message {
     pub code_address: Address,
 }
 
+impl Message {
+    /// Check invariants of a message before execution, which the interpreter assumes to hold.
+    ///
+    /// Gas must not be negative and depth must be within `0..=1024`. A static frame must not
+    /// create contracts nor transfer value, except the apparent value of DELEGATECALL.
+    /// CREATE2 needs Constantinople.
+    pub fn validate(&self, revision: Revision) -> Result<(), StatusCode> {
+        if self.gas < 0 || self.depth < 0 {
+            return Err(StatusCode::ArgumentOutOfRange);
+        }
+        if self.depth > 1024 {
+            return Err(StatusCode::CallDepthExceeded);
+        }
+        if let CallKind::Create2 { .. } = self.kind {
+            if revision < Revision::Constantinople {
+                return Err(StatusCode::UndefinedInstruction);
+            }
+        }
+        if self.is_static {
+            let transfers_value = !self.value.is_zero() && self.kind != CallKind::DelegateCall;
+            let creates = matches!(self.kind, CallKind::Create | CallKind::Create2 { .. });
+            if transfers_value || creates {
+                return Err(StatusCode::StaticModeViolation);
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct CreateMessage {
     pub salt: Option<U256>,
@@ -229,6 +404,241 @@ impl From<CreateMessage> for Message {
     }
 }
 
+/// Host budget that has been exhausted, see `HostBudget`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostBudgetExceeded {
+    /// Too many host interrupts.
+    Interrupts,
+    /// Too many bytes returned by the host.
+    BytesReturned,
+}
+
+/// Reason of a failed contract creation, see `ExecutionStats::create_failure`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreateFailure {
+    /// Init code has not completed successfully, see the status code for details.
+    InitFailed,
+    /// Returned code is larger than [`MAX_CODE_SIZE`](crate::MAX_CODE_SIZE).
+    CodeTooLarge,
+    /// Returned code starts with the 0xEF byte, reserved since London (EIP-3541).
+    InvalidCodePrefix,
+    /// Not enough gas left to pay for the returned code.
+    DepositOutOfGas,
+}
+
+/// Capability of an execution frame, see [`CapabilityMask`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capability {
+    /// SSTORE and TSTORE.
+    WriteStorage,
+    /// LOG0 to LOG4.
+    Log,
+    /// SELFDESTRUCT.
+    Selfdestruct,
+    /// CALL with value.
+    TransferValue,
+    /// CREATE and CREATE2.
+    CreateAccounts,
+    /// Reserved for extensions which change state outside of EVM rules.
+    Sudo,
+}
+
+/// Set of capabilities of an execution frame.
+///
+/// Instructions which need a capability the frame lacks fail with
+/// `StatusCode::StaticModeViolation`. Static frames have none.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapabilityMask(u8);
+
+impl CapabilityMask {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn contains(self, capability: Capability) -> bool {
+        self.0 & (1 << capability as u8) != 0
+    }
+
+    #[must_use]
+    pub const fn with(self, capability: Capability) -> Self {
+        Self(self.0 | (1 << capability as u8))
+    }
+
+    #[must_use]
+    pub const fn without(self, capability: Capability) -> Self {
+        Self(self.0 & !(1 << capability as u8))
+    }
+
+    /// Capabilities of a frame executing `message`: `granted` ones unless it is static.
+    pub(crate) fn for_frame(message: &Message, granted: Self) -> Self {
+        if message.is_static {
+            Self::empty()
+        } else {
+            granted
+        }
+    }
+}
+
+/// Every capability of a non-static frame in EVM, that is all of them but `Capability::Sudo`.
+impl Default for CapabilityMask {
+    fn default() -> Self {
+        Self::empty()
+            .with(Capability::WriteStorage)
+            .with(Capability::Log)
+            .with(Capability::Selfdestruct)
+            .with(Capability::TransferValue)
+            .with(Capability::CreateAccounts)
+    }
+}
+
+/// First attempt of an execution to change persistent state, see
+/// `ExecutionStats::mutation_observed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MutationKind {
+    SStore {
+        pc: usize,
+    },
+    Log {
+        pc: usize,
+    },
+    Selfdestruct {
+        pc: usize,
+    },
+    /// CALL or CALLCODE transferring value.
+    ValueCall {
+        pc: usize,
+    },
+    Create {
+        pc: usize,
+    },
+    /// Non-static call without value. The callee may change state itself.
+    OpaqueCall {
+        pc: usize,
+    },
+}
+
+/// What a [`GasCharge`] pays for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GasReason {
+    /// Instruction cost from the instruction table, except for LOG topics.
+    TableBase,
+    MemoryExpansion,
+    /// Per-word cost of CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY and MCOPY.
+    CopyPerWord,
+    KeccakPerWord,
+    ExpPerByte,
+    /// Cold account access (EIP-2929), on top of the warm cost from the table.
+    ColdAccount,
+    /// Cold storage access (EIP-2929), on top of the warm cost.
+    ColdSload,
+    /// SSTORE cost depending on the original and current values.
+    SstoreDynamic,
+    CallValueTransfer,
+    /// Value transfer to, or pre-Spurious Dragon call of, a non-existent account.
+    CallNewAccount,
+    /// Stipend given to the callee of a value transfer. It is credited to the caller,
+    /// so the amount is negative.
+    CallStipendCredit,
+    /// Gas used by the callee of a call, or by init code of a create.
+    CallForwarded,
+    /// SELFDESTRUCT sending value to a non-existent account.
+    SelfdestructNewAccount,
+    LogData,
+    LogTopics,
+    Create2Salt,
+    /// Per-word cost of CREATE and CREATE2 init code (EIP-3860).
+    InitcodePerWord,
+    /// Change of gas left made by a state modifier.
+    StateModifier,
+    /// Change of the refund counter, which does not affect gas left.
+    Refund {
+        source: RefundSource,
+    },
+}
+
+/// Origin of a [`GasReason::Refund`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RefundSource {
+    Sstore,
+    /// Self-destruct before EIP-3529 (London).
+    Selfdestruct,
+    /// Refund counter of a nested call or create.
+    Callee,
+    StateModifier,
+}
+
+/// Where execution has failed, see `ExecutionStats::failure_site`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FailureSite {
+    /// Program counter of the failed instruction.
+    pub pc: usize,
+    /// Gas left before the failed instruction was charged.
+    pub gas_left: i64,
+}
+
+/// Single change of gas left, recorded with the `gas-trace` feature, see
+/// `ExecutionStats::gas_charges`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasCharge {
+    pub pc: usize,
+    pub opcode: OpCode,
+    /// Gas deducted, negative for credits. For [`GasReason::Refund`] this is the amount
+    /// added to the refund counter instead.
+    pub amount: i64,
+    pub reason: GasReason,
+}
+
+/// Account and storage accesses (EIP-2929) of an execution, see `ExecutionStats::accesses`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccessCounts {
+    pub cold_account_accesses: u64,
+    pub warm_account_accesses: u64,
+    pub cold_storage_accesses: u64,
+    pub warm_storage_accesses: u64,
+    /// Gas charged for cold accesses on top of warm access costs. It adds up to the
+    /// `GasReason::ColdAccount` and `GasReason::ColdSload` charges of a gas trace.
+    pub cold_surcharge_gas_total: i64,
+}
+
+/// Statistics collected during execution. Not part of consensus.
+///
+/// Resource maxima cover the executed frame only: nested calls report their own
+/// in the `Output` returned by the host.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExecutionStats {
+    /// A loop has been detected by the loop detector, see `LoopDetectionConfig`.
+    pub loop_detected: bool,
+    /// Execution has been terminated because this host budget was exhausted.
+    pub host_budget_exceeded: Option<HostBudgetExceeded>,
+    /// Deepest stack observed.
+    pub max_stack_depth: usize,
+    /// Largest memory size in bytes.
+    pub max_memory_bytes: usize,
+    /// Largest return data buffer in bytes, as received from nested calls.
+    pub max_return_data_bytes: usize,
+    /// Why contract creation has failed, if it has been executed by a creating executor
+    /// such as `util::deploy`. Several failures share a status code, this tells them apart.
+    pub create_failure: Option<CreateFailure>,
+    /// First request to the host which may change persistent state. Execution is not
+    /// affected, unlike in static mode. Static calls are not reported.
+    pub mutation_observed: Option<MutationKind>,
+    /// Instruction at which execution has failed with `StatusCode::UndefinedInstruction`,
+    /// `InvalidInstruction`, `StackOverflow`, `StackUnderflow` or `BadJumpDestination`.
+    pub failure_site: Option<FailureSite>,
+    /// Output has been truncated, see `ExecutionOptions::truncate_output_on_oog_return`.
+    pub output_truncated: bool,
+    /// Accesses of the executed frame since Berlin, counted when the host reports them
+    /// cold or warm. Accesses of nested calls are not included.
+    pub accesses: AccessCounts,
+    /// Every change of gas left and of the refund counter, in execution order.
+    ///
+    /// Unless execution has failed, non-refund amounts add up to gas used and refund amounts
+    /// add up to the refund counter, which `Output::gas_refund` reports unless execution has
+    /// reverted. Debug builds of the interpreter assert this after every execution.
+    #[cfg(feature = "gas-trace")]
+    pub gas_charges: Vec<GasCharge>,
+}
+
 /// Output of EVM execution.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Output {
@@ -236,10 +646,18 @@ pub struct Output {
     pub status_code: StatusCode,
     /// How much gas was left after execution
     pub gas_left: i64,
+    /// Refund counter accumulated during execution. Always zero unless execution has
+    /// succeeded.
+    pub gas_refund: i64,
     /// Output data returned.
+    ///
+    /// This is a snapshot copied out of memory: later executions of the same code, or with
+    /// the same host, never change it.
     pub output_data: Bytes,
     /// Contract creation address.
     pub create_address: Option<Address>,
+    /// Execution statistics.
+    pub stats: ExecutionStats,
 }
 
 /// EVM execution output if no error has occurred.
@@ -249,6 +667,8 @@ pub struct SuccessfulOutput {
     pub reverted: bool,
     /// How much gas was left after execution.
     pub gas_left: i64,
+    /// Refund counter accumulated during execution. Zero if reverted.
+    pub gas_refund: i64,
     /// Output data returned.
     pub output_data: Bytes,
 }
@@ -258,6 +678,7 @@ impl From<SuccessfulOutput> for Output {
         SuccessfulOutput {
             reverted,
             gas_left,
+            gas_refund,
             output_data,
         }: SuccessfulOutput,
     ) -> Self {
@@ -268,12 +689,48 @@ impl From<SuccessfulOutput> for Output {
                 StatusCode::Success
             },
             gas_left,
+            gas_refund: if reverted { 0 } else { gas_refund },
             output_data,
             create_address: None,
+            stats: ExecutionStats::default(),
+        }
+    }
+}
+
+impl Output {
+    /// Output data if execution has succeeded, otherwise an error.
+    ///
+    /// A revert with an `Error(string)` payload reports its reason, other reverts their raw
+    /// output. Failures report the status code, which can be recovered with
+    /// `anyhow::Error::downcast`.
+    pub fn into_result(self) -> anyhow::Result<Bytes> {
+        match self.status_code {
+            StatusCode::Success => Ok(self.output_data),
+            StatusCode::Revert => Err(match decode_revert_reason(&self.output_data) {
+                Some(reason) => anyhow::anyhow!("execution reverted: {}", reason),
+                None => anyhow::anyhow!("execution reverted: 0x{}", hex::encode(&self.output_data)),
+            }),
+            status => Err(status.into()),
         }
     }
 }
 
+/// Reason of an ABI-encoded `Error(string)` revert.
+fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    const SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+    let data = data.strip_prefix(&SELECTOR)?;
+    let word = |offset: usize| -> Option<usize> {
+        let word = U256::from_big_endian(data.get(offset..offset.checked_add(32)?)?);
+        (word <= usize::MAX.into()).then(|| word.as_usize())
+    };
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    let reason = data.get(start..start.checked_add(len)?)?;
+    String::from_utf8(reason.to_vec()).ok()
+}
+
 pub(crate) fn u256_to_address(v: U256) -> Address {
     H256(v.into()).into()
 }
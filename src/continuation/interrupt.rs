@@ -7,15 +7,16 @@ macro_rules! interrupt {
             pub(crate) inner: ::core::pin::Pin<
                 Box<
                     dyn Coroutine<
-                            Yield = InterruptDataVariant,
+                            Yield = (CallFrame, InterruptDataVariant),
                             Resume = ResumeDataVariant,
-                            Return = Result<SuccessfulOutput, StatusCode>,
+                            Return = (Result<SuccessfulOutput, StatusCode>, ExecutionStats),
                         > + Send
                         + Sync
                         + Unpin,
                 >,
             >,
             pub(crate) data: $data,
+            pub(crate) frame: CallFrame,
         }
 
         impl sealed::Sealed for $name {}
@@ -28,6 +29,10 @@ macro_rules! interrupt {
                 &self.data
             }
 
+            fn frame(&self) -> &CallFrame {
+                &self.frame
+            }
+
             fn resume(self, resume_data: $resume_with) -> InterruptVariant {
                 resume_interrupt(self.inner, resume_data.into())
             }
@@ -60,6 +65,16 @@ interrupt! {
     SetStorageInterrupt,
     SetStorage => StorageStatusInfo
 }
+interrupt! {
+    /// Need this transient storage key.
+    GetTransientStorageInterrupt,
+    GetTransientStorage => TransientStorageValue
+}
+interrupt! {
+    /// Set this transient storage key.
+    SetTransientStorageInterrupt,
+    SetTransientStorage => ()
+}
 interrupt! {
     /// Get balance of this account.
     GetBalanceInterrupt,
@@ -83,7 +98,7 @@ interrupt! {
 interrupt! {
     /// Selfdestruct this account.
     SelfdestructInterrupt,
-    Selfdestruct => ()
+    Selfdestruct => SelfdestructStatus
 }
 interrupt! {
     /// Execute this message as a new call.
@@ -116,6 +131,12 @@ interrupt! {
     AccessStorage => AccessStorageStatus
 }
 
+interrupt! {
+    /// Get a chain-specific context value, see `Host::get_extended_context`.
+    GetExtendedContextInterrupt,
+    GetExtendedContext => ExtendedContext
+}
+
 /// Collection of all possible interrupts. Match on this to get the specific interrupt returned.
 #[derive(From)]
 pub enum InterruptVariant {
@@ -123,6 +144,8 @@ pub enum InterruptVariant {
     AccountExists(AccountExistsInterrupt),
     GetStorage(GetStorageInterrupt),
     SetStorage(SetStorageInterrupt),
+    GetTransientStorage(GetTransientStorageInterrupt),
+    SetTransientStorage(SetTransientStorageInterrupt),
     GetBalance(GetBalanceInterrupt),
     GetCodeSize(GetCodeSizeInterrupt),
     GetCodeHash(GetCodeHashInterrupt),
@@ -134,5 +157,6 @@ pub enum InterruptVariant {
     EmitLog(EmitLogInterrupt),
     AccessAccount(AccessAccountInterrupt),
     AccessStorage(AccessStorageInterrupt),
-    Complete(Result<SuccessfulOutput, StatusCode>),
+    GetExtendedContext(GetExtendedContextInterrupt),
+    Complete(Result<SuccessfulOutput, StatusCode>, ExecutionStats),
 }
@@ -25,6 +25,19 @@ pub struct SetStorage {
     pub value: U256,
 }
 
+#[derive(Debug)]
+pub struct GetTransientStorage {
+    pub address: Address,
+    pub key: U256,
+}
+
+#[derive(Debug)]
+pub struct SetTransientStorage {
+    pub address: Address,
+    pub key: U256,
+    pub value: U256,
+}
+
 #[derive(Debug)]
 pub struct GetBalance {
     pub address: Address,
@@ -82,12 +95,19 @@ pub struct AccessStorage {
     pub key: U256,
 }
 
+#[derive(Debug)]
+pub struct GetExtendedContext {
+    pub key: u32,
+}
+
 #[derive(Debug)]
 pub enum InterruptDataVariant {
     InstructionStart(Box<InstructionStart>),
     AccountExists(AccountExists),
     GetStorage(GetStorage),
     SetStorage(SetStorage),
+    GetTransientStorage(GetTransientStorage),
+    SetTransientStorage(SetTransientStorage),
     GetBalance(GetBalance),
     GetCodeSize(GetCodeSize),
     GetCodeHash(GetCodeHash),
@@ -99,4 +119,5 @@ pub enum InterruptDataVariant {
     EmitLog(EmitLog),
     AccessAccount(AccessAccount),
     AccessStorage(AccessStorage),
+    GetExtendedContext(GetExtendedContext),
 }
@@ -10,7 +10,7 @@ use derive_more::From;
 use enum_as_inner::EnumAsInner;
 use ethereum_types::*;
 use genawaiter::{Coroutine, GeneratorState};
-use std::{convert::Infallible, pin::Pin};
+use std::{convert::Infallible, pin::Pin, sync::Arc};
 
 mod sealed {
     pub trait Sealed {}
@@ -23,6 +23,18 @@ pub mod interrupt_data;
 /// Data required for resume.
 pub mod resume_data;
 
+/// View of the call frame at an interrupt. The message is shared with the paused frame, so
+/// building the view does not copy it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallFrame {
+    /// Message which has started the frame, which also carries its depth.
+    pub message: Arc<Message>,
+    /// Gas left at the time of the interrupt.
+    pub gas_left: i64,
+    /// Program counter of the instruction being executed.
+    pub pc: usize,
+}
+
 /// Paused EVM with full state inside.
 pub trait Interrupt: sealed::Sealed {
     /// Interrupt data returned.
@@ -32,6 +44,8 @@ pub trait Interrupt: sealed::Sealed {
 
     /// Get interrupt data.
     fn data(&self) -> &Self::InterruptData;
+    /// Get the call frame which has been paused.
+    fn frame(&self) -> &CallFrame;
     /// Resume execution until the next interrupt.
     fn resume(self, resume_data: Self::ResumeData) -> InterruptVariant;
 }
@@ -39,9 +53,9 @@ pub trait Interrupt: sealed::Sealed {
 type InnerCoroutine = Pin<
     Box<
         dyn Coroutine<
-                Yield = InterruptDataVariant,
+                Yield = (CallFrame, InterruptDataVariant),
                 Resume = ResumeDataVariant,
-                Return = Result<SuccessfulOutput, StatusCode>,
+                Return = (Result<SuccessfulOutput, StatusCode>, ExecutionStats),
             > + Send
             + Sync
             + Unpin,
@@ -50,35 +64,59 @@ type InnerCoroutine = Pin<
 
 fn resume_interrupt(mut inner: InnerCoroutine, resume_data: ResumeDataVariant) -> InterruptVariant {
     match Pin::new(&mut *inner).resume_with(resume_data) {
-        GeneratorState::Yielded(interrupt) => match interrupt {
+        GeneratorState::Yielded((frame, interrupt)) => match interrupt {
             InterruptDataVariant::InstructionStart(data) => {
-                InstructionStartInterrupt { inner, data }.into()
+                InstructionStartInterrupt { inner, data, frame }.into()
             }
             InterruptDataVariant::AccountExists(data) => {
-                AccountExistsInterrupt { inner, data }.into()
+                AccountExistsInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::GetStorage(data) => {
+                GetStorageInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::SetStorage(data) => {
+                SetStorageInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::GetTransientStorage(data) => {
+                GetTransientStorageInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::SetTransientStorage(data) => {
+                SetTransientStorageInterrupt { inner, data, frame }.into()
             }
-            InterruptDataVariant::GetStorage(data) => GetStorageInterrupt { inner, data }.into(),
-            InterruptDataVariant::SetStorage(data) => SetStorageInterrupt { inner, data }.into(),
-            InterruptDataVariant::GetBalance(data) => GetBalanceInterrupt { inner, data }.into(),
-            InterruptDataVariant::GetCodeSize(data) => GetCodeSizeInterrupt { inner, data }.into(),
-            InterruptDataVariant::GetCodeHash(data) => GetCodeHashInterrupt { inner, data }.into(),
-            InterruptDataVariant::CopyCode(data) => CopyCodeInterrupt { inner, data }.into(),
+            InterruptDataVariant::GetBalance(data) => {
+                GetBalanceInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::GetCodeSize(data) => {
+                GetCodeSizeInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::GetCodeHash(data) => {
+                GetCodeHashInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::CopyCode(data) => CopyCodeInterrupt { inner, data, frame }.into(),
             InterruptDataVariant::Selfdestruct(data) => {
-                SelfdestructInterrupt { inner, data }.into()
+                SelfdestructInterrupt { inner, data, frame }.into()
             }
-            InterruptDataVariant::Call(data) => CallInterrupt { inner, data }.into(),
-            InterruptDataVariant::GetTxContext => GetTxContextInterrupt { inner, data: () }.into(),
+            InterruptDataVariant::Call(data) => CallInterrupt { inner, data, frame }.into(),
+            InterruptDataVariant::GetTxContext => GetTxContextInterrupt {
+                inner,
+                data: (),
+                frame,
+            }
+            .into(),
             InterruptDataVariant::GetBlockHash(data) => {
-                GetBlockHashInterrupt { inner, data }.into()
+                GetBlockHashInterrupt { inner, data, frame }.into()
             }
-            InterruptDataVariant::EmitLog(data) => EmitLogInterrupt { inner, data }.into(),
+            InterruptDataVariant::EmitLog(data) => EmitLogInterrupt { inner, data, frame }.into(),
             InterruptDataVariant::AccessAccount(data) => {
-                AccessAccountInterrupt { inner, data }.into()
+                AccessAccountInterrupt { inner, data, frame }.into()
             }
             InterruptDataVariant::AccessStorage(data) => {
-                AccessStorageInterrupt { inner, data }.into()
+                AccessStorageInterrupt { inner, data, frame }.into()
+            }
+            InterruptDataVariant::GetExtendedContext(data) => {
+                GetExtendedContextInterrupt { inner, data, frame }.into()
             }
         },
-        GeneratorState::Complete(res) => InterruptVariant::Complete(res),
+        GeneratorState::Complete((res, stats)) => InterruptVariant::Complete(res, stats),
     }
 }
@@ -24,6 +24,11 @@ pub struct StorageValue {
     pub value: U256,
 }
 
+#[derive(Debug)]
+pub struct TransientStorageValue {
+    pub value: U256,
+}
+
 #[derive(Debug)]
 pub struct StorageStatusInfo {
     pub status: StorageStatus,
@@ -49,6 +54,12 @@ pub struct Code {
     pub code: Bytes,
 }
 
+#[derive(Debug)]
+pub struct SelfdestructStatus {
+    /// Whether the account has not been self-destructed before in this transaction.
+    pub first: bool,
+}
+
 #[derive(Debug)]
 pub struct CallOutput {
     pub output: Output,
@@ -64,6 +75,12 @@ pub struct AccessStorageStatus {
     pub status: AccessStatus,
 }
 
+#[derive(Debug)]
+pub struct ExtendedContext {
+    /// Value of the key, or the status execution terminates with.
+    pub value: Result<U256, StatusCode>,
+}
+
 /// All resumed data variants.
 #[derive(Educe, EnumAsInner, From)]
 #[educe(Debug)]
@@ -76,13 +93,16 @@ pub(crate) enum ResumeDataVariant {
     CodeSize(CodeSize),
     StorageValue(StorageValue),
     StorageStatusInfo(StorageStatusInfo),
+    TransientStorageValue(TransientStorageValue),
     CodeHash(CodeHash),
     BlockHash(BlockHash),
     TxContextData(TxContextData),
     Code(Code),
+    SelfdestructStatus(SelfdestructStatus),
     CallOutput(CallOutput),
     AccessAccountStatus(AccessAccountStatus),
     AccessStorageStatus(AccessStorageStatus),
+    ExtendedContext(ExtendedContext),
     Done(Infallible),
 }
 
@@ -7,6 +7,17 @@ use ethereum_types::*;
 use evmc_vm::ExecutionResult;
 use std::convert::TryInto;
 
+/// Oldest EVMC ABI version this module is written against. EVMC 10 added `gas_refund` to
+/// `evmc_result` and the extended storage statuses, EVMC 11 added the transient storage host
+/// functions, which are all used here. Building with the `evmc` feature against an older
+/// `evmc-vm` fails with this message instead of a missing method or reporting refunds as zero.
+const MIN_EVMC_ABI_VERSION: i32 = 11;
+
+const _: () = assert!(
+    EVMC_ABI_VERSION as i32 >= MIN_EVMC_ABI_VERSION,
+    "the evmc feature requires evmc-vm with EVMC ABI version 11 or newer"
+);
+
 pub(crate) trait Convert {
     type Into;
 
@@ -50,6 +61,22 @@ impl From<evmc_access_status> for AccessStatus {
     }
 }
 
+impl From<evmc_storage_status> for StorageStatus {
+    fn from(s: evmc_storage_status) -> Self {
+        match s {
+            evmc_storage_status::EVMC_STORAGE_ASSIGNED => Self::ModifiedAgain,
+            evmc_storage_status::EVMC_STORAGE_ADDED => Self::Added,
+            evmc_storage_status::EVMC_STORAGE_DELETED => Self::Deleted,
+            evmc_storage_status::EVMC_STORAGE_MODIFIED => Self::Modified,
+            evmc_storage_status::EVMC_STORAGE_DELETED_ADDED => Self::DeletedAdded,
+            evmc_storage_status::EVMC_STORAGE_MODIFIED_DELETED => Self::ModifiedDeleted,
+            evmc_storage_status::EVMC_STORAGE_DELETED_RESTORED => Self::DeletedRestored,
+            evmc_storage_status::EVMC_STORAGE_ADDED_DELETED => Self::AddedDeleted,
+            evmc_storage_status::EVMC_STORAGE_MODIFIED_RESTORED => Self::ModifiedRestored,
+        }
+    }
+}
+
 impl From<Message> for ExecutionMessage {
     fn from(msg: Message) -> Self {
         let mut create2_salt = evmc_bytes32::default();
@@ -183,18 +210,23 @@ impl<'a> Host for ExecutionContext<'a> {
     }
 
     fn set_storage(&mut self, address: Address, key: U256, value: U256) -> StorageStatus {
-        match ExecutionContext::set_storage(
+        ExecutionContext::set_storage(self, &address.convert(), &key.convert(), &value.convert())
+            .into()
+    }
+
+    fn get_transient_storage(&self, address: Address, key: U256) -> U256 {
+        ExecutionContext::get_transient_storage(self, &address.convert(), &key.convert())
+            .bytes
+            .into()
+    }
+
+    fn set_transient_storage(&mut self, address: Address, key: U256, value: U256) {
+        ExecutionContext::set_transient_storage(
             self,
             &address.convert(),
             &key.convert(),
             &value.convert(),
-        ) {
-            evmc_storage_status::EVMC_STORAGE_UNCHANGED => StorageStatus::Unchanged,
-            evmc_storage_status::EVMC_STORAGE_MODIFIED => StorageStatus::Modified,
-            evmc_storage_status::EVMC_STORAGE_MODIFIED_AGAIN => StorageStatus::ModifiedAgain,
-            evmc_storage_status::EVMC_STORAGE_ADDED => StorageStatus::Added,
-            evmc_storage_status::EVMC_STORAGE_DELETED => StorageStatus::Deleted,
-        }
+        )
     }
 
     fn get_balance(&self, address: Address) -> U256 {
@@ -217,7 +249,7 @@ impl<'a> Host for ExecutionContext<'a> {
         ExecutionContext::copy_code(self, &address.convert(), offset, buffer)
     }
 
-    fn selfdestruct(&mut self, address: Address, beneficiary: Address) {
+    fn selfdestruct(&mut self, address: Address, beneficiary: Address) -> bool {
         ExecutionContext::selfdestruct(self, &address.convert(), &beneficiary.convert())
     }
 
@@ -227,11 +259,13 @@ impl<'a> Host for ExecutionContext<'a> {
         Output {
             status_code: execution_result.status_code().into(),
             gas_left: execution_result.gas_left(),
+            gas_refund: execution_result.gas_refund(),
             output_data: execution_result
                 .output()
                 .map(|v| v.to_vec().into())
                 .unwrap_or_else(Bytes::new),
             create_address: execution_result.create_address().map(|a| a.bytes.into()),
+            stats: Default::default(),
         }
     }
 
@@ -278,9 +312,12 @@ impl<'a> Host for ExecutionContext<'a> {
     }
 }
 
-impl From<evmc_vm::Revision> for Revision {
-    fn from(rev: evmc_vm::Revision) -> Self {
-        match rev {
+/// Fails with revisions newer than the latest implemented one.
+impl TryFrom<evmc_vm::Revision> for Revision {
+    type Error = evmc_vm::Revision;
+
+    fn try_from(rev: evmc_vm::Revision) -> Result<Self, Self::Error> {
+        Ok(match rev {
             evmc_revision::EVMC_FRONTIER => Revision::Frontier,
             evmc_revision::EVMC_HOMESTEAD => Revision::Homestead,
             evmc_revision::EVMC_TANGERINE_WHISTLE => Revision::Tangerine,
@@ -291,8 +328,11 @@ impl From<evmc_vm::Revision> for Revision {
             evmc_revision::EVMC_ISTANBUL => Revision::Istanbul,
             evmc_revision::EVMC_BERLIN => Revision::Berlin,
             evmc_revision::EVMC_LONDON => Revision::London,
+            evmc_revision::EVMC_PARIS => Revision::Paris,
             evmc_revision::EVMC_SHANGHAI => Revision::Shanghai,
-        }
+            evmc_revision::EVMC_CANCUN => Revision::Cancun,
+            evmc_revision::EVMC_PRAGUE => return Err(rev),
+        })
     }
 }
 
@@ -309,7 +349,9 @@ impl From<Revision> for evmc_vm::Revision {
             Revision::Istanbul => evmc_revision::EVMC_ISTANBUL,
             Revision::Berlin => evmc_revision::EVMC_BERLIN,
             Revision::London => evmc_revision::EVMC_LONDON,
+            Revision::Paris => evmc_revision::EVMC_PARIS,
             Revision::Shanghai => evmc_revision::EVMC_SHANGHAI,
+            Revision::Cancun => evmc_revision::EVMC_CANCUN,
         }
     }
 }
@@ -329,6 +371,10 @@ impl EvmcVm for EvmOdin {
         message: &'a ExecutionMessage,
         context: Option<&'a mut ExecutionContext<'a>>,
     ) -> ExecutionResult {
+        let revision = match Revision::try_from(revision) {
+            Ok(revision) => revision,
+            Err(_) => return ExecutionResult::new(evmc_status_code::EVMC_REJECTED, 0, 0, None),
+        };
         let code = AnalyzedCode::analyze(code);
 
         let output = if let Some(context) = context {
@@ -337,7 +383,7 @@ impl EvmcVm for EvmOdin {
                 &mut NoopTracer,
                 None,
                 Message::from_evmc(message),
-                revision.into(),
+                revision,
             )
         } else {
             code.execute(
@@ -345,14 +391,85 @@ impl EvmcVm for EvmOdin {
                 &mut NoopTracer,
                 None,
                 Message::from_evmc(message),
-                revision.into(),
+                revision,
             )
         };
 
+        output.into()
+    }
+}
+
+/// The refund is passed through `evmc_result::gas_refund`, see `MIN_EVMC_ABI_VERSION`.
+impl From<Output> for ExecutionResult {
+    fn from(output: Output) -> Self {
+        // Failures consume all gas, whatever `ExecutionOptions::preserve_gas_on_failure` says.
+        let gas_left = match output.status_code {
+            StatusCode::Success | StatusCode::Revert => output.gas_left,
+            _ => 0,
+        };
         ExecutionResult::new(
-            output.status_code.clone().into(),
-            output.gas_left,
+            output.status_code.into(),
+            gas_left,
+            output.gas_refund,
             (!output.output_data.is_empty()).then(|| &*output.output_data),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execution_result_carries_refund() {
+        let result = ExecutionResult::from(Output {
+            status_code: StatusCode::Success,
+            gas_left: 100,
+            gas_refund: 4800,
+            output_data: Bytes::from_static(b"\x01"),
+            create_address: None,
+            stats: Default::default(),
+        });
+
+        assert_eq!(result.status_code(), evmc_status_code::EVMC_SUCCESS);
+        assert_eq!(result.gas_left(), 100);
+        assert_eq!(result.gas_refund(), 4800);
+        assert_eq!(result.output().map(|v| &v[..]), Some(&[1_u8][..]));
+    }
+
+    #[test]
+    fn failure_consumes_all_gas() {
+        let result = ExecutionResult::from(Output {
+            status_code: StatusCode::InvalidInstruction,
+            gas_left: 91,
+            gas_refund: 0,
+            output_data: Bytes::new(),
+            create_address: None,
+            stats: Default::default(),
+        });
+
+        assert_eq!(
+            result.status_code(),
+            evmc_status_code::EVMC_INVALID_INSTRUCTION
+        );
+        assert_eq!(result.gas_left(), 0);
+    }
+
+    #[test]
+    fn revision_round_trip() {
+        for revision in Revision::iter() {
+            assert_eq!(
+                Revision::try_from(evmc_vm::Revision::from(revision)),
+                Ok(revision)
+            );
+        }
+        assert_eq!(
+            Revision::try_from(evmc_revision::EVMC_PARIS),
+            Ok(Revision::Paris)
+        );
+        assert_eq!(
+            Revision::try_from(evmc_revision::EVMC_PRAGUE),
+            Err(evmc_revision::EVMC_PRAGUE)
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::common::{Message, Output};
+use bytes::Bytes;
 use ethereum_types::*;
 
 /// State access status (EIP-2929).
@@ -26,6 +27,52 @@ pub enum StorageStatus {
     Added,
     /// A storage item has been deleted: X -> 0.
     Deleted,
+    /// A storage item is deleted and then re-added: X -> 0 -> Z.
+    DeletedAdded,
+    /// A modified storage item is deleted: X -> Y -> 0.
+    ModifiedDeleted,
+    /// A deleted storage item is restored to its original value: X -> 0 -> X.
+    DeletedRestored,
+    /// A newly added storage item is deleted: 0 -> Y -> 0.
+    AddedDeleted,
+    /// A modified storage item is restored to its original value: X -> Y -> X.
+    ModifiedRestored,
+}
+
+impl StorageStatus {
+    /// Status of writing `new` to a storage item holding `current`, whose value at the start
+    /// of the transaction was `original`, following EIP-2200.
+    pub(crate) fn of_write(original: U256, current: U256, new: U256) -> Self {
+        if current == new {
+            Self::Unchanged
+        } else if original == current {
+            if original.is_zero() {
+                Self::Added
+            } else if !new.is_zero() {
+                Self::Modified
+            } else {
+                Self::Deleted
+            }
+        } else if !original.is_zero() {
+            if current.is_zero() {
+                if new == original {
+                    Self::DeletedRestored
+                } else {
+                    Self::DeletedAdded
+                }
+            } else if new.is_zero() {
+                Self::ModifiedDeleted
+            } else if new == original {
+                Self::ModifiedRestored
+            } else {
+                Self::ModifiedAgain
+            }
+        } else if new.is_zero() {
+            Self::AddedDeleted
+        } else {
+            Self::ModifiedAgain
+        }
+    }
 }
 
 /// The transaction and block data for execution.
@@ -51,6 +98,23 @@ pub struct TxContext {
     pub block_base_fee: U256,
 }
 
+impl Default for TxContext {
+    /// Empty context on Ethereum mainnet (chain ID 1).
+    fn default() -> Self {
+        Self {
+            tx_gas_price: U256::zero(),
+            tx_origin: Address::zero(),
+            block_coinbase: Address::zero(),
+            block_number: 0,
+            block_timestamp: 0,
+            block_gas_limit: 0,
+            block_difficulty: U256::zero(),
+            chain_id: U256::one(),
+            block_base_fee: U256::zero(),
+        }
+    }
+}
+
 /// Abstraction that exposes host context to EVM.
 pub trait Host {
     /// Check if an account exists.
@@ -61,10 +125,38 @@ pub trait Host {
     fn get_storage(&self, address: Address, key: U256) -> U256;
     /// Set value of a storage key.
     fn set_storage(&mut self, address: Address, key: U256, value: U256) -> StorageStatus;
+    /// Get values of several storage keys of an account in one step.
+    ///
+    /// Hosts serving queries remotely may override this to make a single round trip.
+    fn get_storage_many(&self, address: Address, keys: &[H256]) -> Vec<H256> {
+        keys.iter()
+            .map(|key| {
+                H256(
+                    self.get_storage(address, U256::from_big_endian(&key.0))
+                        .into(),
+                )
+            })
+            .collect()
+    }
+    /// Get value of a transient storage key (EIP-1153).
+    ///
+    /// Returns `U256::zero()` if it has not been set in this transaction.
+    fn get_transient_storage(&self, address: Address, key: U256) -> U256;
+    /// Set value of a transient storage key (EIP-1153), discarded at the end of the transaction.
+    fn set_transient_storage(&mut self, address: Address, key: U256, value: U256);
     /// Get balance of an account.
     ///
     /// Returns `Ok(0)` if account does not exist.
     fn get_balance(&self, address: Address) -> U256;
+    /// Get balances of several accounts in one step.
+    ///
+    /// Hosts serving queries remotely may override this to make a single round trip.
+    fn get_balances(&self, addresses: &[Address]) -> Vec<U256> {
+        addresses
+            .iter()
+            .map(|&address| self.get_balance(address))
+            .collect()
+    }
     /// Get code size of an account.
     ///
     /// Returns `Ok(0)` if account does not exist.
@@ -77,8 +169,19 @@ pub trait Host {
     ///
     /// Returns `Ok(0)` if offset is invalid.
     fn copy_code(&self, address: Address, offset: usize, buffer: &mut [u8]) -> usize;
+    /// Get full code of an account in one step.
+    ///
+    /// Returns empty code if account does not exist.
+    fn get_code(&self, address: Address) -> Bytes {
+        let mut code = vec![0; self.get_code_size(address).as_usize()];
+        let copied = self.copy_code(address, 0, &mut code);
+        code.truncate(copied);
+        code.into()
+    }
     /// Self-destruct account.
-    fn selfdestruct(&mut self, address: Address, beneficiary: Address);
+    ///
+    /// Returns `true` if the account has not been self-destructed before in this transaction.
+    fn selfdestruct(&mut self, address: Address, beneficiary: Address) -> bool;
     /// Call to another account.
     fn call(&mut self, msg: &Message) -> Output;
     /// Retrieve transaction context.
@@ -97,9 +200,22 @@ pub trait Host {
     ///
     /// Returns `Ok(AccessStatus::Cold)` if account does not exist.
     fn access_storage(&mut self, address: Address, key: U256) -> AccessStatus;
+    /// Get a chain-specific context value, such as the L1 block number on an L2.
+    ///
+    /// Keys are defined by the chain. No key is supported by default.
+    ///
+    /// Execution terminates with the returned error if it is a `StatusCode`, and with
+    /// `StatusCode::InternalError` otherwise, see `StatusCode::from_host_error`.
+    fn get_extended_context(&self, key: u32) -> anyhow::Result<U256> {
+        Err(anyhow::anyhow!(
+            "extended context key {} is not supported",
+            key
+        ))
+    }
 }
 
 /// Host that does not support any ops.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct DummyHost;
 
 impl Host for DummyHost {
@@ -115,6 +231,14 @@ impl Host for DummyHost {
         todo!()
     }
 
+    fn get_transient_storage(&self, _: Address, _: U256) -> U256 {
+        todo!()
+    }
+
+    fn set_transient_storage(&mut self, _: Address, _: U256, _: U256) {
+        todo!()
+    }
+
     fn get_balance(&self, _: Address) -> U256 {
         todo!()
     }
@@ -131,7 +255,7 @@ impl Host for DummyHost {
         todo!()
     }
 
-    fn selfdestruct(&mut self, _: Address, _: Address) {
+    fn selfdestruct(&mut self, _: Address, _: Address) -> bool {
         todo!()
     }
 
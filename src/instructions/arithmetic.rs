@@ -1,4 +1,4 @@
-use crate::{state::*, Revision, StatusCode};
+use crate::{instructions::fee::*, state::*, GasReason, Revision, StatusCode};
 use core::convert::TryInto;
 use ethereum_types::{U256, U512};
 use i256::I256;
@@ -109,17 +109,13 @@ pub(crate) fn exp(state: &mut ExecutionState) -> Result<(), StatusCode> {
     let mut power = state.stack.pop();
 
     if !power.is_zero() {
-        let additional_gas = if state.evm_revision >= Revision::Spurious {
-            50
+        let additional_gas = u64::from(if state.evm_revision >= Revision::Spurious {
+            EXP_BYTE_GAS_SPURIOUS
         } else {
-            10
-        } * (log2floor(power) / 8 + 1);
+            EXP_BYTE_GAS
+        }) * (log2floor(power) / 8 + 1);
 
-        state.gas_left -= additional_gas as i64;
-
-        if state.gas_left < 0 {
-            return Err(StatusCode::OutOfGas);
-        }
+        state.use_gas(additional_gas as i64, GasReason::ExpPerByte)?;
     }
 
     let mut v = U256::one();
@@ -1,14 +1,12 @@
-#[doc(hidden)]
-#[macro_export]
 macro_rules! do_call {
-    ($co:expr, $state:expr, $kind:expr, $is_static:expr) => {{
-        use std::cmp::min;
+    ($co:expr, $state:expr, $pc:expr, $kind:expr, $is_static:expr) => {{
+        use std::cmp::{max, min};
         use $crate::{
             common::u256_to_address,
             continuation::{interrupt_data::*, resume_data::*},
             host::AccessStatus,
-            instructions::{memory::MemoryRegion, properties::*},
-            CallKind, Message,
+            instructions::{fee::*, memory::MemoryRegion},
+            CallKind, Capability, Message, MutationKind,
         };
 
         let gas = $state.stack.pop();
@@ -26,20 +24,22 @@ macro_rules! do_call {
 
         $state.stack.push(U256::zero()); // Assume failure.
 
-        if $state.evm_revision >= Revision::Berlin
-            && ResumeDataVariant::into_access_account_status(
-                $co.yield_(InterruptDataVariant::AccessAccount(AccessAccount {
-                    address: dst,
-                }))
+        if $state.evm_revision >= Revision::Berlin {
+            let access_status = ResumeDataVariant::into_access_account_status(
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessAccount(AccessAccount { address: dst }),
+                ))
                 .await,
             )
             .unwrap()
-            .status
-                == AccessStatus::Cold
-        {
-            $state.gas_left -= i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
-            if $state.gas_left < 0 {
-                return Err(StatusCode::OutOfGas);
+            .status;
+            $state.count_account_access(access_status, ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
+            if access_status == AccessStatus::Cold {
+                $state.use_gas(
+                    i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST),
+                    GasReason::ColdAccount,
+                )?;
             }
         }
 
@@ -76,28 +76,33 @@ macro_rules! do_call {
                 .unwrap_or_default(),
         };
 
-        let mut cost = if has_value { 9000 } else { 0 };
+        if has_value {
+            $state.charge_gas(
+                i64::from(CALL_VALUE_TRANSFER_GAS),
+                GasReason::CallValueTransfer,
+            );
+        }
 
         if matches!($kind, CallKind::Call) {
-            if has_value && $state.message.is_static {
-                return Err(StatusCode::StaticModeViolation);
+            if has_value {
+                $state.require_capability(Capability::TransferValue)?;
             }
 
             if (has_value || $state.evm_revision < Revision::Spurious)
                 && !ResumeDataVariant::into_account_exists_status(
-                    $co.yield_(InterruptDataVariant::AccountExists(AccountExists {
-                        address: dst,
-                    }))
+                    $co.yield_((
+                        $state.frame(),
+                        InterruptDataVariant::AccountExists(AccountExists { address: dst }),
+                    ))
                     .await,
                 )
                 .unwrap()
                 .exists
             {
-                cost += 25000;
+                $state.charge_gas(i64::from(NEW_ACCOUNT_GAS), GasReason::CallNewAccount);
             }
         }
-        $state.gas_left -= cost;
-        if $state.gas_left < 0 {
+        if $state.out_of_gas() {
             return Err(StatusCode::OutOfGas);
         }
 
@@ -106,15 +111,20 @@ macro_rules! do_call {
         }
 
         if $state.evm_revision >= Revision::Tangerine {
-            // TODO: Always true for STATICCALL.
+            // EIP-150: all calls, STATICCALL included, get at most 63/64 of the remaining gas.
             msg.gas = min(msg.gas, $state.gas_left - $state.gas_left / 64);
-        } else if msg.gas > $state.gas_left {
+        } else if $state.metering && msg.gas > $state.gas_left {
             return Err(StatusCode::OutOfGas);
         }
+        if !$state.metering {
+            // Gas left may be short of the requested amount, or even negative.
+            msg.gas = max(0, min(msg.gas, $state.gas_left));
+        }
 
+        // STATICCALL and DELEGATECALL never transfer value, so they never get the stipend.
         if has_value {
-            msg.gas += 2300; // Add stipend.
-            $state.gas_left += 2300;
+            msg.gas += i64::from(CALL_STIPEND);
+            $state.credit_gas(i64::from(CALL_STIPEND), GasReason::CallStipendCredit);
         }
 
         $state.return_data.clear();
@@ -122,18 +132,26 @@ macro_rules! do_call {
         if $state.message.depth < 1024
             && !(has_value
                 && ResumeDataVariant::into_balance(
-                    $co.yield_(InterruptDataVariant::GetBalance(GetBalance {
-                        address: $state.message.recipient,
-                    }))
+                    $co.yield_((
+                        $state.frame(),
+                        InterruptDataVariant::GetBalance(GetBalance {
+                            address: $state.message.recipient,
+                        }),
+                    ))
                     .await,
                 )
                 .unwrap()
                 .balance
                     < value)
         {
+            if has_value {
+                $state.observe_mutation(MutationKind::ValueCall { pc: $pc });
+            } else if !msg.is_static {
+                $state.observe_mutation(MutationKind::OpaqueCall { pc: $pc });
+            }
             let msg_gas = msg.gas;
             let result = ResumeDataVariant::into_call_output(
-                $co.yield_(InterruptDataVariant::Call(Call::Call(msg)))
+                $co.yield_(($state.frame(), InterruptDataVariant::Call(Call::Call(msg))))
                     .await,
             )
             .unwrap()
@@ -153,31 +171,41 @@ macro_rules! do_call {
                 }
             }
 
+            // Gas consumed by the callee is charged as soon as the call returns, before the
+            // next instruction is executed. It stays spent even if the caller runs out of gas
+            // right after the call.
             let gas_used = msg_gas - result.gas_left;
-            $state.gas_left -= gas_used;
+            $state.charge_gas(gas_used, GasReason::CallForwarded);
+            // Refunds of a failed callee are reverted together with its state changes.
+            if result.status_code == StatusCode::Success {
+                $state.add_refund(result.gas_refund, RefundSource::Callee);
+            }
         }
     }};
 }
 
-#[doc(hidden)]
-#[macro_export]
+pub(crate) use do_call;
+
 macro_rules! do_create {
-    ($co:expr, $state:expr, $create2:expr) => {{
+    ($co:expr, $state:expr, $pc:expr, $create2:expr) => {{
         use ethereum_types::U256;
         use $crate::{
             common::*,
             continuation::{interrupt_data::*, resume_data::*},
-            CreateMessage,
+            instructions::fee::*,
+            CreateMessage, MAX_INITCODE_SIZE,
         };
 
-        if $state.message.is_static {
-            return Err(StatusCode::StaticModeViolation);
-        }
+        $state.require_capability(Capability::CreateAccounts)?;
 
         let endowment = $state.stack.pop();
         let init_code_offset = $state.stack.pop();
         let init_code_size = $state.stack.pop();
 
+        if $state.evm_revision >= Revision::Shanghai && init_code_size > MAX_INITCODE_SIZE.into() {
+            return Err(StatusCode::OutOfGas);
+        }
+
         let region = memory::verify_memory_region($state, init_code_offset, init_code_size)
             .map_err(|_| StatusCode::OutOfGas)?;
 
@@ -185,11 +213,8 @@ macro_rules! do_create {
             let salt = $state.stack.pop();
 
             if let Some(region) = &region {
-                let salt_cost = memory::num_words(region.size.get()) * 6;
-                $state.gas_left -= salt_cost;
-                if $state.gas_left < 0 {
-                    return Err(StatusCode::OutOfGas);
-                }
+                let salt_cost = memory::num_words(region.size.get()) * i64::from(KECCAK_WORD_GAS);
+                $state.use_gas(salt_cost, GasReason::Create2Salt)?;
             }
 
             Some(salt)
@@ -197,15 +222,26 @@ macro_rules! do_create {
             None
         };
 
+        if $state.evm_revision >= Revision::Shanghai {
+            if let Some(region) = &region {
+                let initcode_cost =
+                    memory::num_words(region.size.get()) * i64::from(INITCODE_WORD_GAS);
+                $state.use_gas(initcode_cost, GasReason::InitcodePerWord)?;
+            }
+        }
+
         $state.stack.push(U256::zero());
         $state.return_data.clear();
 
         if $state.message.depth < 1024
             && !(!endowment.is_zero()
                 && ResumeDataVariant::into_balance(
-                    $co.yield_(InterruptDataVariant::GetBalance(GetBalance {
-                        address: $state.message.recipient,
-                    }))
+                    $co.yield_((
+                        $state.frame(),
+                        InterruptDataVariant::GetBalance(GetBalance {
+                            address: $state.message.recipient,
+                        }),
+                    ))
                     .await,
                 )
                 .unwrap()
@@ -213,11 +249,15 @@ macro_rules! do_create {
                     < endowment)
         {
             let msg = CreateMessage {
-                gas: if $state.evm_revision >= Revision::Tangerine {
-                    $state.gas_left - $state.gas_left / 64
-                } else {
-                    $state.gas_left
-                },
+                // Gas left may be negative without metering.
+                gas: std::cmp::max(
+                    0,
+                    if $state.evm_revision >= Revision::Tangerine {
+                        $state.gas_left - $state.gas_left / 64
+                    } else {
+                        $state.gas_left
+                    },
+                ),
 
                 salt,
                 initcode: if !init_code_size.is_zero() {
@@ -232,14 +272,22 @@ macro_rules! do_create {
                 depth: $state.message.depth + 1,
                 endowment,
             };
+            $state.observe_mutation(MutationKind::Create { pc: $pc });
             let msg_gas = msg.gas;
             let result = ResumeDataVariant::into_call_output(
-                $co.yield_(InterruptDataVariant::Call(Call::Create(msg)))
-                    .await,
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::Call(Call::Create(msg)),
+                ))
+                .await,
             )
             .unwrap()
             .output;
-            $state.gas_left -= msg_gas - result.gas_left;
+            // Same as for calls: gas consumed by init code is charged before the next instruction.
+            $state.charge_gas(msg_gas - result.gas_left, GasReason::CallForwarded);
+            if result.status_code == StatusCode::Success {
+                $state.add_refund(result.gas_refund, RefundSource::Callee);
+            }
 
             $state.return_data = result.output_data;
             if result.status_code == StatusCode::Success {
@@ -249,3 +297,5 @@ macro_rules! do_create {
         }
     }};
 }
+
+pub(crate) use do_create;
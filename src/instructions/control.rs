@@ -1,11 +1,24 @@
 use crate::state::ExecutionState;
 use crate::{interpreter::JumpdestMap, StatusCode};
 use ethereum_types::U256;
+use std::cmp::min;
 
 pub(crate) fn ret(state: &mut ExecutionState) -> Result<(), StatusCode> {
     let offset = *state.stack.get(0);
     let size = *state.stack.get(1);
 
+    if state.truncate_output_on_oog_return
+        && !super::memory::memory_region_affordable(state, offset, size)
+    {
+        // Return what is already in memory without expanding it.
+        let len = U256::from(state.memory.len());
+        let start = min(offset, len).as_usize();
+        let end = min(offset.saturating_add(size), len).as_usize();
+        state.output_data = state.memory[start..end].to_vec().into();
+        state.output_truncated = true;
+        return Ok(());
+    }
+
     if let Some(region) = super::memory::verify_memory_region(state, offset, size)
         .map_err(|_| StatusCode::OutOfGas)?
     {
@@ -13,39 +13,42 @@ pub(crate) fn callvalue(state: &mut ExecutionState) {
     state.stack.push(state.message.value);
 }
 
-#[doc(hidden)]
-#[macro_export]
 macro_rules! balance {
     ($co:expr, $state:expr) => {
         use crate::{
             common::*,
             continuation::{interrupt_data::*, resume_data::*},
             host::*,
-            instructions::properties::*,
+            instructions::fee::*,
         };
 
         let address = u256_to_address($state.stack.pop());
 
         if $state.evm_revision >= Revision::Berlin {
             let access_status = ResumeDataVariant::into_access_account_status(
-                $co.yield_(InterruptDataVariant::AccessAccount(AccessAccount {
-                    address,
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessAccount(AccessAccount { address }),
+                ))
                 .await,
             )
             .unwrap()
             .status;
+            $state.count_account_access(access_status, ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
             if access_status == AccessStatus::Cold {
-                $state.gas_left -= i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
-                if $state.gas_left < 0 {
-                    return Err(StatusCode::OutOfGas);
-                }
+                $state.use_gas(
+                    i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST),
+                    GasReason::ColdAccount,
+                )?;
             }
         }
 
         let balance = ResumeDataVariant::into_balance(
-            $co.yield_(InterruptDataVariant::GetBalance(GetBalance { address }))
-                .await,
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::GetBalance(GetBalance { address }),
+            ))
+            .await,
         )
         .unwrap()
         .balance;
@@ -54,39 +57,44 @@ macro_rules! balance {
     };
 }
 
-#[doc(hidden)]
-#[macro_export]
+pub(crate) use balance;
+
 macro_rules! extcodesize {
     ($co:expr, $state:expr) => {
         use crate::{
             common::*,
             continuation::{interrupt_data::*, resume_data::*},
             host::*,
-            instructions::properties::*,
+            instructions::fee::*,
         };
 
         let address = u256_to_address($state.stack.pop());
 
         if $state.evm_revision >= Revision::Berlin {
             let access_account = ResumeDataVariant::into_access_account_status(
-                $co.yield_(InterruptDataVariant::AccessAccount(AccessAccount {
-                    address,
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessAccount(AccessAccount { address }),
+                ))
                 .await,
             )
             .unwrap()
             .status;
+            $state.count_account_access(access_account, ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
             if access_account == AccessStatus::Cold {
-                $state.gas_left -= i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
-                if $state.gas_left < 0 {
-                    return Err(StatusCode::OutOfGas);
-                }
+                $state.use_gas(
+                    i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST),
+                    GasReason::ColdAccount,
+                )?;
             }
         }
 
         let code_size = ResumeDataVariant::into_code_size(
-            $co.yield_(InterruptDataVariant::GetCodeSize(GetCodeSize { address }))
-                .await,
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::GetCodeSize(GetCodeSize { address }),
+            ))
+            .await,
         )
         .unwrap()
         .code_size;
@@ -94,14 +102,15 @@ macro_rules! extcodesize {
     };
 }
 
-#[doc(hidden)]
-#[macro_export]
+pub(crate) use extcodesize;
+
 macro_rules! push_txcontext {
     ($co:expr, $state:expr, $accessor:expr) => {
         use $crate::continuation::{interrupt_data::*, resume_data::*};
 
         let tx_context = ResumeDataVariant::into_tx_context_data(
-            $co.yield_(InterruptDataVariant::GetTxContext).await,
+            $co.yield_(($state.frame(), InterruptDataVariant::GetTxContext))
+                .await,
         )
         .unwrap()
         .context;
@@ -110,6 +119,8 @@ macro_rules! push_txcontext {
     };
 }
 
+pub(crate) use push_txcontext;
+
 pub(crate) fn origin_accessor(tx_context: TxContext) -> U256 {
     address_to_u256(tx_context.tx_origin)
 }
@@ -146,16 +157,17 @@ pub(crate) fn basefee_accessor(tx_context: TxContext) -> U256 {
     tx_context.block_base_fee
 }
 
-#[doc(hidden)]
-#[macro_export]
 macro_rules! selfbalance {
     ($co:expr, $state:expr) => {{
         use $crate::continuation::{interrupt_data::*, resume_data::*};
 
         let balance = ResumeDataVariant::into_balance(
-            $co.yield_(InterruptDataVariant::GetBalance(GetBalance {
-                address: $state.message.recipient,
-            }))
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::GetBalance(GetBalance {
+                    address: $state.message.recipient,
+                }),
+            ))
             .await,
         )
         .unwrap()
@@ -165,8 +177,8 @@ macro_rules! selfbalance {
     }};
 }
 
-#[doc(hidden)]
-#[macro_export]
+pub(crate) use selfbalance;
+
 macro_rules! blockhash {
     ($co:expr, $state:expr) => {
         use $crate::continuation::{interrupt_data::*, resume_data::*};
@@ -174,7 +186,8 @@ macro_rules! blockhash {
         let number = $state.stack.pop();
 
         let upper_bound = ResumeDataVariant::into_tx_context_data(
-            $co.yield_(InterruptDataVariant::GetTxContext).await,
+            $co.yield_(($state.frame(), InterruptDataVariant::GetTxContext))
+                .await,
         )
         .unwrap()
         .context
@@ -186,9 +199,10 @@ macro_rules! blockhash {
             let n = number.as_u64();
             if (lower_bound..upper_bound).contains(&n) {
                 header = ResumeDataVariant::into_block_hash(
-                    $co.yield_(InterruptDataVariant::GetBlockHash(GetBlockHash {
-                        block_number: n,
-                    }))
+                    $co.yield_((
+                        $state.frame(),
+                        InterruptDataVariant::GetBlockHash(GetBlockHash { block_number: n }),
+                    ))
                     .await,
                 )
                 .unwrap()
@@ -200,17 +214,46 @@ macro_rules! blockhash {
     };
 }
 
-#[doc(hidden)]
-#[macro_export]
-macro_rules! do_log {
-    ($co:expr, $state:expr, $num_topics:expr) => {{
-        use arrayvec::ArrayVec;
+pub(crate) use blockhash;
+
+macro_rules! extended_context {
+    ($co:expr, $state:expr) => {
         use $crate::continuation::{interrupt_data::*, resume_data::*};
 
-        if $state.message.is_static {
-            return Err(StatusCode::StaticModeViolation);
+        let key = $state.stack.pop();
+        if key > u32::MAX.into() {
+            return Err(StatusCode::InternalError(format!(
+                "extended context key {} is out of range",
+                key
+            )));
         }
 
+        let value = ResumeDataVariant::into_extended_context(
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::GetExtendedContext(GetExtendedContext { key: key.as_u32() }),
+            ))
+            .await,
+        )
+        .unwrap()
+        .value?;
+
+        $state.stack.push(value);
+    };
+}
+
+pub(crate) use extended_context;
+
+macro_rules! do_log {
+    ($co:expr, $state:expr, $pc:expr, $num_topics:expr) => {{
+        use arrayvec::ArrayVec;
+        use $crate::{
+            continuation::{interrupt_data::*, resume_data::*},
+            instructions::fee::*,
+        };
+
+        $state.require_capability(Capability::Log)?;
+
         let offset = $state.stack.pop();
         let size = $state.stack.pop();
 
@@ -218,11 +261,8 @@ macro_rules! do_log {
             memory::verify_memory_region($state, offset, size).map_err(|_| StatusCode::OutOfGas)?;
 
         if let Some(region) = &region {
-            let cost = region.size.get() as i64 * 8;
-            $state.gas_left -= cost;
-            if cost < 0 {
-                return Err(StatusCode::OutOfGas);
-            }
+            let cost = region.size.get() as i64 * i64::from(LOG_DATA_BYTE_GAS);
+            $state.use_gas(cost, GasReason::LogData)?;
         }
 
         let mut topics = ArrayVec::new();
@@ -235,56 +275,63 @@ macro_rules! do_log {
         } else {
             &[]
         };
+        $state.observe_mutation(MutationKind::Log { pc: $pc });
         let r = $co
-            .yield_(InterruptDataVariant::EmitLog(EmitLog {
-                address: $state.message.recipient,
-                data: data.to_vec().into(),
-                topics,
-            }))
+            .yield_((
+                $state.frame(),
+                InterruptDataVariant::EmitLog(EmitLog {
+                    address: $state.message.recipient,
+                    data: data.to_vec().into(),
+                    topics,
+                }),
+            ))
             .await;
 
         assert!(matches!(r, ResumeDataVariant::Empty));
     }};
 }
 
-#[doc(hidden)]
-#[macro_export]
+pub(crate) use do_log;
+
 macro_rules! sload {
     ($co:expr, $state:expr) => {{
         use $crate::{
             continuation::{interrupt_data::*, resume_data::*},
             host::*,
-            instructions::properties::{COLD_SLOAD_COST, WARM_STORAGE_READ_COST},
+            instructions::fee::ADDITIONAL_COLD_SLOAD_COST,
         };
 
         let key = $state.stack.pop();
 
         if $state.evm_revision >= Revision::Berlin {
             let access_status = ResumeDataVariant::into_access_storage_status(
-                $co.yield_(InterruptDataVariant::AccessStorage(AccessStorage {
-                    address: $state.message.recipient,
-                    key,
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessStorage(AccessStorage {
+                        address: $state.message.recipient,
+                        key,
+                    }),
+                ))
                 .await,
             )
             .unwrap()
             .status;
+            $state.count_storage_access(access_status, ADDITIONAL_COLD_SLOAD_COST);
             if access_status == AccessStatus::Cold {
                 // The warm storage access cost is already applied (from the cost table).
                 // Here we need to apply additional cold storage access cost.
-                const ADDITIONAL_COLD_SLOAD_COST: u16 = COLD_SLOAD_COST - WARM_STORAGE_READ_COST;
-                $state.gas_left -= i64::from(ADDITIONAL_COLD_SLOAD_COST);
-                if $state.gas_left < 0 {
-                    return Err(StatusCode::OutOfGas);
-                }
+                $state.use_gas(i64::from(ADDITIONAL_COLD_SLOAD_COST), GasReason::ColdSload)?;
             }
         }
 
         let storage = ResumeDataVariant::into_storage_value(
-            $co.yield_(InterruptDataVariant::GetStorage(GetStorage {
-                address: $state.message.recipient,
-                key,
-            }))
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::GetStorage(GetStorage {
+                    address: $state.message.recipient,
+                    key,
+                }),
+            ))
             .await,
         )
         .unwrap()
@@ -294,114 +341,205 @@ macro_rules! sload {
     }};
 }
 
-#[doc(hidden)]
-#[macro_export]
+pub(crate) use sload;
+
 macro_rules! sstore {
-    ($co:expr, $state:expr) => {{
+    ($co:expr, $state:expr, $pc:expr) => {{
         use $crate::{
             continuation::{interrupt_data::*, resume_data::*},
             host::*,
-            instructions::properties::{COLD_SLOAD_COST, WARM_STORAGE_READ_COST},
+            instructions::fee::*,
         };
 
-        if $state.message.is_static {
-            return Err(StatusCode::StaticModeViolation);
-        }
+        $state.require_capability(Capability::WriteStorage)?;
 
-        if $state.evm_revision >= Revision::Istanbul && $state.gas_left <= 2300 {
+        if $state.metering
+            && $state.evm_revision >= Revision::Istanbul
+            && $state.gas_left <= i64::from(CALL_STIPEND)
+        {
             return Err(StatusCode::OutOfGas);
         }
 
         let key = $state.stack.pop();
         let value = $state.stack.pop();
 
-        let mut cost = 0;
         if $state.evm_revision >= Revision::Berlin {
             let access_status = ResumeDataVariant::into_access_storage_status(
-                $co.yield_(InterruptDataVariant::AccessStorage(AccessStorage {
-                    address: $state.message.recipient,
-                    key,
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessStorage(AccessStorage {
+                        address: $state.message.recipient,
+                        key,
+                    }),
+                ))
                 .await,
             )
             .unwrap()
             .status;
 
+            $state.count_storage_access(access_status, COLD_SLOAD_COST);
             if access_status == AccessStatus::Cold {
-                cost = COLD_SLOAD_COST;
+                $state.charge_gas(i64::from(COLD_SLOAD_COST), GasReason::ColdSload);
             }
         }
 
+        $state.observe_mutation(MutationKind::SStore { pc: $pc });
         let status = ResumeDataVariant::into_storage_status_info(
-            $co.yield_(InterruptDataVariant::SetStorage(SetStorage {
-                address: $state.message.recipient,
-                key,
-                value,
-            }))
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::SetStorage(SetStorage {
+                    address: $state.message.recipient,
+                    key,
+                    value,
+                }),
+            ))
             .await,
         )
         .unwrap()
         .status;
 
-        cost = match status {
-            StorageStatus::Unchanged | StorageStatus::ModifiedAgain => {
-                if $state.evm_revision >= Revision::Berlin {
-                    cost + WARM_STORAGE_READ_COST
-                } else if $state.evm_revision == Revision::Istanbul {
-                    800
-                } else if $state.evm_revision == Revision::Constantinople {
-                    200
-                } else {
-                    5000
+        // Net gas metering: EIP-1283, EIP-2200, EIP-2929 and EIP-3529.
+        let net_metering = $state.evm_revision.sstore_gas_metering();
+        let sload_cost = if $state.evm_revision >= Revision::Berlin {
+            WARM_STORAGE_READ_COST
+        } else if $state.evm_revision == Revision::Istanbul {
+            SLOAD_GAS_ISTANBUL
+        } else {
+            SLOAD_GAS_TANGERINE
+        };
+        let set_cost = SSTORE_SET_GAS;
+        let reset_cost = if $state.evm_revision >= Revision::Berlin {
+            SSTORE_RESET_GAS - COLD_SLOAD_COST
+        } else {
+            SSTORE_RESET_GAS
+        };
+        let clear_refund = i64::from(if $state.evm_revision >= Revision::London {
+            SSTORE_CLEARS_REFUND_LONDON
+        } else {
+            SSTORE_CLEARS_REFUND
+        });
+
+        let (status_cost, refund) = if net_metering {
+            match status {
+                StorageStatus::Unchanged | StorageStatus::ModifiedAgain => (sload_cost, 0),
+                StorageStatus::Modified => (reset_cost, 0),
+                StorageStatus::Deleted => (reset_cost, clear_refund),
+                StorageStatus::Added => (set_cost, 0),
+                StorageStatus::DeletedAdded => (sload_cost, -clear_refund),
+                StorageStatus::ModifiedDeleted => (sload_cost, clear_refund),
+                StorageStatus::DeletedRestored => (
+                    sload_cost,
+                    i64::from(reset_cost) - i64::from(sload_cost) - clear_refund,
+                ),
+                StorageStatus::AddedDeleted => {
+                    (sload_cost, i64::from(set_cost) - i64::from(sload_cost))
+                }
+                StorageStatus::ModifiedRestored => {
+                    (sload_cost, i64::from(reset_cost) - i64::from(sload_cost))
                 }
             }
-            StorageStatus::Modified | StorageStatus::Deleted => {
-                if $state.evm_revision >= Revision::Berlin {
-                    cost + 5000 - COLD_SLOAD_COST
-                } else {
-                    5000
+        } else {
+            match status {
+                StorageStatus::Added
+                | StorageStatus::DeletedAdded
+                | StorageStatus::DeletedRestored => (set_cost, 0),
+                StorageStatus::Deleted
+                | StorageStatus::ModifiedDeleted
+                | StorageStatus::AddedDeleted => {
+                    (SSTORE_RESET_GAS, i64::from(SSTORE_CLEARS_REFUND))
                 }
+                StorageStatus::Unchanged
+                | StorageStatus::ModifiedAgain
+                | StorageStatus::Modified
+                | StorageStatus::ModifiedRestored => (SSTORE_RESET_GAS, 0),
             }
-            StorageStatus::Added => cost + 20000,
         };
-        $state.gas_left -= i64::from(cost);
-        if $state.gas_left < 0 {
-            return Err(StatusCode::OutOfGas);
-        }
+        $state.add_refund(refund, RefundSource::Sstore);
+        $state.use_gas(i64::from(status_cost), GasReason::SstoreDynamic)?;
     }};
 }
 
-#[doc(hidden)]
-#[macro_export]
-macro_rules! selfdestruct {
+pub(crate) use sstore;
+
+macro_rules! tload {
+    ($co:expr, $state:expr) => {{
+        use $crate::continuation::{interrupt_data::*, resume_data::*};
+
+        let key = $state.stack.pop();
+
+        let value = ResumeDataVariant::into_transient_storage_value(
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::GetTransientStorage(GetTransientStorage {
+                    address: $state.message.recipient,
+                    key,
+                }),
+            ))
+            .await,
+        )
+        .unwrap()
+        .value;
+
+        $state.stack.push(value);
+    }};
+}
+
+pub(crate) use tload;
+
+macro_rules! tstore {
     ($co:expr, $state:expr) => {{
+        use $crate::continuation::{interrupt_data::*, resume_data::*};
+
+        $state.require_capability(Capability::WriteStorage)?;
+
+        let key = $state.stack.pop();
+        let value = $state.stack.pop();
+
+        assert!(matches!(
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::SetTransientStorage(SetTransientStorage {
+                    address: $state.message.recipient,
+                    key,
+                    value,
+                }),
+            ))
+            .await,
+            ResumeDataVariant::Empty
+        ));
+    }};
+}
+
+pub(crate) use tstore;
+
+macro_rules! selfdestruct {
+    ($co:expr, $state:expr, $pc:expr) => {{
         use crate::{
             common::*,
             continuation::{interrupt_data::*, resume_data::*},
             host::*,
-            instructions::properties::*,
+            instructions::fee::*,
         };
 
-        if $state.message.is_static {
-            return Err(StatusCode::StaticModeViolation);
-        }
+        $state.require_capability(Capability::Selfdestruct)?;
 
         let beneficiary = u256_to_address($state.stack.pop());
 
         if $state.evm_revision >= Revision::Berlin {
             let access_status = ResumeDataVariant::into_access_account_status(
-                $co.yield_(InterruptDataVariant::AccessAccount(AccessAccount {
-                    address: beneficiary,
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessAccount(AccessAccount {
+                        address: beneficiary,
+                    }),
+                ))
                 .await,
             )
             .unwrap()
             .status;
+            $state.count_account_access(access_status, COLD_ACCOUNT_ACCESS_COST);
             if access_status == AccessStatus::Cold {
-                $state.gas_left -= i64::from(COLD_ACCOUNT_ACCESS_COST);
-                if $state.gas_left < 0 {
-                    return Err(StatusCode::OutOfGas);
-                }
+                $state.use_gas(i64::from(COLD_ACCOUNT_ACCESS_COST), GasReason::ColdAccount)?;
             }
         }
 
@@ -409,9 +547,12 @@ macro_rules! selfdestruct {
             && ($state.evm_revision == Revision::Tangerine
                 || !{
                     ResumeDataVariant::into_balance(
-                        $co.yield_(InterruptDataVariant::GetBalance(GetBalance {
-                            address: $state.message.recipient,
-                        }))
+                        $co.yield_((
+                            $state.frame(),
+                            InterruptDataVariant::GetBalance(GetBalance {
+                                address: $state.message.recipient,
+                            }),
+                        ))
                         .await,
                     )
                     .unwrap()
@@ -422,32 +563,45 @@ macro_rules! selfdestruct {
             // After TANGERINE_WHISTLE apply additional cost of
             // sending value to a non-existing account.
             if !ResumeDataVariant::into_account_exists_status(
-                $co.yield_(InterruptDataVariant::AccountExists(AccountExists {
-                    address: beneficiary,
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccountExists(AccountExists {
+                        address: beneficiary,
+                    }),
+                ))
                 .await,
             )
             .unwrap()
             .exists
             {
-                $state.gas_left -= 25000;
-                if $state.gas_left < 0 {
-                    return Err(StatusCode::OutOfGas);
-                }
+                $state.use_gas(
+                    i64::from(NEW_ACCOUNT_GAS),
+                    GasReason::SelfdestructNewAccount,
+                )?;
             }
         }
 
-        assert!(matches!(
-            $co.yield_(InterruptDataVariant::Selfdestruct(Selfdestruct {
-                address: $state.message.recipient,
-                beneficiary,
-            }))
+        $state.observe_mutation(MutationKind::Selfdestruct { pc: $pc });
+        let first = ResumeDataVariant::into_selfdestruct_status(
+            $co.yield_((
+                $state.frame(),
+                InterruptDataVariant::Selfdestruct(Selfdestruct {
+                    address: $state.message.recipient,
+                    beneficiary,
+                }),
+            ))
             .await,
-            ResumeDataVariant::Empty
-        ));
+        )
+        .unwrap()
+        .first;
+        if first && $state.evm_revision < Revision::London {
+            $state.add_refund(i64::from(SELFDESTRUCT_REFUND), RefundSource::Selfdestruct);
+        }
     }};
 }
 
+pub(crate) use selfdestruct;
+
 #[cfg(test)]
 mod tests {
     use crate::common::u256_to_address;
@@ -0,0 +1,119 @@
+//! Gas parameters of the protocol which are not part of the per-revision instruction tables.
+//!
+//! Where a parameter has changed, the constant is suffixed with the revision which has
+//! introduced its value.
+
+/// Cost of a storage read before EIP-1884 (since Tangerine Whistle).
+pub const SLOAD_GAS_TANGERINE: u16 = 200;
+/// Cost of a storage read after EIP-1884 (Istanbul).
+pub const SLOAD_GAS_ISTANBUL: u16 = 800;
+
+/// Cost of a cold storage read (EIP-2929).
+pub const COLD_SLOAD_COST: u16 = 2100;
+/// Cost of a cold account access (EIP-2929).
+pub const COLD_ACCOUNT_ACCESS_COST: u16 = 2600;
+/// Cost of a warm storage read or account access (EIP-2929).
+pub const WARM_STORAGE_READ_COST: u16 = 100;
+
+/// Additional cold account access cost.
+///
+/// The warm access cost is unconditionally applied for every account access instruction.
+/// If the access turns out to be cold, this cost must be applied additionally.
+pub const ADDITIONAL_COLD_ACCOUNT_ACCESS_COST: u16 =
+    COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST;
+/// Additional cold storage read cost, on top of the warm cost charged by the table.
+pub const ADDITIONAL_COLD_SLOAD_COST: u16 = COLD_SLOAD_COST - WARM_STORAGE_READ_COST;
+
+/// Cost of setting a storage slot from zero to non-zero.
+pub const SSTORE_SET_GAS: u16 = 20000;
+/// Cost of changing a non-zero storage slot, including the cold read since Berlin.
+pub const SSTORE_RESET_GAS: u16 = 5000;
+/// Refund for clearing a storage slot.
+pub const SSTORE_CLEARS_REFUND: u16 = 15000;
+/// Cost of an access list storage key (EIP-2930), which the London clearing refund is made of.
+pub const ACCESS_LIST_STORAGE_KEY_COST: u16 = 1900;
+/// Refund for clearing a storage slot since EIP-3529 (London).
+pub const SSTORE_CLEARS_REFUND_LONDON: u16 =
+    SSTORE_RESET_GAS - COLD_SLOAD_COST + ACCESS_LIST_STORAGE_KEY_COST;
+
+/// Refund for a self-destruct before EIP-3529 (London), credited once per account and
+/// transaction as reported by `Host::selfdestruct`.
+pub const SELFDESTRUCT_REFUND: u16 = 24000;
+
+/// Additional cost of a call which transfers value.
+pub const CALL_VALUE_TRANSFER_GAS: u16 = 9000;
+/// Additional cost of a call or a self-destruct which creates an account.
+pub const NEW_ACCOUNT_GAS: u16 = 25000;
+/// Gas given to the callee for free when value is transferred. It is also the least gas left
+/// which SSTORE needs since EIP-2200 (Istanbul).
+pub const CALL_STIPEND: u16 = 2300;
+/// Base cost of CREATE and CREATE2.
+pub const CREATE_GAS: u16 = 32000;
+/// Cost of every byte of code deployed by a contract creation.
+pub const CODE_DEPOSIT_COST: u16 = 200;
+
+/// Base cost of LOG0.
+pub const LOG_GAS: u16 = 375;
+/// Cost of every LOG topic.
+pub const LOG_TOPIC_GAS: u16 = 375;
+/// Cost of every byte of LOG data.
+pub const LOG_DATA_BYTE_GAS: u16 = 8;
+
+/// Cost of every word hashed by KECCAK256, and by CREATE2 when deriving the address.
+pub const KECCAK_WORD_GAS: u16 = 6;
+/// Cost of every word of CREATE and CREATE2 init code since EIP-3860 (Shanghai).
+pub const INITCODE_WORD_GAS: u16 = 2;
+/// Cost of every word copied by `*COPY` instructions.
+pub const COPY_WORD_GAS: u16 = 3;
+/// Linear coefficient of the memory expansion cost.
+pub const MEMORY_WORD_GAS: u16 = 3;
+/// Divisor of the quadratic memory expansion cost.
+pub const MEMORY_QUAD_COEFF_DIV: u16 = 512;
+
+/// Cost of the opcode set in `ExecutionOptions::extended_context_opcode`, the same as for
+/// block context instructions.
+pub const EXTENDED_CONTEXT_GAS: u16 = 2;
+
+/// Cost of every byte of EXP exponent.
+pub const EXP_BYTE_GAS: u16 = 10;
+/// Cost of every byte of EXP exponent since EIP-160 (Spurious Dragon).
+pub const EXP_BYTE_GAS_SPURIOUS: u16 = 50;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{instructions::properties::gas_costs, OpCode, Revision};
+
+    fn cost(revision: Revision, op: OpCode) -> u16 {
+        gas_costs(revision)[op.to_usize()].unwrap()
+    }
+
+    #[test]
+    fn match_instruction_tables() {
+        for revision in Revision::iter() {
+            assert_eq!(cost(revision, OpCode::CREATE), CREATE_GAS);
+            for topics in 0..=4 {
+                assert_eq!(
+                    cost(revision, OpCode(OpCode::LOG0.to_u8() + topics)),
+                    LOG_GAS + u16::from(topics) * LOG_TOPIC_GAS
+                );
+            }
+
+            let sload = cost(revision, OpCode::SLOAD);
+            if revision >= Revision::Berlin {
+                assert_eq!(sload, WARM_STORAGE_READ_COST);
+            } else if revision == Revision::Istanbul {
+                assert_eq!(sload, SLOAD_GAS_ISTANBUL);
+            } else if revision >= Revision::Tangerine {
+                assert_eq!(sload, SLOAD_GAS_TANGERINE);
+            }
+        }
+    }
+
+    #[test]
+    fn derived_values() {
+        assert_eq!(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST, 2500);
+        assert_eq!(ADDITIONAL_COLD_SLOAD_COST, 2000);
+        assert_eq!(SSTORE_CLEARS_REFUND_LONDON, 4800);
+    }
+}
@@ -1,14 +1,20 @@
 use crate::{instructions::properties, Revision};
 use once_cell::sync::Lazy;
 
+/// Static requirements of an instruction checked before its execution.
 #[derive(Clone, Copy, Debug)]
 pub struct InstructionTableEntry {
+    /// Base gas cost charged before execution.
     pub gas_cost: u16,
+    /// Minimum stack height required.
     pub stack_height_required: u8,
+    /// Whether the instruction grows the stack.
     pub can_overflow_stack: bool,
 }
 
+/// Instruction table for a single revision, indexed by opcode. `None` marks undefined instructions.
 pub type InstructionTable = [Option<InstructionTableEntry>; 256];
+/// Instruction tables for all revisions, indexed by `Revision`.
 pub type InstructionTables = [InstructionTable; Revision::len()];
 
 pub static INSTRUCTION_TABLES: Lazy<InstructionTables> = Lazy::new(|| {
@@ -37,6 +43,7 @@ pub static INSTRUCTION_TABLES: Lazy<InstructionTables> = Lazy::new(|| {
     table
 });
 
+/// Get the instruction table used by the interpreter for the given revision.
 pub fn get_baseline_instruction_table(revision: Revision) -> &'static InstructionTable {
     &INSTRUCTION_TABLES[revision as usize]
 }
@@ -1,6 +1,5 @@
-use crate::{common::*, state::*};
+use crate::{common::*, instructions::fee::*, state::*};
 use ethereum_types::U256;
-use sha3::{Digest, Keccak256};
 use std::{cmp::min, num::NonZeroUsize};
 
 pub(crate) const MAX_BUFFER_SIZE: u32 = u32::MAX;
@@ -14,6 +13,17 @@ pub(crate) fn num_words(size_in_bytes: usize) -> i64 {
     ((size_in_bytes as i64) + (WORD_SIZE - 1)) / WORD_SIZE
 }
 
+/// Gas cost of expanding memory from `current_words` to `new_words`: the difference of
+/// `3w + w²/512` at both sizes, each rounded down separately.
+///
+/// Memory is limited to `MAX_BUFFER_SIZE` bytes, so the result always fits into `i64`.
+pub(crate) fn memory_expansion_cost(current_words: i64, new_words: i64) -> i64 {
+    let cost = |words: i64| {
+        i64::from(MEMORY_WORD_GAS) * words + words * words / i64::from(MEMORY_QUAD_COEFF_DIV)
+    };
+    cost(new_words) - cost(current_words)
+}
+
 pub(crate) fn mload(state: &mut ExecutionState) -> Result<(), StatusCode> {
     let index = state.stack.pop();
 
@@ -73,20 +83,17 @@ pub(crate) fn verify_memory_region_u64(
     let current_size = state.memory.len();
     if new_size > current_size {
         let new_words = num_words(new_size);
-        let current_words = (current_size / 32) as i64;
-        let new_cost = 3 * new_words + new_words * new_words / 512;
-        let current_cost = 3 * current_words + current_words * current_words / 512;
-        let cost = new_cost - current_cost;
+        let current_words = num_words(current_size);
+        let cost = memory_expansion_cost(current_words, new_words);
 
-        state.gas_left -= cost;
-
-        if state.gas_left < 0 {
-            return Err(());
-        }
+        state
+            .use_gas(cost, GasReason::MemoryExpansion)
+            .map_err(|_| ())?;
 
         state
             .memory
             .resize((new_words * WORD_SIZE) as usize, Default::default());
+        debug_assert_eq!(state.memory.len() % WORD_SIZE as usize, 0);
     }
 
     Ok(MemoryRegion {
@@ -116,6 +123,48 @@ pub(crate) fn verify_memory_region(
     verify_memory_region_u64(state, offset, NonZeroUsize::new(size.as_usize()).unwrap()).map(Some)
 }
 
+/// Whether `verify_memory_region` would succeed without running out of gas. Nothing is charged.
+pub(crate) fn memory_region_affordable(state: &ExecutionState, offset: U256, size: U256) -> bool {
+    if size.is_zero() {
+        return true;
+    }
+
+    if offset > U256::from(MAX_BUFFER_SIZE) || size > U256::from(MAX_BUFFER_SIZE) {
+        return false;
+    }
+
+    let new_size = offset.as_usize() + size.as_usize();
+    let current_size = state.memory.len();
+    new_size <= current_size
+        || !state.metering
+        || memory_expansion_cost(num_words(current_size), num_words(new_size)) <= state.gas_left
+}
+
+/// Copy `src_data` starting at `src_offset` into the memory region.
+///
+/// The source offset is clamped to the source length, so any part of the region
+/// not covered by the source data is zero-filled.
+pub(crate) fn copy_into_memory(
+    memory: &mut [u8],
+    region: &MemoryRegion,
+    src_data: &[u8],
+    src_offset: U256,
+) {
+    let src_len = src_data.len() as u64;
+    let src = if src_offset > U256::from(src_len) {
+        src_len
+    } else {
+        src_offset.as_u64()
+    } as usize;
+
+    let dst = &mut memory[region.offset..region.offset + region.size.get()];
+    let copy_size = min(dst.len(), src_data.len() - src);
+
+    let (copied, rest) = dst.split_at_mut(copy_size);
+    copied.copy_from_slice(&src_data[src..src + copy_size]);
+    rest.fill(0);
+}
+
 pub(crate) fn calldatacopy(state: &mut ExecutionState) -> Result<(), StatusCode> {
     let mem_index = state.stack.pop();
     let input_index = state.stack.pop();
@@ -124,25 +173,36 @@ pub(crate) fn calldatacopy(state: &mut ExecutionState) -> Result<(), StatusCode>
     let region = verify_memory_region(state, mem_index, size).map_err(|_| StatusCode::OutOfGas)?;
 
     if let Some(region) = &region {
-        let copy_cost = num_words(region.size.get()) * 3;
-        state.gas_left -= copy_cost;
-        if state.gas_left < 0 {
-            return Err(StatusCode::OutOfGas);
-        }
+        let copy_cost = num_words(region.size.get()) * i64::from(COPY_WORD_GAS);
+        state.use_gas(copy_cost, GasReason::CopyPerWord)?;
+
+        copy_into_memory(
+            &mut state.memory,
+            region,
+            &state.message.input_data,
+            input_index,
+        );
+    }
 
-        let input_len = state.message.input_data.len().into();
+    Ok(())
+}
 
-        let src = core::cmp::min(input_len, input_index).as_usize();
-        let copy_size = core::cmp::min(size, input_len - src).as_usize();
+pub(crate) fn mcopy(state: &mut ExecutionState) -> Result<(), StatusCode> {
+    let dst_index = state.stack.pop();
+    let src_index = state.stack.pop();
+    let size = state.stack.pop();
 
-        if copy_size > 0 {
-            state.memory[region.offset..region.offset + copy_size]
-                .copy_from_slice(&state.message.input_data[src..src + copy_size]);
-        }
+    // Both regions are expanded, so the total cost is that of the larger end.
+    let dst = verify_memory_region(state, dst_index, size).map_err(|_| StatusCode::OutOfGas)?;
+    let src = verify_memory_region(state, src_index, size).map_err(|_| StatusCode::OutOfGas)?;
 
-        if region.size.get() - copy_size > 0 {
-            state.memory[region.offset + copy_size..region.offset + region.size.get()].fill(0);
-        }
+    if let (Some(dst), Some(src)) = (dst, src) {
+        let copy_cost = num_words(dst.size.get()) * i64::from(COPY_WORD_GAS);
+        state.use_gas(copy_cost, GasReason::CopyPerWord)?;
+
+        state
+            .memory
+            .copy_within(src.offset..src.offset + src.size.get(), dst.offset);
     }
 
     Ok(())
@@ -154,20 +214,17 @@ pub(crate) fn keccak256(state: &mut ExecutionState) -> Result<(), StatusCode> {
 
     let region = verify_memory_region(state, index, size).map_err(|_| StatusCode::OutOfGas)?;
 
-    state.stack.push(U256::from_big_endian(&*Keccak256::digest(
-        if let Some(region) = region {
-            let w = num_words(region.size.get());
-            let cost = w * 6;
-            state.gas_left -= cost;
-            if state.gas_left < 0 {
-                return Err(StatusCode::OutOfGas);
-            }
+    let data: &[u8] = if let Some(region) = region {
+        let w = num_words(region.size.get());
+        let cost = w * i64::from(KECCAK_WORD_GAS);
+        state.use_gas(cost, GasReason::KeccakPerWord)?;
 
-            &state.memory[region.offset..region.offset + region.size.get()]
-        } else {
-            &[]
-        },
-    )));
+        &state.memory[region.offset..region.offset + region.size.get()]
+    } else {
+        &[]
+    };
+    let hash = state.keccak.keccak256(data);
+    state.stack.push(U256::from_big_endian(&hash));
 
     Ok(())
 }
@@ -177,47 +234,29 @@ pub(crate) fn codesize(stack: &mut Stack, code: &[u8]) {
 }
 
 pub(crate) fn codecopy(state: &mut ExecutionState, code: &[u8]) -> Result<(), StatusCode> {
-    // TODO: Similar to calldatacopy().
-
     let mem_index = state.stack.pop();
     let input_index = state.stack.pop();
     let size = state.stack.pop();
 
     let region = verify_memory_region(state, mem_index, size).map_err(|_| StatusCode::OutOfGas)?;
 
-    if let Some(region) = region {
-        let src = min(U256::from(code.len()), input_index).as_usize();
-        let copy_size = min(region.size.get(), code.len() - src);
-
-        let copy_cost = num_words(region.size.get()) * 3;
-        state.gas_left -= copy_cost;
-        if state.gas_left < 0 {
-            return Err(StatusCode::OutOfGas);
-        }
-
-        // TODO: Add unit tests for each combination of conditions.
-        if copy_size > 0 {
-            state.memory[region.offset..region.offset + copy_size]
-                .copy_from_slice(&code[src..src + copy_size]);
-        }
+    if let Some(region) = &region {
+        let copy_cost = num_words(region.size.get()) * i64::from(COPY_WORD_GAS);
+        state.use_gas(copy_cost, GasReason::CopyPerWord)?;
 
-        if region.size.get() - copy_size > 0 {
-            state.memory[region.offset + copy_size..region.offset + region.size.get()].fill(0);
-        }
+        copy_into_memory(&mut state.memory, region, code, input_index);
     }
 
     Ok(())
 }
 
-#[doc(hidden)]
-#[macro_export]
 macro_rules! extcodecopy {
     ($co:expr, $state:expr) => {
         use crate::{
             common::*,
             continuation::{interrupt_data::*, resume_data::*},
             host::*,
-            instructions::{memory::*, properties::*},
+            instructions::{fee::*, memory::*},
         };
         use core::cmp::min;
 
@@ -230,27 +269,26 @@ macro_rules! extcodecopy {
             verify_memory_region($state, mem_index, size).map_err(|_| StatusCode::OutOfGas)?;
 
         if let Some(region) = &region {
-            let copy_cost = num_words(region.size.get()) * 3;
-            $state.gas_left -= copy_cost;
-            if $state.gas_left < 0 {
-                return Err(StatusCode::OutOfGas);
-            }
+            let copy_cost = num_words(region.size.get()) * i64::from(COPY_WORD_GAS);
+            $state.use_gas(copy_cost, GasReason::CopyPerWord)?;
         }
 
-        if $state.evm_revision >= Revision::Berlin
-            && ResumeDataVariant::into_access_account_status(
-                $co.yield_(InterruptDataVariant::AccessAccount(AccessAccount {
-                    address: addr,
-                }))
+        if $state.evm_revision >= Revision::Berlin {
+            let access_status = ResumeDataVariant::into_access_account_status(
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessAccount(AccessAccount { address: addr }),
+                ))
                 .await,
             )
             .unwrap()
-            .status
-                == AccessStatus::Cold
-        {
-            $state.gas_left -= i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
-            if $state.gas_left < 0 {
-                return Err(StatusCode::OutOfGas);
+            .status;
+            $state.count_account_access(access_status, ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
+            if access_status == AccessStatus::Cold {
+                $state.use_gas(
+                    i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST),
+                    GasReason::ColdAccount,
+                )?;
             }
         }
 
@@ -259,11 +297,14 @@ macro_rules! extcodecopy {
 
             let r = &mut $state.memory[region.offset..region.offset + region.size.get()];
             let code = ResumeDataVariant::into_code(
-                $co.yield_(InterruptDataVariant::CopyCode(CopyCode {
-                    address: addr,
-                    offset: src,
-                    max_size: r.len(),
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::CopyCode(CopyCode {
+                        address: addr,
+                        offset: src,
+                        max_size: r.len(),
+                    }),
+                ))
                 .await,
             )
             .unwrap()
@@ -278,6 +319,8 @@ macro_rules! extcodecopy {
     };
 }
 
+pub(crate) use extcodecopy;
+
 pub(crate) fn returndatasize(state: &mut ExecutionState) {
     state.stack.push(state.return_data.len().into());
 }
@@ -299,11 +342,8 @@ pub(crate) fn returndatacopy(state: &mut ExecutionState) -> Result<(), StatusCod
     }
 
     if let Some(region) = region {
-        let copy_cost = num_words(region.size.get()) * 3;
-        state.gas_left -= copy_cost;
-        if state.gas_left < 0 {
-            return Err(StatusCode::OutOfGas);
-        }
+        let copy_cost = num_words(region.size.get()) * i64::from(COPY_WORD_GAS);
+        state.use_gas(copy_cost, GasReason::CopyPerWord)?;
 
         state.memory[region.offset..region.offset + region.size.get()]
             .copy_from_slice(&state.return_data[src..src + region.size.get()]);
@@ -312,41 +352,42 @@ pub(crate) fn returndatacopy(state: &mut ExecutionState) -> Result<(), StatusCod
     Ok(())
 }
 
-#[doc(hidden)]
-#[macro_export]
 macro_rules! extcodehash {
     ($co:expr, $state:expr) => {
         use crate::{
             common::*,
             continuation::{interrupt_data::*, resume_data::*},
             host::*,
-            instructions::properties::*,
+            instructions::fee::*,
         };
 
         let addr = u256_to_address($state.stack.pop());
 
-        if $state.evm_revision >= Revision::Berlin
-            && ResumeDataVariant::into_access_account_status(
-                $co.yield_(InterruptDataVariant::AccessAccount(AccessAccount {
-                    address: addr,
-                }))
+        if $state.evm_revision >= Revision::Berlin {
+            let access_status = ResumeDataVariant::into_access_account_status(
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::AccessAccount(AccessAccount { address: addr }),
+                ))
                 .await,
             )
             .unwrap()
-            .status
-                == AccessStatus::Cold
-        {
-            $state.gas_left -= i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
-            if $state.gas_left < 0 {
-                return Err(StatusCode::OutOfGas);
+            .status;
+            $state.count_account_access(access_status, ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
+            if access_status == AccessStatus::Cold {
+                $state.use_gas(
+                    i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST),
+                    GasReason::ColdAccount,
+                )?;
             }
         }
 
         $state.stack.push(
             ResumeDataVariant::into_code_hash(
-                $co.yield_(InterruptDataVariant::GetCodeHash(GetCodeHash {
-                    address: addr,
-                }))
+                $co.yield_((
+                    $state.frame(),
+                    InterruptDataVariant::GetCodeHash(GetCodeHash { address: addr }),
+                ))
                 .await,
             )
             .unwrap()
@@ -354,3 +395,106 @@ macro_rules! extcodehash {
         );
     };
 }
+
+pub(crate) use extcodehash;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn copy(src_data: &[u8], src_offset: U256, size: usize) -> Vec<u8> {
+        let mut memory = vec![0xff; size + 2];
+        let region = MemoryRegion {
+            offset: 1,
+            size: NonZeroUsize::new(size).unwrap(),
+        };
+        copy_into_memory(&mut memory, &region, src_data, src_offset);
+
+        // Bytes outside of the region must stay untouched.
+        assert_eq!(memory[0], 0xff);
+        assert_eq!(memory[size + 1], 0xff);
+
+        memory[1..size + 1].to_vec()
+    }
+
+    #[test]
+    fn memory_expansion_cost_known_values() {
+        // Largest memory size reachable: offset MAX_BUFFER_SIZE plus a 32 bytes word.
+        let max_words = num_words(MAX_BUFFER_SIZE as usize + 32);
+
+        for (current_words, new_words, cost) in [
+            (0, 0, 0),
+            (0, 1, 3),
+            (0, 22, 66),
+            // 704 and 705 bytes are 22 and 23 words.
+            (22, 23, 4),
+            (0, 23, 70),
+            (0, 32, 98),
+            (0, 724, 3195),
+            (0, 1024, 5120),
+            (1023, 1024, 7),
+            (0, max_words, 35184775266307),
+            (max_words - 1, max_words, 524291),
+        ] {
+            assert_eq!(
+                memory_expansion_cost(current_words, new_words),
+                cost,
+                "{} -> {}",
+                current_words,
+                new_words
+            );
+        }
+
+        assert_eq!(max_words, 134217729);
+    }
+
+    proptest! {
+        #[test]
+        fn memory_expansion_cost_is_incremental(
+            mut sizes in proptest::collection::vec(0..MAX_BUFFER_SIZE as usize + 32, 0..16)
+        ) {
+            sizes.sort_unstable();
+
+            let mut current_words = 0;
+            let mut total = 0;
+            for size in sizes {
+                let new_words = num_words(size);
+                total += memory_expansion_cost(current_words, new_words);
+                current_words = new_words;
+            }
+
+            prop_assert_eq!(total, memory_expansion_cost(0, current_words));
+        }
+    }
+
+    #[test]
+    fn copy_into_memory_full() {
+        assert_eq!(copy(&[1, 2, 3, 4], 0.into(), 4), [1, 2, 3, 4]);
+        assert_eq!(copy(&[1, 2, 3, 4], 1.into(), 2), [2, 3]);
+    }
+
+    #[test]
+    fn copy_into_memory_size_beyond_data() {
+        assert_eq!(copy(&[1, 2, 3, 4], 2.into(), 4), [3, 4, 0, 0]);
+        assert_eq!(copy(&[], 0.into(), 3), [0, 0, 0]);
+    }
+
+    #[test]
+    fn copy_into_memory_offset_at_or_beyond_data() {
+        assert_eq!(copy(&[1, 2, 3, 4], 4.into(), 3), [0, 0, 0]);
+        assert_eq!(copy(&[1, 2, 3, 4], 5.into(), 3), [0, 0, 0]);
+    }
+
+    #[test]
+    fn copy_into_memory_adversarial_offset() {
+        for src_offset in [
+            U256::from(u64::MAX),
+            U256::from(u64::MAX) + 1,
+            U256::one() << 128,
+            U256::MAX,
+        ] {
+            assert_eq!(copy(&[1, 2, 3, 4], src_offset, 3), [0, 0, 0]);
+        }
+    }
+}
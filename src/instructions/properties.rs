@@ -1,18 +1,8 @@
 use once_cell::sync::Lazy;
 
+use super::fee::*;
 use crate::{common::Revision, opcode::*};
 
-pub(crate) const COLD_SLOAD_COST: u16 = 2100;
-pub(crate) const COLD_ACCOUNT_ACCESS_COST: u16 = 2600;
-pub(crate) const WARM_STORAGE_READ_COST: u16 = 100;
-
-/// Additional cold account access cost.
-///
-/// The warm access cost is unconditionally applied for every account access instruction.
-/// If the access turns out to be cold, this cost must be applied additionally.
-pub(crate) const ADDITIONAL_COLD_ACCOUNT_ACCESS_COST: u16 =
-    COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST;
-
 /// EVM instruction properties
 #[derive(Clone, Copy, Debug)]
 pub struct Properties {
@@ -106,6 +96,10 @@ pub static PROPERTIES: Lazy<[Option<Properties>; 256]> = Lazy::new(|| {
     table[OpCode::MSIZE.to_usize()] = Some(Properties::new("MSIZE", 0, 1));
     table[OpCode::GAS.to_usize()] = Some(Properties::new("GAS", 0, 1));
     table[OpCode::JUMPDEST.to_usize()] = Some(Properties::new("JUMPDEST", 0, 0));
+    table[OpCode::TLOAD.to_usize()] = Some(Properties::new("TLOAD", 1, 0));
+    table[OpCode::TSTORE.to_usize()] = Some(Properties::new("TSTORE", 2, -2));
+    table[OpCode::MCOPY.to_usize()] = Some(Properties::new("MCOPY", 3, -3));
+    table[OpCode::PUSH0.to_usize()] = Some(Properties::new("PUSH0", 0, 1));
 
     table[OpCode::PUSH1.to_usize()] = Some(Properties::new("PUSH1", 0, 1));
     table[OpCode::PUSH2.to_usize()] = Some(Properties::new("PUSH2", 0, 1));
@@ -356,7 +350,21 @@ static LONDON_GAS_COSTS: Lazy<[Option<u16>; 256]> = Lazy::new(|| {
     table
 });
 
-static SHANGHAI_GAS_COSTS: Lazy<[Option<u16>; 256]> = Lazy::new(|| *LONDON_GAS_COSTS);
+static PARIS_GAS_COSTS: Lazy<[Option<u16>; 256]> = Lazy::new(|| *LONDON_GAS_COSTS);
+
+static SHANGHAI_GAS_COSTS: Lazy<[Option<u16>; 256]> = Lazy::new(|| {
+    let mut table = *PARIS_GAS_COSTS;
+    table[OpCode::PUSH0.to_usize()] = Some(2);
+    table
+});
+
+static CANCUN_GAS_COSTS: Lazy<[Option<u16>; 256]> = Lazy::new(|| {
+    let mut table = *SHANGHAI_GAS_COSTS;
+    table[OpCode::TLOAD.to_usize()] = Some(WARM_STORAGE_READ_COST);
+    table[OpCode::TSTORE.to_usize()] = Some(WARM_STORAGE_READ_COST);
+    table[OpCode::MCOPY.to_usize()] = Some(3);
+    table
+});
 
 pub fn gas_costs(revision: Revision) -> &'static [Option<u16>; 256] {
     match revision {
@@ -370,6 +378,8 @@ pub fn gas_costs(revision: Revision) -> &'static [Option<u16>; 256] {
         Revision::Istanbul => &ISTANBUL_GAS_COSTS,
         Revision::Berlin => &BERLIN_GAS_COSTS,
         Revision::London => &LONDON_GAS_COSTS,
+        Revision::Paris => &PARIS_GAS_COSTS,
         Revision::Shanghai => &SHANGHAI_GAS_COSTS,
+        Revision::Cancun => &CANCUN_GAS_COSTS,
     }
 }
@@ -2,24 +2,65 @@ use self::instruction_table::*;
 use crate::{
     common::*,
     continuation::{interrupt::*, interrupt_data::*, resume_data::*, *},
-    instructions::{control::*, stack_manip::*, *},
+    host::StorageStatus,
+    instructions::{
+        call::{do_call, do_create},
+        control::*,
+        external::{
+            balance, blockhash, do_log, extcodesize, extended_context, push_txcontext, selfbalance,
+            selfdestruct, sload, sstore, tload, tstore,
+        },
+        memory::{extcodecopy, extcodehash},
+        stack_manip::*,
+        *,
+    },
     state::*,
-    tracing::Tracer,
+    tracing::{NoopTracer, Tracer},
     *,
 };
-use ethereum_types::U256;
+use ethereum_types::{Address, H256, U256};
 use genawaiter::sync::*;
-use std::sync::Arc;
+use sha3::{Digest, Keccak256};
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+    sync::Arc,
+};
+
+/// Requirements of the opcode set in `ExecutionOptions::extended_context_opcode`, which
+/// replaces the key on top of the stack with its value.
+const EXTENDED_CONTEXT_REQUIREMENTS: InstructionTableEntry = InstructionTableEntry {
+    gas_cost: fee::EXTENDED_CONTEXT_GAS,
+    stack_height_required: 1,
+    can_overflow_stack: false,
+};
 
+/// Check requirements of an instruction and charge its base cost. `op` is `None` for the
+/// extended context opcode, whose requirements are not in the instruction table.
+///
+/// Returns whether the instruction grows the stack.
+#[cfg_attr(not(feature = "gas-trace"), allow(unused_variables))]
 fn check_requirements(
-    instruction_table: &InstructionTable,
+    metrics: &InstructionTableEntry,
     state: &mut ExecutionState,
-    op: OpCode,
-) -> Result<(), StatusCode> {
-    let metrics = &instruction_table[op.to_usize()].ok_or(StatusCode::UndefinedInstruction)?;
-
-    state.gas_left -= metrics.gas_cost as i64;
-    if state.gas_left < 0 {
+    op: Option<OpCode>,
+) -> Result<bool, StatusCode> {
+    let cost = i64::from(metrics.gas_cost);
+    #[cfg(feature = "gas-trace")]
+    {
+        // LOG topics are priced in the table, but reported apart from the base cost.
+        let topics = op.map_or(u8::MAX, |op| op.to_u8().wrapping_sub(OpCode::LOG0.to_u8()));
+        if topics <= 4 {
+            let topics_cost = i64::from(fee::LOG_TOPIC_GAS) * i64::from(topics);
+            state.charge_gas(cost - topics_cost, GasReason::TableBase);
+            state.charge_gas(topics_cost, GasReason::LogTopics);
+        } else {
+            state.charge_gas(cost, GasReason::TableBase);
+        }
+    }
+    #[cfg(not(feature = "gas-trace"))]
+    state.charge_gas(cost, GasReason::TableBase);
+    if state.out_of_gas() {
         return Err(StatusCode::OutOfGas);
     }
 
@@ -32,7 +73,7 @@ fn check_requirements(
         return Err(StatusCode::StackUnderflow);
     }
 
-    Ok(())
+    Ok(metrics.can_overflow_stack)
 }
 
 #[derive(Clone, Debug)]
@@ -40,8 +81,87 @@ pub struct JumpdestMap(Arc<[bool]>);
 
 impl JumpdestMap {
     pub fn contains(&self, dst: U256) -> bool {
-        dst < self.0.len().into() && self.0[dst.as_usize()]
+        // Code is far shorter than 2^64, so any destination with high limbs set is out of it.
+        let U256([low, high @ ..]) = dst;
+        high.iter().all(|&limb| limb == 0) && low < self.0.len() as u64 && self.0[low as usize]
+    }
+}
+
+const EOF_PREFIX: [u8; 2] = [0xef, 0x00];
+const EOF_VERSION: u8 = 0x01;
+const EOF_SECTION_TERMINATOR: u8 = 0x00;
+const EOF_SECTION_CODE: u8 = 0x01;
+const EOF_SECTION_DATA: u8 = 0x02;
+
+/// Validate EOF container headers and return its code section.
+fn validate_eof(container: &[u8]) -> Result<&[u8], EofError> {
+    if !container.starts_with(&EOF_PREFIX) {
+        return Err(EofError::InvalidPrefix);
+    }
+
+    if container.get(EOF_PREFIX.len()) != Some(&EOF_VERSION) {
+        return Err(EofError::UnknownVersion);
+    }
+
+    let mut code_size = None;
+    let mut data_size = None;
+
+    let mut pos = EOF_PREFIX.len() + 1;
+    loop {
+        let section_id = *container
+            .get(pos)
+            .ok_or(EofError::SectionHeadersNotTerminated)?;
+        pos += 1;
+
+        if section_id == EOF_SECTION_TERMINATOR {
+            break;
+        }
+
+        let size = container
+            .get(pos..pos + 2)
+            .ok_or(EofError::IncompleteSectionSize)?;
+        let size = usize::from(u16::from_be_bytes([size[0], size[1]]));
+        pos += 2;
+
+        if size == 0 {
+            return Err(EofError::ZeroSectionSize);
+        }
+
+        match section_id {
+            EOF_SECTION_CODE => {
+                if code_size.is_some() {
+                    return Err(EofError::MultipleCodeSections);
+                }
+                code_size = Some(size);
+            }
+            EOF_SECTION_DATA => {
+                if code_size.is_none() {
+                    return Err(EofError::CodeSectionMissing);
+                }
+                if data_size.is_some() {
+                    return Err(EofError::MultipleDataSections);
+                }
+                data_size = Some(size);
+            }
+            _ => return Err(EofError::UnknownSectionId),
+        }
     }
+
+    let code_size = code_size.ok_or(EofError::CodeSectionMissing)?;
+
+    if container.len() - pos != code_size + data_size.unwrap_or(0) {
+        return Err(EofError::InvalidSectionBodiesSize);
+    }
+
+    Ok(&container[pos..pos + code_size])
+}
+
+const SERIALIZATION_VERSION: u8 = 1;
+const CHECKSUM_SIZE: usize = 4;
+
+fn serialization_checksum(data: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let hash = Keccak256::digest(data);
+    [hash[0], hash[1], hash[2], hash[3]]
 }
 
 /// Code with analysis.
@@ -119,6 +239,196 @@ impl AnalyzedCode {
         }
     }
 
+    /// Analyze code, rejecting code that is oversized, ends with a truncated PUSH
+    /// or statically jumps to an invalid destination.
+    pub fn analyze_strict(code: impl Into<Vec<u8>>) -> Result<Self, AnalysisError> {
+        let code = code.into();
+        if code.len() > MAX_CODE_SIZE {
+            return Err(AnalysisError::OversizeCode { size: code.len() });
+        }
+
+        let analyzed = Self::analyze(code);
+
+        let code = &analyzed.code;
+        let mut pc = 0;
+        while pc < code.len() {
+            let op = OpCode(code[pc]);
+            if let Some(push_len) = op.push_size() {
+                let push_len = usize::from(push_len);
+                if pc + push_len >= code.len() {
+                    return Err(AnalysisError::TruncatedPush { pc });
+                }
+
+                if let Some(&next) = code.get(pc + push_len + 1) {
+                    let next = OpCode(next);
+                    let dst = U256::from_big_endian(&code[pc + 1..pc + 1 + push_len]);
+                    if (next == OpCode::JUMP || next == OpCode::JUMPI)
+                        && !analyzed.jumpdest_map.contains(dst)
+                    {
+                        return Err(AnalysisError::InvalidJumpdest { pc });
+                    }
+                }
+
+                pc += push_len;
+            }
+            pc += 1;
+        }
+
+        Ok(analyzed)
+    }
+
+    /// Validate an EOF container ([EIP-3540](https://eips.ethereum.org/EIPS/eip-3540), [EIP-3670](https://eips.ethereum.org/EIPS/eip-3670)) and analyze its code section.
+    pub fn analyze_eof(container: &[u8]) -> Result<Self, AnalysisError> {
+        if container.len() > MAX_CODE_SIZE {
+            return Err(AnalysisError::OversizeCode {
+                size: container.len(),
+            });
+        }
+
+        let code = validate_eof(container).map_err(AnalysisError::Eof)?;
+
+        let mut pc = 0;
+        while pc < code.len() {
+            let op = OpCode(code[pc]);
+            if PROPERTIES[op.to_usize()].is_none() {
+                return Err(AnalysisError::Eof(EofError::UndefinedInstruction));
+            }
+            if let Some(push_len) = op.push_size() {
+                pc += usize::from(push_len);
+                if pc >= code.len() {
+                    return Err(AnalysisError::Eof(EofError::TruncatedImmediate));
+                }
+            }
+            pc += 1;
+        }
+
+        Ok(Self::analyze(code))
+    }
+
+    /// Serialize code together with its analysis, so that it can be loaded with
+    /// [`AnalyzedCode::from_bytes`] without analyzing it again.
+    ///
+    /// The format is a version byte, code length as big-endian `u32`, code, a bitset of
+    /// jump destinations with the lowest bit first, and the first 4 bytes of Keccak-256
+    /// of everything before them.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let code_len = self.code.len();
+        let mut data = Vec::with_capacity(1 + 4 + code_len + (code_len + 7) / 8 + CHECKSUM_SIZE);
+        data.push(SERIALIZATION_VERSION);
+        data.extend_from_slice(&(code_len as u32).to_be_bytes());
+        data.extend_from_slice(&self.code);
+        let mut bitset = vec![0_u8; (code_len + 7) / 8];
+        for (pc, &is_jumpdest) in self.jumpdest_map.0.iter().enumerate() {
+            if is_jumpdest {
+                bitset[pc / 8] |= 1 << (pc % 8);
+            }
+        }
+        data.extend_from_slice(&bitset);
+        let checksum = serialization_checksum(&data);
+        data.extend_from_slice(&checksum);
+        data
+    }
+
+    /// Load code serialized with [`AnalyzedCode::to_bytes`].
+    ///
+    /// Without `verify`, jump destinations are only checked to point at JUMPDEST bytes, which
+    /// may be PUSH data, and missing destinations are not detected. The checksum protects
+    /// against corruption, not tampering, so data from untrusted sources must be loaded with
+    /// `verify`, which compares jump destinations against a fresh analysis of the code.
+    pub fn from_bytes(data: &[u8], verify: bool) -> Result<Self, DecodeError> {
+        let (&version, rest) = data.split_first().ok_or(DecodeError::Truncated)?;
+        if version != SERIALIZATION_VERSION {
+            return Err(DecodeError::UnsupportedVersion { version });
+        }
+
+        let code_len = rest.get(..4).ok_or(DecodeError::Truncated)?;
+        let code_len = u32::from_be_bytes([code_len[0], code_len[1], code_len[2], code_len[3]]);
+        let code_len = code_len as usize;
+        let bitset_len = (code_len + 7) / 8;
+        let body_len = 1 + 4 + code_len + bitset_len;
+        if data.len() < body_len + CHECKSUM_SIZE {
+            return Err(DecodeError::Truncated);
+        }
+        if data.len() > body_len + CHECKSUM_SIZE {
+            return Err(DecodeError::TrailingBytes);
+        }
+        let (body, checksum) = data.split_at(body_len);
+        if serialization_checksum(body) != checksum {
+            return Err(DecodeError::ChecksumMismatch);
+        }
+
+        let code = &body[5..5 + code_len];
+        let bitset = &body[5 + code_len..];
+        let mut jumpdest_map = vec![false; code_len];
+        for (i, &byte) in bitset.iter().enumerate() {
+            for bit in 0..8 {
+                if byte & (1 << bit) != 0 {
+                    let pc = i * 8 + bit;
+                    if code.get(pc) != Some(&OpCode::JUMPDEST.to_u8()) {
+                        return Err(DecodeError::InvalidJumpdest { pc });
+                    }
+                    jumpdest_map[pc] = true;
+                }
+            }
+        }
+
+        if verify && Self::analyze(code).jumpdest_map.0[..] != jumpdest_map[..] {
+            return Err(DecodeError::AnalysisMismatch);
+        }
+
+        // Enough STOPs for the longest PUSH truncated at the end of code, and a final STOP.
+        let mut padded_code = Vec::with_capacity(code_len + 33);
+        padded_code.extend_from_slice(code);
+        padded_code.resize(code_len + 33, OpCode::STOP.to_u8());
+        let padded_code = Bytes::from(padded_code);
+        let mut code = padded_code.clone();
+        code.truncate(code_len);
+
+        Ok(Self {
+            jumpdest_map: JumpdestMap(jumpdest_map.into()),
+            code,
+            padded_code,
+        })
+    }
+
+    /// Original code, without padding.
+    pub fn code(&self) -> &Bytes {
+        &self.code
+    }
+
+    /// Positions of all valid jump destinations, in ascending order.
+    pub fn jumpdests(&self) -> Vec<usize> {
+        self.jumpdest_map
+            .0
+            .iter()
+            .enumerate()
+            .filter_map(|(pc, &is_jumpdest)| is_jumpdest.then_some(pc))
+            .collect()
+    }
+
+    /// Instruction counts of the code, as split into instructions by analysis.
+    pub fn stats(&self) -> AnalysisStats {
+        let mut stats = AnalysisStats {
+            jumpdests: self
+                .jumpdest_map
+                .0
+                .iter()
+                .filter(|&&is_jumpdest| is_jumpdest)
+                .count(),
+            ..Default::default()
+        };
+
+        let mut pc = 0;
+        while pc < self.code.len() {
+            let push_size = usize::from(OpCode(self.code[pc]).push_size().unwrap_or(0));
+            stats.instructions += 1;
+            stats.push_bytes += push_size.min(self.code.len() - pc - 1);
+            pc += 1 + push_size;
+        }
+
+        stats
+    }
+
     /// Execute analyzed EVM bytecode using provided `Host` context. Optionally modify the state after each instruction using provided closure.
     pub fn execute<H: Host, T: Tracer>(
         &self,
@@ -127,14 +437,39 @@ impl AnalyzedCode {
         state_modifier: StateModifier,
         message: Message,
         revision: Revision,
+    ) -> Output {
+        self.execute_with_options(
+            host,
+            tracer,
+            state_modifier,
+            message,
+            revision,
+            &ExecutionOptions::default(),
+        )
+    }
+
+    /// Execute analyzed EVM bytecode with provided `ExecutionOptions`.
+    pub fn execute_with_options<H: Host, T: Tracer>(
+        &self,
+        host: &mut H,
+        tracer: &mut T,
+        state_modifier: StateModifier,
+        message: Message,
+        revision: Revision,
+        options: &ExecutionOptions,
     ) -> Output {
         if !T::DUMMY {
             tracer.notify_execution_start(revision, message.clone(), self.code.clone());
         }
 
         let output = self
-            .execute_resumable(!T::DUMMY || state_modifier.is_some(), message, revision)
-            .run_to_completion_with_host(host, tracer, state_modifier);
+            .execute_resumable_with_options(
+                !T::DUMMY || state_modifier.is_some(),
+                message,
+                revision,
+                options,
+            )
+            .run_to_completion_with_options(host, tracer, state_modifier, options);
 
         if !T::DUMMY {
             tracer.notify_execution_end(&output);
@@ -149,13 +484,33 @@ impl AnalyzedCode {
         trace: bool,
         message: Message,
         revision: Revision,
+    ) -> ExecutionStartInterrupt {
+        self.execute_resumable_with_options(trace, message, revision, &ExecutionOptions::default())
+    }
+
+    /// Execute in resumable EVM with provided `ExecutionOptions`.
+    ///
+    /// Only interpreter-level options are applied, driver-level ones are up to the caller.
+    pub fn execute_resumable_with_options(
+        &self,
+        trace: bool,
+        message: Message,
+        revision: Revision,
+        options: &ExecutionOptions,
     ) -> ExecutionStartInterrupt {
         let code = self.clone();
+        let options = options.clone();
+        let state = ExecutionState::new(message, revision);
+        let frame = state.frame();
         let inner = Box::pin(Gen::new(move |co| {
-            interpreter_producer(co, code, ExecutionState::new(message, revision), trace)
+            interpreter_producer(co, code, state, trace, options)
         }));
 
-        ExecutionStartInterrupt { inner, data: () }
+        ExecutionStartInterrupt {
+            inner,
+            data: (),
+            frame,
+        }
     }
 }
 
@@ -166,147 +521,628 @@ impl ExecutionStartInterrupt {
         tracer: &mut T,
         state_modifier: StateModifier,
     ) -> Output {
-        let mut interrupt = self.resume(());
+        self.run_to_completion_with_options(
+            host,
+            tracer,
+            state_modifier,
+            &ExecutionOptions::default(),
+        )
+    }
 
-        loop {
-            interrupt = match interrupt {
-                InterruptVariant::InstructionStart(i) => {
-                    tracer.notify_instruction_start(i.data().pc, i.data().opcode, &i.data().state);
-                    i.resume(state_modifier.clone())
-                }
-                InterruptVariant::AccountExists(i) => {
-                    let exists = host.account_exists(i.data().address);
-                    i.resume(AccountExistsStatus { exists })
-                }
-                InterruptVariant::GetBalance(i) => {
-                    let balance = host.get_balance(i.data().address);
-                    i.resume(Balance { balance })
-                }
-                InterruptVariant::GetCodeSize(i) => {
-                    let code_size = host.get_code_size(i.data().address);
-                    i.resume(CodeSize { code_size })
+    /// Run to completion, applying driver-level `ExecutionOptions`.
+    pub fn run_to_completion_with_options<H: Host, T: Tracer>(
+        self,
+        host: &mut H,
+        tracer: &mut T,
+        state_modifier: StateModifier,
+        options: &ExecutionOptions,
+    ) -> Output {
+        match drive(
+            self.resume(()),
+            host,
+            tracer,
+            state_modifier,
+            options,
+            |_| false,
+        ) {
+            Ok(output) => output,
+            Err(_) => unreachable!("no breakpoint is set"),
+        }
+    }
+
+    /// Run until the instruction `opcode` is about to be executed, answering host
+    /// interrupts with `host`.
+    ///
+    /// Returns the paused instruction, or the output if execution completes without reaching
+    /// it. Instructions only pause if execution has been started with `trace` enabled.
+    pub fn run_until_opcode<H: Host>(
+        self,
+        host: &mut H,
+        opcode: OpCode,
+    ) -> Result<InstructionStartInterrupt, Output> {
+        match drive(
+            self.resume(()),
+            host,
+            &mut NoopTracer,
+            None,
+            &ExecutionOptions::default(),
+            |i| matches!(i, InterruptVariant::InstructionStart(i) if i.data().opcode == opcode),
+        ) {
+            Ok(output) => Err(output),
+            Err(InterruptVariant::InstructionStart(paused)) => Ok(paused),
+            Err(_) => unreachable!("only instructions are breakpoints"),
+        }
+    }
+}
+
+/// Results of a `PrefetchPlan`.
+#[derive(Default)]
+struct Prefetched {
+    storage: HashMap<(Address, H256), U256>,
+    balances: HashMap<Address, U256>,
+}
+
+impl Prefetched {
+    fn fetch<H: Host>(host: &H, plan: &PrefetchPlan) -> Self {
+        let mut storage = HashMap::new();
+        for (address, keys) in &plan.storage {
+            let values = host.get_storage_many(*address, keys);
+            for (&key, value) in keys.iter().zip(values) {
+                storage.insert((*address, key), U256::from_big_endian(&value.0));
+            }
+        }
+
+        let balances = if plan.balances.is_empty() {
+            HashMap::new()
+        } else {
+            plan.balances
+                .iter()
+                .copied()
+                .zip(host.get_balances(&plan.balances))
+                .collect()
+        };
+
+        Self { storage, balances }
+    }
+}
+
+/// Answer interrupts with `host` until execution completes, applying driver-level
+/// `ExecutionOptions`, or until `breakpoint` returns `true` for an interrupt.
+pub(crate) fn drive<H: Host, T: Tracer>(
+    mut interrupt: InterruptVariant,
+    host: &mut H,
+    tracer: &mut T,
+    state_modifier: StateModifier,
+    options: &ExecutionOptions,
+    mut breakpoint: impl FnMut(&InterruptVariant) -> bool,
+) -> Result<Output, InterruptVariant> {
+    let overrides = &options.overrides;
+    // Original and current values of overridden storage slots.
+    let mut storage_overrides = overrides
+        .storage
+        .iter()
+        .map(|(&slot, value)| {
+            let value = U256::from_big_endian(&value.0);
+            (slot, (value, value))
+        })
+        .collect::<HashMap<_, _>>();
+    let mut host_budget = options.host_budget;
+    let mut prefetched = options
+        .prefetch
+        .as_ref()
+        .map(|plan| Prefetched::fetch(host, plan))
+        .unwrap_or_default();
+
+    loop {
+        if breakpoint(&interrupt) {
+            return Err(interrupt);
+        }
+
+        if let Some(budget) = &mut host_budget {
+            if !matches!(
+                interrupt,
+                InterruptVariant::InstructionStart(_) | InterruptVariant::Complete(..)
+            ) {
+                if budget.max_interrupts == 0 {
+                    return Ok(host_budget_exceeded(HostBudgetExceeded::Interrupts));
                 }
-                InterruptVariant::GetStorage(i) => {
-                    let value = host.get_storage(i.data().address, i.data().key);
-                    i.resume(StorageValue { value })
+                budget.max_interrupts -= 1;
+            }
+        }
+
+        interrupt = match interrupt {
+            InterruptVariant::InstructionStart(i) => {
+                tracer.notify_instruction_start(i.data().pc, i.data().opcode, &i.data().state);
+                i.resume(state_modifier.clone())
+            }
+            InterruptVariant::AccountExists(i) => {
+                let address = i.data().address;
+                let exists = overrides.contains_account(address) || host.account_exists(address);
+                i.resume(AccountExistsStatus { exists })
+            }
+            InterruptVariant::GetBalance(i) => {
+                let address = i.data().address;
+                let balance = match overrides.balance.get(&address) {
+                    Some(&balance) => balance,
+                    None => match prefetched.balances.get(&address) {
+                        Some(&balance) => balance,
+                        None => host.get_balance(address),
+                    },
+                };
+                i.resume(Balance { balance })
+            }
+            InterruptVariant::GetCodeSize(i) => {
+                let address = i.data().address;
+                let code_size = match overrides.code.get(&address) {
+                    Some(code) => code.len().into(),
+                    None => host.get_code_size(address),
+                };
+                i.resume(CodeSize { code_size })
+            }
+            InterruptVariant::GetStorage(i) => {
+                let address = i.data().address;
+                let key = i.data().key;
+                let slot = (address, H256(key.into()));
+                let value = match storage_overrides.get(&slot) {
+                    Some(&(_, current)) => current,
+                    None => match prefetched.storage.get(&slot) {
+                        Some(&value) => value,
+                        None => host.get_storage(address, key),
+                    },
+                };
+                if !charge_host_bytes(&mut host_budget, 32) {
+                    return Ok(host_budget_exceeded(HostBudgetExceeded::BytesReturned));
                 }
-                InterruptVariant::SetStorage(i) => {
-                    let status = host.set_storage(i.data().address, i.data().key, i.data().value);
-                    i.resume(StorageStatusInfo { status })
+                i.resume(StorageValue { value })
+            }
+            InterruptVariant::SetStorage(i) => {
+                let slot = (i.data().address, H256(i.data().key.into()));
+                prefetched.storage.remove(&slot);
+                let mut status = host.set_storage(i.data().address, i.data().key, i.data().value);
+                // The host has not seen the overridden value, so its status is recomputed with
+                // the override as the original value.
+                if let Some((original, current)) = storage_overrides.get_mut(&slot) {
+                    status = StorageStatus::of_write(*original, *current, i.data().value);
+                    *current = i.data().value;
                 }
-                InterruptVariant::GetCodeHash(i) => {
-                    let hash = host.get_code_hash(i.data().address);
-                    i.resume(CodeHash { hash })
+                i.resume(StorageStatusInfo { status })
+            }
+            InterruptVariant::GetTransientStorage(i) => {
+                let value = host.get_transient_storage(i.data().address, i.data().key);
+                if !charge_host_bytes(&mut host_budget, 32) {
+                    return Ok(host_budget_exceeded(HostBudgetExceeded::BytesReturned));
                 }
-                InterruptVariant::CopyCode(i) => {
-                    let mut code = vec![0; i.data().max_size];
-                    let copied = host.copy_code(i.data().address, i.data().offset, &mut code[..]);
-                    if copied > code.len() {
-                        return Output {
-                            status_code: StatusCode::InternalError(format!(
-                                "copy code: copied {} > max size {}",
-                                copied,
-                                code.len()
-                            )),
-                            gas_left: 0,
-                            output_data: Bytes::new(),
-                            create_address: None,
-                        };
+                i.resume(TransientStorageValue { value })
+            }
+            InterruptVariant::SetTransientStorage(i) => {
+                host.set_transient_storage(i.data().address, i.data().key, i.data().value);
+                i.resume(())
+            }
+            InterruptVariant::GetCodeHash(i) => {
+                let address = i.data().address;
+                let hash = match overrides.code.get(&address) {
+                    Some(code) => U256::from_big_endian(&options.keccak.keccak256(code)),
+                    None => host.get_code_hash(address),
+                };
+                i.resume(CodeHash { hash })
+            }
+            InterruptVariant::CopyCode(i) => {
+                let mut code = vec![0; i.data().max_size];
+                let copied = match overrides.code.get(&i.data().address) {
+                    Some(overridden) => {
+                        let offset = min(i.data().offset, overridden.len());
+                        let copied = min(code.len(), overridden.len() - offset);
+                        code[..copied].copy_from_slice(&overridden[offset..offset + copied]);
+                        copied
                     }
-                    code.truncate(copied);
-                    let code = code.into();
-                    i.resume(Code { code })
+                    None => host.copy_code(i.data().address, i.data().offset, &mut code[..]),
+                };
+                if copied > code.len() {
+                    return Ok(Output {
+                        status_code: StatusCode::InternalError(format!(
+                            "copy code: copied {} > max size {}",
+                            copied,
+                            code.len()
+                        )),
+                        gas_left: 0,
+                        gas_refund: 0,
+                        output_data: Bytes::new(),
+                        create_address: None,
+                        stats: ExecutionStats::default(),
+                    });
                 }
-                InterruptVariant::Selfdestruct(i) => {
-                    host.selfdestruct(i.data().address, i.data().beneficiary);
-                    i.resume(())
+                code.truncate(copied);
+                if !charge_host_bytes(&mut host_budget, copied) {
+                    return Ok(host_budget_exceeded(HostBudgetExceeded::BytesReturned));
                 }
-                InterruptVariant::Call(i) => {
-                    let message = match i.data() {
-                        Call::Call(message) => message.clone(),
-                        Call::Create(message) => message.clone().into(),
-                    };
+                let code = code.into();
+                i.resume(Code { code })
+            }
+            InterruptVariant::Selfdestruct(i) => {
+                prefetched = Prefetched::default();
+                let first = host.selfdestruct(i.data().address, i.data().beneficiary);
+                i.resume(SelfdestructStatus { first })
+            }
+            InterruptVariant::Call(i) => {
+                let message = match i.data() {
+                    Call::Call(message) => message.clone(),
+                    Call::Create(message) => message.clone().into(),
+                };
+                let too_large = options
+                    .max_call_input_size
+                    .map_or(false, |max| message.input_data.len() > max);
+                let output = if too_large {
+                    // Rejected before the host is called, like a call with insufficient
+                    // balance: only the call fails, and its gas is returned.
+                    Output {
+                        status_code: StatusCode::Failure,
+                        gas_left: message.gas,
+                        gas_refund: 0,
+                        output_data: Bytes::new(),
+                        create_address: None,
+                        stats: ExecutionStats::default(),
+                    }
+                } else {
+                    if !T::DUMMY {
+                        tracer.notify_call_start(&message);
+                    }
+                    // The callee may change prefetched storage and balances.
+                    prefetched = Prefetched::default();
                     let output = host.call(&message);
-                    i.resume(CallOutput { output })
-                }
-                InterruptVariant::GetTxContext(i) => {
-                    let context = host.get_tx_context();
-                    i.resume(TxContextData { context })
-                }
-                InterruptVariant::GetBlockHash(i) => {
-                    let hash = host.get_block_hash(i.data().block_number);
-                    i.resume(BlockHash { hash })
-                }
-                InterruptVariant::EmitLog(i) => {
-                    host.emit_log(
-                        i.data().address,
-                        &*i.data().data,
-                        i.data().topics.as_slice(),
-                    );
-                    i.resume(())
-                }
-                InterruptVariant::AccessAccount(i) => {
-                    let status = host.access_account(i.data().address);
-                    i.resume(AccessAccountStatus { status })
-                }
-                InterruptVariant::AccessStorage(i) => {
-                    let status = host.access_storage(i.data().address, i.data().key);
-                    i.resume(AccessStorageStatus { status })
-                }
-                InterruptVariant::Complete(i) => {
-                    let output = match i {
-                        Ok(output) => output.into(),
-                        Err(status_code) => Output {
-                            status_code,
-                            gas_left: 0,
-                            output_data: Bytes::new(),
-                            create_address: None,
+                    if !T::DUMMY {
+                        tracer.notify_call_end(&output);
+                    }
+                    if !charge_host_bytes(&mut host_budget, output.output_data.len()) {
+                        return Ok(host_budget_exceeded(HostBudgetExceeded::BytesReturned));
+                    }
+                    output
+                };
+                i.resume(CallOutput { output })
+            }
+            InterruptVariant::GetTxContext(i) => {
+                let context = host.get_tx_context();
+                i.resume(TxContextData { context })
+            }
+            InterruptVariant::GetBlockHash(i) => {
+                let hash = host.get_block_hash(i.data().block_number);
+                i.resume(BlockHash { hash })
+            }
+            InterruptVariant::EmitLog(i) => {
+                host.emit_log(
+                    i.data().address,
+                    &*i.data().data,
+                    i.data().topics.as_slice(),
+                );
+                i.resume(())
+            }
+            InterruptVariant::AccessAccount(i) => {
+                let status = host.access_account(i.data().address);
+                i.resume(AccessAccountStatus { status })
+            }
+            InterruptVariant::AccessStorage(i) => {
+                let status = host.access_storage(i.data().address, i.data().key);
+                i.resume(AccessStorageStatus { status })
+            }
+            InterruptVariant::GetExtendedContext(i) => {
+                let value = host
+                    .get_extended_context(i.data().key)
+                    .map_err(StatusCode::from_host_error);
+                i.resume(ExtendedContext { value })
+            }
+            InterruptVariant::Complete(i, stats) => {
+                let mut output = match i {
+                    Ok(output) => Output::from(output),
+                    Err(status_code) => Output {
+                        status_code,
+                        gas_left: match stats.failure_site {
+                            Some(site) if options.preserve_gas_on_failure => site.gas_left,
+                            _ => 0,
                         },
-                    };
+                        gas_refund: 0,
+                        output_data: Bytes::new(),
+                        create_address: None,
+                        stats: ExecutionStats::default(),
+                    },
+                };
+                output.stats = stats;
+
+                return Ok(output);
+            }
+        };
+    }
+}
+
+/// Charge bytes returned by the host. Returns `false` if the budget is exhausted.
+fn charge_host_bytes(budget: &mut Option<HostBudget>, bytes: usize) -> bool {
+    if let Some(budget) = budget {
+        let bytes = bytes as u64;
+        if bytes > budget.max_bytes_returned {
+            return false;
+        }
+        budget.max_bytes_returned -= bytes;
+    }
+
+    true
+}
 
-                    return output;
+fn host_budget_exceeded(exceeded: HostBudgetExceeded) -> Output {
+    Output {
+        status_code: StatusCode::InternalError("host budget exceeded".into()),
+        gas_left: 0,
+        gas_refund: 0,
+        output_data: Bytes::new(),
+        create_address: None,
+        stats: ExecutionStats {
+            host_budget_exceeded: Some(exceeded),
+            ..Default::default()
+        },
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct LoopSample {
+    pc: usize,
+    stack_height: usize,
+    memory_size: usize,
+    gas_left: i64,
+    gas_delta: Option<i64>,
+}
+
+/// Loop detector, see `LoopDetectionConfig`.
+#[derive(Debug)]
+struct LoopDetector {
+    config: LoopDetectionConfig,
+    instructions: u64,
+    sample: Option<LoopSample>,
+}
+
+impl LoopDetector {
+    fn new(config: LoopDetectionConfig) -> Self {
+        Self {
+            config,
+            instructions: 0,
+            sample: None,
+        }
+    }
+
+    /// Observe the instruction about to be executed. Returns `true` if a loop is detected.
+    fn observe(&mut self, pc: usize, state: &ExecutionState) -> bool {
+        self.instructions += 1;
+
+        if let Some(sample) = &mut self.sample {
+            if sample.pc == pc {
+                if sample.stack_height != state.stack.len()
+                    || sample.memory_size != state.memory.len()
+                {
+                    self.sample = None;
+                } else {
+                    let gas_delta = sample.gas_left - state.gas_left;
+                    if gas_delta > 0 && sample.gas_delta == Some(gas_delta) {
+                        return true;
+                    }
+                    sample.gas_left = state.gas_left;
+                    sample.gas_delta = Some(gas_delta);
                 }
-            };
+            }
+        }
+
+        if self.sample.is_none() && self.instructions % self.config.sample_interval.max(1) == 0 {
+            self.sample = Some(LoopSample {
+                pc,
+                stack_height: state.stack.len(),
+                memory_size: state.memory.len(),
+                gas_left: state.gas_left,
+                gas_delta: None,
+            });
         }
+
+        false
+    }
+
+    /// Host interaction makes the loop unpredictable, start over.
+    fn reset(&mut self) {
+        self.sample = None;
     }
 }
 
+/// Whether the instruction may interact with the host.
+fn interacts_with_host(op: OpCode) -> bool {
+    matches!(
+        op,
+        OpCode::BALANCE
+            | OpCode::ORIGIN
+            | OpCode::GASPRICE
+            | OpCode::EXTCODESIZE
+            | OpCode::EXTCODECOPY
+            | OpCode::EXTCODEHASH
+            | OpCode::BLOCKHASH
+            | OpCode::COINBASE
+            | OpCode::TIMESTAMP
+            | OpCode::NUMBER
+            | OpCode::DIFFICULTY
+            | OpCode::GASLIMIT
+            | OpCode::CHAINID
+            | OpCode::SELFBALANCE
+            | OpCode::BASEFEE
+            | OpCode::SLOAD
+            | OpCode::SSTORE
+            | OpCode::TLOAD
+            | OpCode::TSTORE
+            | OpCode::LOG0
+            | OpCode::LOG1
+            | OpCode::LOG2
+            | OpCode::LOG3
+            | OpCode::LOG4
+            | OpCode::CREATE
+            | OpCode::CALL
+            | OpCode::CALLCODE
+            | OpCode::DELEGATECALL
+            | OpCode::CREATE2
+            | OpCode::STATICCALL
+            | OpCode::SELFDESTRUCT
+    )
+}
+
 async fn interpreter_producer(
-    mut co: Co<InterruptDataVariant, ResumeDataVariant>,
+    co: Co<(CallFrame, InterruptDataVariant), ResumeDataVariant>,
     s: AnalyzedCode,
     mut state: ExecutionState,
     trace: bool,
-) -> Result<SuccessfulOutput, StatusCode> {
-    let state = &mut state;
+    options: ExecutionOptions,
+) -> (Result<SuccessfulOutput, StatusCode>, ExecutionStats) {
+    let mut stats = ExecutionStats::default();
+    state.return_data = options.initial_return_data.clone();
+    stats.max_return_data_bytes = state.return_data.len();
+    state.memory.extend_from_slice(&options.initial_memory);
+    state.memory.resize((state.memory.len() + 31) / 32 * 32, 0);
+    state.metering = options.metering;
+    state.truncate_output_on_oog_return = options.truncate_output_on_oog_return;
+    state.capabilities = CapabilityMask::for_frame(&state.message, options.capabilities);
+    state.keccak = options.keccak.clone();
+    let res = interpret(co, s, &mut state, trace, &options, &mut stats).await;
+    stats.max_stack_depth = max(stats.max_stack_depth, state.stack.len());
+    // Memory never shrinks, so its final size is the maximum.
+    stats.max_memory_bytes = state.memory.len();
+    stats.mutation_observed = state.mutation_observed;
+    stats.accesses = state.accesses;
+    stats.output_truncated = state.output_truncated;
+    if let Err(
+        StatusCode::UndefinedInstruction
+        | StatusCode::InvalidInstruction
+        | StatusCode::StackOverflow
+        | StatusCode::StackUnderflow
+        | StatusCode::BadJumpDestination,
+    ) = &res
+    {
+        let (pc, gas_left) = state.instruction_start;
+        stats.failure_site = Some(FailureSite { pc, gas_left });
+    }
+    #[cfg(feature = "gas-trace")]
+    {
+        if let Ok(output) = &res {
+            check_gas_trace(
+                &state.gas_trace.charges,
+                state.message.gas - output.gas_left,
+                state.gas_refund,
+            );
+        }
+        stats.gas_charges = std::mem::take(&mut state.gas_trace.charges);
+    }
+    (res, stats)
+}
+
+/// Assert that recorded gas charges add up to gas used and refund, in debug builds only.
+#[cfg(feature = "gas-trace")]
+fn check_gas_trace(charges: &[GasCharge], gas_used: i64, gas_refund: i64) {
+    let (refunds, deductions): (Vec<_>, Vec<_>) = charges
+        .iter()
+        .partition(|charge| matches!(charge.reason, GasReason::Refund { .. }));
+    debug_assert_eq!(
+        deductions.iter().map(|charge| charge.amount).sum::<i64>(),
+        gas_used,
+        "gas charges do not add up to gas used: {:?}",
+        deductions
+    );
+    debug_assert_eq!(
+        refunds.iter().map(|charge| charge.amount).sum::<i64>(),
+        gas_refund,
+        "refunds do not add up to gas refund: {:?}",
+        refunds
+    );
+}
 
+async fn interpret(
+    mut co: Co<(CallFrame, InterruptDataVariant), ResumeDataVariant>,
+    s: AnalyzedCode,
+    state: &mut ExecutionState,
+    trace: bool,
+    options: &ExecutionOptions,
+    stats: &mut ExecutionStats,
+) -> Result<SuccessfulOutput, StatusCode> {
     let instruction_table = get_baseline_instruction_table(state.evm_revision);
 
+    let mut loop_detector = options.loop_detection.map(LoopDetector::new);
+
     let mut reverted = false;
 
+    // Stack depth only reaches a new maximum after an instruction growing the stack.
+    let mut stack_grown = true;
+
     let mut pc = 0;
 
     loop {
         let op = OpCode(s.padded_code[pc]);
 
+        #[cfg(feature = "gas-trace")]
+        {
+            state.gas_trace.pc = pc;
+            state.gas_trace.opcode = op;
+        }
+
+        if stack_grown {
+            stats.max_stack_depth = max(stats.max_stack_depth, state.stack.len());
+        }
+
         // Do not print stop on the final STOP
-        if trace && pc < s.code.len() {
+        if (trace || options.instruction_start_pcs.contains(&pc)) && pc < s.code.len() {
             if let Some(modifier) = co
-                .yield_(InterruptDataVariant::InstructionStart(Box::new(
-                    InstructionStart {
+                .yield_((
+                    CallFrame {
+                        pc,
+                        ..state.frame()
+                    },
+                    InterruptDataVariant::InstructionStart(Box::new(InstructionStart {
                         pc,
                         opcode: op,
                         state: state.clone(),
-                    },
-                )))
+                    })),
+                ))
                 .await
                 .as_state_modifier()
                 .unwrap()
             {
-                (modifier)(state)
+                let gas_left = state.gas_left;
+                let gas_refund = state.gas_refund;
+                (modifier)(state);
+                stats.max_stack_depth = max(stats.max_stack_depth, state.stack.len());
+                state.trace_gas(gas_left - state.gas_left, GasReason::StateModifier);
+                state.trace_gas(
+                    state.gas_refund - gas_refund,
+                    GasReason::Refund {
+                        source: RefundSource::StateModifier,
+                    },
+                );
             }
         }
 
-        check_requirements(instruction_table, state, op)?;
+        if let Some(loop_detector) = &mut loop_detector {
+            if interacts_with_host(op) || options.extended_context_opcode == Some(op) {
+                loop_detector.reset();
+            } else if loop_detector.observe(pc, state) {
+                stats.loop_detected = true;
+            }
+        }
+
+        state.instruction_start = (pc, state.gas_left);
+
+        let extended_context = options.extended_context_opcode == Some(op);
+        stack_grown = if extended_context {
+            check_requirements(&EXTENDED_CONTEXT_REQUIREMENTS, state, None)?
+        } else {
+            let metrics =
+                instruction_table[op.to_usize()].ok_or(StatusCode::UndefinedInstruction)?;
+            check_requirements(&metrics, state, Some(op))?
+        };
+
+        if options.disabled_opcodes.contains(&op) {
+            return Err(StatusCode::UndefinedInstruction);
+        }
+
+        if extended_context {
+            extended_context!(co, state);
+            pc += 1;
+            continue;
+        }
 
         match op {
             OpCode::STOP => {
@@ -418,6 +1254,9 @@ async fn interpreter_producer(
             OpCode::CODECOPY => {
                 memory::codecopy(state, &s.code[..])?;
             }
+            OpCode::MCOPY => {
+                memory::mcopy(state)?;
+            }
             OpCode::EXTCODESIZE => {
                 extcodesize!(co, state);
             }
@@ -494,14 +1333,22 @@ async fn interpreter_producer(
                 }
             }
             OpCode::PC => state.stack.push(pc.into()),
+            OpCode::PUSH0 => state.stack.push(U256::zero()),
             OpCode::MSIZE => memory::msize(state),
             OpCode::SLOAD => {
                 sload!(co, state);
             }
             OpCode::SSTORE => {
-                sstore!(co, state);
+                sstore!(co, state, pc);
             }
-            OpCode::GAS => state.stack.push(state.gas_left.into()),
+            OpCode::TLOAD => {
+                tload!(co, state);
+            }
+            OpCode::TSTORE => {
+                tstore!(co, state);
+            }
+            // Gas left may be negative without metering.
+            OpCode::GAS => state.stack.push(max(state.gas_left, 0).into()),
             OpCode::JUMPDEST => {}
             OpCode::PUSH1
             | OpCode::PUSH2
@@ -583,15 +1430,18 @@ async fn interpreter_producer(
             ),
 
             OpCode::LOG0 | OpCode::LOG1 | OpCode::LOG2 | OpCode::LOG3 | OpCode::LOG4 => {
-                do_log!(co, state, op.to_usize() - OpCode::LOG0.to_usize());
+                do_log!(co, state, pc, op.to_usize() - OpCode::LOG0.to_usize());
             }
             OpCode::CREATE | OpCode::CREATE2 => {
-                do_create!(co, state, op == OpCode::CREATE2);
+                do_create!(co, state, pc, op == OpCode::CREATE2);
+                stats.max_return_data_bytes =
+                    max(stats.max_return_data_bytes, state.return_data.len());
             }
             OpCode::CALL | OpCode::CALLCODE | OpCode::DELEGATECALL | OpCode::STATICCALL => {
                 do_call!(
                     co,
                     state,
+                    pc,
                     match op {
                         OpCode::CALL | OpCode::STATICCALL => CallKind::Call,
                         OpCode::CALLCODE => CallKind::CallCode,
@@ -600,6 +1450,8 @@ async fn interpreter_producer(
                     },
                     op == OpCode::STATICCALL
                 );
+                stats.max_return_data_bytes =
+                    max(stats.max_return_data_bytes, state.return_data.len());
             }
             OpCode::RETURN | OpCode::REVERT => {
                 ret(state)?;
@@ -610,7 +1462,7 @@ async fn interpreter_producer(
                 return Err(StatusCode::InvalidInstruction);
             }
             OpCode::SELFDESTRUCT => {
-                selfdestruct!(co, state);
+                selfdestruct!(co, state, pc);
                 break;
             }
             other => {
@@ -624,8 +1476,32 @@ async fn interpreter_producer(
     let output = SuccessfulOutput {
         reverted,
         gas_left: state.gas_left,
+        // Refunds are reverted together with state changes.
+        gas_refund: if reverted { 0 } else { state.gas_refund },
         output_data: state.output_data.clone(),
     };
 
     Ok(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jumpdest_map_contains() {
+        let map = JumpdestMap(vec![true, false, true].into());
+        let len = 3;
+
+        assert!(map.contains(0.into()));
+        assert!(!map.contains(1.into()));
+        assert!(map.contains((len - 1).into()));
+        assert!(!map.contains(len.into()));
+        assert!(!map.contains(U256::one() << 64));
+        assert!(!map.contains((U256::one() << 128) + 5));
+        assert!(!map.contains(U256::one() << 255));
+        assert!(!map.contains(U256::MAX));
+        // Low limb pointing at a JUMPDEST must not alias.
+        assert!(!map.contains((U256::one() << 192) + 2));
+    }
+}
@@ -0,0 +1,22 @@
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// Keccak-256 implementation used by execution, set in `ExecutionOptions::keccak`.
+///
+/// Implementations must compute standard Keccak-256, otherwise results are not
+/// consensus-valid.
+pub trait KeccakProvider: fmt::Debug + Send + Sync {
+    fn keccak256(&self, data: &[u8]) -> [u8; 32];
+}
+
+/// Keccak-256 of the `sha3` crate, used by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha3Keccak;
+
+impl KeccakProvider for Sha3Keccak {
+    fn keccak256(&self, data: &[u8]) -> [u8; 32] {
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&Keccak256::digest(data));
+        hash
+    }
+}
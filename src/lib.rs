@@ -1,22 +1,40 @@
 #![doc = include_str!("../README.md")]
 use bytes::Bytes;
 pub use common::{
-    CallKind, CreateMessage, Message, Output, Revision, StatusCode, SuccessfulOutput,
+    AccessCounts, AnalysisError, AnalysisStats, CallKind, Capability, CapabilityMask,
+    CreateFailure, CreateMessage, DecodeError, EofError, ExecutionStats, FailureSite, GasCharge,
+    GasReason, HostBudgetExceeded, Message, MutationKind, Output, RefundSource, Revision,
+    StatusCode, SuccessfulOutput,
 };
 pub use host::Host;
+pub use instructions::instruction_table::{
+    get_baseline_instruction_table, InstructionTable, InstructionTableEntry, InstructionTables,
+};
 pub use interpreter::AnalyzedCode;
+pub use keccak::{KeccakProvider, Sha3Keccak};
+pub use lint::{LintFinding, LintKind};
 pub use opcode::OpCode;
-pub use state::{ExecutionState, Stack};
+pub use options::{
+    ExecutionOptions, ExecutionOverrides, HostBudget, LoopDetectionConfig, PrefetchPlan,
+};
+pub use state::{ExecutionState, Stack, VerboseExecutionState};
 
 /// Maximum allowed EVM bytecode size.
 pub const MAX_CODE_SIZE: usize = 0x6000;
 
+/// Maximum allowed size of CREATE and CREATE2 init code since EIP-3860 (Shanghai).
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
 mod common;
 pub mod host;
 #[doc(hidden)]
 pub mod instructions;
 mod interpreter;
+mod keccak;
+mod lint;
 pub mod opcode;
+mod options;
+pub mod prelude;
 mod state;
 pub mod tracing;
 
@@ -26,3 +44,15 @@ pub mod util;
 
 #[cfg(feature = "evmc")]
 pub mod evmc;
+
+/// Instruction macros are crate-internal and must not leak into the crate root.
+///
+/// ```compile_fail
+/// use evmodin::sstore;
+/// ```
+///
+/// ```compile_fail
+/// use evmodin::do_call;
+/// ```
+#[cfg(doctest)]
+pub struct InstructionMacrosAreSealed;
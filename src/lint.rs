@@ -0,0 +1,268 @@
+use crate::{instructions::instruction_table::*, *};
+use ethereum_types::U256;
+use std::collections::HashSet;
+
+/// Kind of a gas-wasteful pattern found by [`AnalyzedCode::lint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintKind {
+    /// PUSH of zero where PUSH0 is available.
+    PushZero,
+    /// `SWAP1 POP POP`, which is equivalent to `POP POP`.
+    SwapPopPop,
+    /// `DUPn POP`, which does nothing.
+    DupPop,
+    /// JUMPDEST which is not a target of any jump and is only reached by falling through.
+    UntargetedJumpdest,
+    /// KECCAK256 of the same constant memory region as an earlier one, with no memory
+    /// writes in between.
+    RepeatedKeccak,
+    /// Instructions which can never be executed.
+    Unreachable,
+}
+
+/// Gas-wasteful pattern found by [`AnalyzedCode::lint`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintFinding {
+    /// Position of the first instruction of the pattern.
+    pub pc: usize,
+    pub kind: LintKind,
+    pub message: String,
+    /// Gas saved every time the pattern is executed, if it is rewritten.
+    pub estimated_savings: i64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Instruction {
+    pc: usize,
+    op: OpCode,
+    /// Immediate value of PUSH instructions.
+    immediate: Option<U256>,
+}
+
+fn decode(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = OpCode(code[pc]);
+        let push_len = op.push_size().map_or(0, usize::from);
+        // Truncated immediate is padded with zeros, as in execution.
+        let immediate = op.push_size().map(|_| {
+            let data = code.get(pc + 1..).unwrap_or_default();
+            let data = &data[..data.len().min(push_len)];
+            let mut bytes = [0; 32];
+            bytes[32 - push_len..][..data.len()].copy_from_slice(data);
+            U256::from_big_endian(&bytes)
+        });
+        instructions.push(Instruction { pc, op, immediate });
+        pc += 1 + push_len;
+    }
+    instructions
+}
+
+/// Whether execution never continues to the next instruction.
+fn is_terminating(op: OpCode, table: &InstructionTable) -> bool {
+    matches!(
+        op,
+        OpCode::STOP
+            | OpCode::JUMP
+            | OpCode::RETURN
+            | OpCode::REVERT
+            | OpCode::INVALID
+            | OpCode::SELFDESTRUCT
+    ) || table[op.to_usize()].is_none()
+}
+
+/// Static destination of a jump, if it is pushed by the preceding instruction.
+fn static_jump_target(instructions: &[Instruction], i: usize) -> Option<U256> {
+    i.checked_sub(1)
+        .and_then(|prev| instructions[prev].immediate)
+}
+
+/// Mark instructions which may be executed.
+///
+/// JUMPDESTs are only entered through jumps with destinations pushed right before them,
+/// unless some reachable jump has a computed destination, in which case any JUMPDEST may be
+/// entered.
+fn reachability(instructions: &[Instruction], table: &InstructionTable) -> Vec<bool> {
+    let index_of = |pc: U256| {
+        instructions
+            .binary_search_by(|i| U256::from(i.pc).cmp(&pc))
+            .ok()
+            .filter(|&i| instructions[i].op == OpCode::JUMPDEST)
+    };
+
+    let mut reachable = vec![false; instructions.len()];
+    let mut all_jumpdests_entered = false;
+    let mut worklist = vec![0];
+    while let Some(i) = worklist.pop() {
+        if i >= instructions.len() || reachable[i] {
+            continue;
+        }
+        reachable[i] = true;
+
+        let op = instructions[i].op;
+        if op == OpCode::JUMP || op == OpCode::JUMPI {
+            match static_jump_target(instructions, i) {
+                Some(dst) => worklist.extend(index_of(dst)),
+                None if !all_jumpdests_entered => {
+                    all_jumpdests_entered = true;
+                    worklist.extend(
+                        (0..instructions.len()).filter(|&j| instructions[j].op == OpCode::JUMPDEST),
+                    );
+                }
+                None => {}
+            }
+        }
+
+        if !is_terminating(op, table) {
+            worklist.push(i + 1);
+        }
+    }
+
+    reachable
+}
+
+fn gas(table: &InstructionTable, op: OpCode) -> i64 {
+    table[op.to_usize()].map_or(0, |entry| entry.gas_cost.into())
+}
+
+fn writes_memory(op: OpCode) -> bool {
+    matches!(
+        op,
+        OpCode::MSTORE
+            | OpCode::MSTORE8
+            | OpCode::CALLDATACOPY
+            | OpCode::CODECOPY
+            | OpCode::EXTCODECOPY
+            | OpCode::RETURNDATACOPY
+            | OpCode::MCOPY
+            | OpCode::CALL
+            | OpCode::CALLCODE
+            | OpCode::DELEGATECALL
+            | OpCode::STATICCALL
+    )
+}
+
+impl AnalyzedCode {
+    /// Find gas-wasteful patterns in reachable code, and report unreachable code.
+    pub fn lint(&self, revision: Revision) -> Vec<LintFinding> {
+        let table = get_baseline_instruction_table(revision);
+        let instructions = decode(self.code());
+        let reachable = reachability(&instructions, table);
+
+        let mut has_dynamic_jumps = false;
+        let mut static_targets = HashSet::new();
+        for (i, instruction) in instructions.iter().enumerate() {
+            if reachable[i] && matches!(instruction.op, OpCode::JUMP | OpCode::JUMPI) {
+                match static_jump_target(&instructions, i) {
+                    Some(dst) => {
+                        static_targets.insert(dst);
+                    }
+                    None => has_dynamic_jumps = true,
+                }
+            }
+        }
+
+        let op_at = |i: usize| instructions.get(i).map(|instruction| instruction.op);
+
+        let mut findings = Vec::new();
+        // Last KECCAK256 of a constant region in the current straight-line sequence.
+        let mut last_keccak: Option<(U256, U256)> = None;
+        let mut i = 0;
+        while i < instructions.len() {
+            let Instruction { pc, op, immediate } = instructions[i];
+
+            if !reachable[i] {
+                let start = i;
+                while i < instructions.len() && !reachable[i] {
+                    i += 1;
+                }
+                findings.push(LintFinding {
+                    pc,
+                    kind: LintKind::Unreachable,
+                    message: format!("{} unreachable instructions", i - start),
+                    estimated_savings: 0,
+                });
+                last_keccak = None;
+                continue;
+            }
+
+            if op == OpCode::JUMPDEST || writes_memory(op) || is_terminating(op, table) {
+                last_keccak = None;
+            }
+
+            if let (Some(push_len), Some(value)) = (op.push_size(), immediate) {
+                if value.is_zero() {
+                    if let Some(push0) = table[OpCode::PUSH0.to_usize()] {
+                        findings.push(LintFinding {
+                            pc,
+                            kind: LintKind::PushZero,
+                            message: format!("PUSH{} of zero can be replaced with PUSH0", push_len),
+                            estimated_savings: gas(table, op) - i64::from(push0.gas_cost),
+                        });
+                    }
+                }
+            }
+
+            if op == OpCode::SWAP1
+                && op_at(i + 1) == Some(OpCode::POP)
+                && op_at(i + 2) == Some(OpCode::POP)
+            {
+                findings.push(LintFinding {
+                    pc,
+                    kind: LintKind::SwapPopPop,
+                    message: "SWAP1 before POP POP can be removed".into(),
+                    estimated_savings: gas(table, op),
+                });
+            }
+
+            if (OpCode::DUP1.to_u8()..=OpCode::DUP16.to_u8()).contains(&op.to_u8())
+                && op_at(i + 1) == Some(OpCode::POP)
+            {
+                findings.push(LintFinding {
+                    pc,
+                    kind: LintKind::DupPop,
+                    message: format!("{} POP does nothing", op),
+                    estimated_savings: gas(table, op) + gas(table, OpCode::POP),
+                });
+            }
+
+            if op == OpCode::JUMPDEST && !has_dynamic_jumps && !static_targets.contains(&pc.into())
+            {
+                findings.push(LintFinding {
+                    pc,
+                    kind: LintKind::UntargetedJumpdest,
+                    message: "JUMPDEST is never jumped to".into(),
+                    estimated_savings: gas(table, op),
+                });
+            }
+
+            if op == OpCode::KECCAK256 && i >= 2 {
+                if let (Some(offset), Some(size)) =
+                    (instructions[i - 1].immediate, instructions[i - 2].immediate)
+                {
+                    if last_keccak == Some((offset, size)) {
+                        // Keep the earlier hash on the stack with DUP instead of two PUSHes
+                        // and a KECCAK256.
+                        let words = size.min(u32::MAX.into()).as_u64().div_ceil(32) as i64;
+                        findings.push(LintFinding {
+                            pc: instructions[i - 2].pc,
+                            kind: LintKind::RepeatedKeccak,
+                            message: "KECCAK256 of the same memory region is computed again".into(),
+                            estimated_savings: gas(table, op)
+                                + 6 * words
+                                + gas(table, instructions[i - 1].op)
+                                + gas(table, instructions[i - 2].op)
+                                - gas(table, OpCode::DUP1),
+                        });
+                    }
+                    last_keccak = Some((offset, size));
+                }
+            }
+
+            i += 1;
+        }
+
+        findings
+    }
+}
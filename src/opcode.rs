@@ -1,6 +1,10 @@
 use std::{borrow::Cow, fmt::Display};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub mod doc;
+
+pub use doc::export_json;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct OpCode(pub u8);
 
 impl OpCode {
@@ -85,6 +89,10 @@ impl OpCode {
     pub const MSIZE: OpCode = OpCode(0x59);
     pub const GAS: OpCode = OpCode(0x5a);
     pub const JUMPDEST: OpCode = OpCode(0x5b);
+    pub const TLOAD: OpCode = OpCode(0x5c);
+    pub const TSTORE: OpCode = OpCode(0x5d);
+    pub const MCOPY: OpCode = OpCode(0x5e);
+    pub const PUSH0: OpCode = OpCode(0x5f);
 
     pub const PUSH1: OpCode = OpCode(0x60);
     pub const PUSH2: OpCode = OpCode(0x61);
@@ -237,6 +245,10 @@ impl OpCode {
             OpCode::MSIZE => "MSIZE",
             OpCode::GAS => "GAS",
             OpCode::JUMPDEST => "JUMPDEST",
+            OpCode::TLOAD => "TLOAD",
+            OpCode::TSTORE => "TSTORE",
+            OpCode::MCOPY => "MCOPY",
+            OpCode::PUSH0 => "PUSH0",
             OpCode::PUSH1 => "PUSH1",
             OpCode::PUSH2 => "PUSH2",
             OpCode::PUSH3 => "PUSH3",
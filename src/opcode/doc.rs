@@ -0,0 +1,179 @@
+//! Machine-readable opcode documentation.
+
+use super::OpCode;
+use crate::{
+    instructions::{properties::gas_costs, PROPERTIES},
+    Revision,
+};
+use serde::{Deserialize, Serialize};
+
+/// Short description of an opcode, `None` for undefined ones.
+pub fn description(opcode: OpCode) -> Option<&'static str> {
+    Some(match opcode {
+        OpCode::STOP => "Halt execution successfully without output.",
+        OpCode::ADD => "Addition modulo 2^256.",
+        OpCode::MUL => "Multiplication modulo 2^256.",
+        OpCode::SUB => "Subtraction modulo 2^256.",
+        OpCode::DIV => "Unsigned integer division. Division by zero yields zero.",
+        OpCode::SDIV => "Signed integer division. Division by zero yields zero.",
+        OpCode::MOD => "Unsigned modulo. Modulo zero yields zero.",
+        OpCode::SMOD => "Signed modulo, the result takes the sign of the dividend.",
+        OpCode::ADDMOD => "Addition of the first two items modulo the third, without overflow.",
+        OpCode::MULMOD => {
+            "Multiplication of the first two items modulo the third, without overflow."
+        }
+        OpCode::EXP => "Exponentiation modulo 2^256.",
+        OpCode::SIGNEXTEND => {
+            "Extend the sign of a two's complement integer of the given byte length."
+        }
+        OpCode::LT => "Unsigned less-than comparison.",
+        OpCode::GT => "Unsigned greater-than comparison.",
+        OpCode::SLT => "Signed less-than comparison.",
+        OpCode::SGT => "Signed greater-than comparison.",
+        OpCode::EQ => "Equality comparison.",
+        OpCode::ISZERO => "Push 1 if the item is zero, 0 otherwise.",
+        OpCode::AND => "Bitwise AND.",
+        OpCode::OR => "Bitwise OR.",
+        OpCode::XOR => "Bitwise XOR.",
+        OpCode::NOT => "Bitwise NOT.",
+        OpCode::BYTE => "Retrieve a single byte of a word, counting from the most significant one.",
+        OpCode::SHL => "Left shift.",
+        OpCode::SHR => "Logical right shift.",
+        OpCode::SAR => "Arithmetic right shift.",
+        OpCode::KECCAK256 => "Compute Keccak-256 hash of a memory region.",
+        OpCode::ADDRESS => "Push the address of the currently executing account.",
+        OpCode::BALANCE => "Push the balance of the given account.",
+        OpCode::ORIGIN => "Push the transaction origin address.",
+        OpCode::CALLER => "Push the address of the caller.",
+        OpCode::CALLVALUE => "Push the value deposited by the call.",
+        OpCode::CALLDATALOAD => "Push a word of input data at the given offset.",
+        OpCode::CALLDATASIZE => "Push the size of input data.",
+        OpCode::CALLDATACOPY => "Copy input data to memory.",
+        OpCode::CODESIZE => "Push the size of the executing code.",
+        OpCode::CODECOPY => "Copy the executing code to memory.",
+        OpCode::GASPRICE => "Push the gas price of the transaction.",
+        OpCode::EXTCODESIZE => "Push the code size of the given account.",
+        OpCode::EXTCODECOPY => "Copy code of the given account to memory.",
+        OpCode::RETURNDATASIZE => "Push the size of data returned by the last call.",
+        OpCode::RETURNDATACOPY => "Copy data returned by the last call to memory.",
+        OpCode::EXTCODEHASH => "Push the code hash of the given account.",
+        OpCode::BLOCKHASH => "Push the hash of one of the 256 most recent blocks.",
+        OpCode::COINBASE => "Push the beneficiary address of the block.",
+        OpCode::TIMESTAMP => "Push the timestamp of the block.",
+        OpCode::NUMBER => "Push the number of the block.",
+        OpCode::DIFFICULTY => "Push the difficulty of the block.",
+        OpCode::GASLIMIT => "Push the gas limit of the block.",
+        OpCode::CHAINID => "Push the chain ID.",
+        OpCode::SELFBALANCE => "Push the balance of the currently executing account.",
+        OpCode::BASEFEE => "Push the base fee of the block.",
+        OpCode::POP => "Remove an item from the stack.",
+        OpCode::MLOAD => "Load a word from memory.",
+        OpCode::MSTORE => "Store a word to memory.",
+        OpCode::MSTORE8 => "Store a single byte to memory.",
+        OpCode::SLOAD => "Load a word from storage.",
+        OpCode::SSTORE => "Store a word to storage.",
+        OpCode::JUMP => "Jump to the given JUMPDEST.",
+        OpCode::JUMPI => "Jump to the given JUMPDEST if the condition is non-zero.",
+        OpCode::PC => "Push the position of this instruction.",
+        OpCode::MSIZE => "Push the size of active memory in bytes.",
+        OpCode::GAS => "Push the amount of gas left after this instruction.",
+        OpCode::JUMPDEST => "Mark a valid jump destination.",
+        OpCode::TLOAD => "Load a word from transient storage.",
+        OpCode::TSTORE => "Store a word to transient storage.",
+        OpCode::MCOPY => "Copy memory to memory, handling overlapping regions.",
+        OpCode::PUSH0 => "Push zero.",
+        op if (OpCode::PUSH1.0..=OpCode::PUSH32.0).contains(&op.0) => {
+            "Push the immediate value following the instruction."
+        }
+        op if (OpCode::DUP1.0..=OpCode::DUP16.0).contains(&op.0) => {
+            "Duplicate the n-th stack item."
+        }
+        op if (OpCode::SWAP1.0..=OpCode::SWAP16.0).contains(&op.0) => {
+            "Swap the top stack item with the (n+1)-th one."
+        }
+        op if (OpCode::LOG0.0..=OpCode::LOG4.0).contains(&op.0) => {
+            "Emit a log record with memory data and n topics."
+        }
+        OpCode::CREATE => "Create a new account with the given init code.",
+        OpCode::CALL => "Message-call into an account.",
+        OpCode::CALLCODE => "Message-call into this account with code of another account.",
+        OpCode::RETURN => "Halt execution returning a memory region.",
+        OpCode::DELEGATECALL => {
+            "Message-call into this account with code of another account, \
+             keeping the caller and value."
+        }
+        OpCode::CREATE2 => {
+            "Create a new account at an address derived from the salt and init code."
+        }
+        OpCode::STATICCALL => "Message-call into an account disallowing state modifications.",
+        OpCode::REVERT => "Halt execution reverting state changes and returning a memory region.",
+        OpCode::INVALID => "Designated invalid instruction, consumes all gas.",
+        OpCode::SELFDESTRUCT => "Halt execution and register the account for deletion.",
+        _ => return None,
+    })
+}
+
+/// Gas cost of an opcode in a revision.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionGas {
+    pub revision: String,
+    pub gas: u16,
+}
+
+/// Documentation of a single opcode.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpCodeDoc {
+    pub name: String,
+    pub value: u8,
+    /// Number of stack items consumed.
+    pub stack_in: u8,
+    /// Number of stack items produced.
+    pub stack_out: u8,
+    /// First revision the opcode is defined in.
+    pub introduced_in: String,
+    /// Static gas cost in every revision the opcode is defined in.
+    pub gas: Vec<RevisionGas>,
+    pub description: String,
+}
+
+/// First revision in which the opcode is defined.
+pub fn introduced_in(opcode: OpCode) -> Option<Revision> {
+    Revision::iter()
+        .into_iter()
+        .find(|&revision| gas_costs(revision)[opcode.to_usize()].is_some())
+}
+
+/// Documentation of every opcode defined in the latest revision, ordered by value.
+pub fn opcodes() -> Vec<OpCodeDoc> {
+    (0..=u8::MAX)
+        .map(OpCode)
+        .filter(|opcode| gas_costs(Revision::latest())[opcode.to_usize()].is_some())
+        .map(|opcode| {
+            let properties = PROPERTIES[opcode.to_usize()].unwrap();
+
+            OpCodeDoc {
+                name: properties.name.to_string(),
+                value: opcode.to_u8(),
+                stack_in: properties.stack_height_required,
+                stack_out: (properties.stack_height_required as i8 + properties.stack_height_change)
+                    as u8,
+                introduced_in: introduced_in(opcode).unwrap().to_string(),
+                gas: Revision::iter()
+                    .into_iter()
+                    .filter_map(|revision| {
+                        gas_costs(revision)[opcode.to_usize()].map(|gas| RevisionGas {
+                            revision: revision.to_string(),
+                            gas,
+                        })
+                    })
+                    .collect(),
+                description: description(opcode).unwrap_or_default().to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Export documentation of all opcodes as JSON.
+pub fn export_json() -> String {
+    serde_json::to_string(&opcodes()).unwrap()
+}
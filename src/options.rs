@@ -0,0 +1,175 @@
+use crate::{common::CapabilityMask, KeccakProvider, OpCode, Sha3Keccak};
+use bytes::Bytes;
+use ethereum_types::*;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+
+/// State overrides for the duration of a call, as in `eth_call`.
+///
+/// Overridden queries are answered by the execution driver without consulting the host.
+/// Code override for the entry address must already be reflected in the executed `AnalyzedCode`.
+/// Nested calls are executed by the host, which is responsible for applying overrides there.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionOverrides {
+    /// Code of accounts, also used for code size and hash queries.
+    pub code: HashMap<Address, Bytes>,
+    /// Balance of accounts.
+    pub balance: HashMap<Address, U256>,
+    /// Values of storage slots at the start of execution. Writes to overridden slots are
+    /// passed on to the host, but their gas and refunds are computed from the overridden
+    /// values.
+    pub storage: HashMap<(Address, H256), H256>,
+}
+
+impl ExecutionOverrides {
+    /// Whether this account is overridden, and thus implied to exist.
+    pub fn contains_account(&self, address: Address) -> bool {
+        self.code.contains_key(&address)
+            || self.balance.contains_key(&address)
+            || self.storage.keys().any(|(a, _)| *a == address)
+    }
+}
+
+/// Host queries predicted by the caller, e.g. with `util::derive_access_list` from a previous
+/// simulation.
+///
+/// The driver makes them up front with `Host::get_storage_many` and `Host::get_balances`,
+/// and serves matching queries of the executed frame from the results. Other queries go to
+/// the host as usual. Prefetched values are dropped once the frame writes the storage key or
+/// makes a call, which may change them.
+#[derive(Clone, Debug, Default)]
+pub struct PrefetchPlan {
+    /// Storage keys of accounts, fetched with one `Host::get_storage_many` per account.
+    pub storage: Vec<(Address, Vec<H256>)>,
+    /// Accounts whose balance is fetched with `Host::get_balances`.
+    pub balances: Vec<Address>,
+}
+
+/// Heuristic detection of loops which are bound to run out of gas.
+///
+/// Every `sample_interval` instructions the interpreter fingerprints the current
+/// instruction (pc, stack height and memory size). When the same fingerprint recurs twice
+/// in a row with identical gas spent per iteration and no host interaction in between,
+/// the loop is reported in `ExecutionStats::loop_detected`.
+///
+/// This is a heuristic: the fingerprint ignores stack and memory contents, so a loop
+/// exiting on a data-dependent condition, such as a countdown, is reported as well.
+/// Detection is report-only and never changes the result of execution.
+#[derive(Clone, Copy, Debug)]
+pub struct LoopDetectionConfig {
+    /// Number of instructions between fingerprint samples.
+    pub sample_interval: u64,
+}
+
+impl Default for LoopDetectionConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval: 1024,
+        }
+    }
+}
+
+/// Limits on host traffic, protecting remote hosts from contracts issuing excessive queries.
+///
+/// Enforced by the execution driver for the current call frame. Exhausting either budget
+/// terminates execution with `StatusCode::InternalError`.
+#[derive(Clone, Copy, Debug)]
+pub struct HostBudget {
+    /// Maximum number of host interrupts.
+    pub max_interrupts: u64,
+    /// Maximum number of bytes returned by the host: code copies, call outputs,
+    /// and 32 bytes per storage value.
+    pub max_bytes_returned: u64,
+}
+
+/// Options for execution drivers and the interpreter.
+#[derive(Clone, Debug)]
+pub struct ExecutionOptions {
+    /// State overrides applied by the driver.
+    pub overrides: ExecutionOverrides,
+    /// Opt-in detection of loops bound to run out of gas.
+    pub loop_detection: Option<LoopDetectionConfig>,
+    /// Limits on host traffic, enforced by the driver.
+    pub host_budget: Option<HostBudget>,
+    /// Host queries made up front by the driver.
+    pub prefetch: Option<PrefetchPlan>,
+    /// Maximum length of input data of nested calls and of init code of nested creates,
+    /// enforced by the driver. A larger call or create fails with `StatusCode::Failure`
+    /// before the host is called, and its gas is returned to the caller.
+    pub max_call_input_size: Option<usize>,
+    /// Return data buffer contents before the first instruction, as if left by a previous call.
+    /// Applied by the interpreter. Meant for testing: it is always empty in real executions.
+    pub initial_return_data: Bytes,
+    /// Memory contents before the first instruction, padded with zeros to whole words.
+    /// Applied by the interpreter, and no gas is charged for this memory. Meant for
+    /// reproducing an instruction in the middle of execution without replaying the code
+    /// preceding it.
+    pub initial_memory: Bytes,
+    /// Whether running out of gas terminates execution. Enabled by default.
+    ///
+    /// Without metering gas is still charged and may go negative, so gas used is reported,
+    /// but execution never fails with `StatusCode::OutOfGas`. This is meant for exploring
+    /// control flow without a gas budget, and results are not consensus-valid. Nested calls
+    /// are executed by the host and get whatever gas is left, if any.
+    pub metering: bool,
+    /// Whether a RETURN or REVERT which cannot pay for memory expansion returns the part of
+    /// its region already in memory instead of failing with `StatusCode::OutOfGas`.
+    /// Disabled by default.
+    ///
+    /// Truncation is reported in `ExecutionStats::output_truncated`. This is meant for showing
+    /// partial output, such as a revert reason, in simulations with a tight gas budget, and
+    /// results are not consensus-valid.
+    pub truncate_output_on_oog_return: bool,
+    /// Whether `Output::gas_left` reports the gas left at `ExecutionStats::failure_site`
+    /// instead of zero. Disabled by default.
+    ///
+    /// This is meant for error messages of developer tooling, and results are not
+    /// consensus-valid. The EVMC interface always reports zero.
+    pub preserve_gas_on_failure: bool,
+    /// Capabilities of the executed frame, applied by the interpreter unless the frame is
+    /// static. Default capabilities are those of EVM.
+    pub capabilities: CapabilityMask,
+    /// Instructions at which the interpreter pauses with `InstructionStart` even if execution
+    /// has been started without `trace`, so that a driver can observe them without tracing
+    /// every instruction.
+    pub instruction_start_pcs: BTreeSet<usize>,
+    /// Opcode which replaces the key on top of the stack with its value from
+    /// `Host::get_extended_context`, applied by the interpreter. It takes precedence over
+    /// an instruction with the same opcode, if any.
+    ///
+    /// Keys above `u32::MAX` and keys unsupported by the host terminate execution with
+    /// `StatusCode::InternalError`. This is meant for chains exposing extra block data to
+    /// contracts, and is not part of EVM.
+    pub extended_context_opcode: Option<OpCode>,
+    /// Opcodes which fail with `StatusCode::UndefinedInstruction` once their requirements are
+    /// checked, applied by the interpreter. This is meant for sandboxed execution, e.g.
+    /// forbidding SELFDESTRUCT, and results are not consensus-valid.
+    pub disabled_opcodes: HashSet<OpCode>,
+    /// Keccak-256 implementation used by KECCAK256 and by the driver for hashes of
+    /// overridden code. Defaults to `Sha3Keccak`.
+    pub keccak: Arc<dyn KeccakProvider>,
+}
+
+impl Default for ExecutionOptions {
+    fn default() -> Self {
+        Self {
+            overrides: Default::default(),
+            loop_detection: None,
+            host_budget: None,
+            prefetch: None,
+            max_call_input_size: None,
+            initial_return_data: Bytes::new(),
+            initial_memory: Bytes::new(),
+            metering: true,
+            truncate_output_on_oog_return: false,
+            preserve_gas_on_failure: false,
+            capabilities: CapabilityMask::default(),
+            instruction_start_pcs: BTreeSet::new(),
+            extended_context_opcode: None,
+            disabled_opcodes: HashSet::new(),
+            keccak: Arc::new(Sha3Keccak),
+        }
+    }
+}
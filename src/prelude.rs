@@ -0,0 +1,10 @@
+//! Commonly used types, intended for glob import: `use evmodin::prelude::*;`.
+
+pub use crate::{
+    host::{AccessStatus, Host, StorageStatus, TxContext},
+    tracing::{NoopTracer, StdoutTracer, Tracer},
+    AnalyzedCode, CallKind, ExecutionOptions, Message, OpCode, Output, Revision, StatusCode,
+};
+
+#[cfg(feature = "util")]
+pub use crate::util::{mocked_host::MockedHost, Bytecode, CallInstruction, EvmTester};
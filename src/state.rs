@@ -1,9 +1,21 @@
-use crate::common::{Message, Revision};
+#[cfg(feature = "gas-trace")]
+use crate::{common::GasCharge, opcode::OpCode};
+use crate::{
+    common::{
+        AccessCounts, CallKind, Capability, CapabilityMask, GasReason, Message, MutationKind,
+        RefundSource, Revision, StatusCode,
+    },
+    continuation::CallFrame,
+    host::AccessStatus,
+    KeccakProvider, Sha3Keccak,
+};
 use arrayvec::ArrayVec;
 use bytes::Bytes;
+use educe::Educe;
 use ethereum_types::U256;
 use getset::{Getters, MutGetters};
 use serde::Serialize;
+use std::{borrow::Cow, fmt, sync::Arc};
 
 const SIZE: usize = 1024;
 
@@ -24,6 +36,20 @@ impl Stack {
         &self.0[self.get_pos(pos)]
     }
 
+    /// Item `pos` places below the top, or `None` if the stack is not that deep.
+    pub fn try_get(&self, pos: usize) -> Option<&U256> {
+        if pos < self.len() {
+            Some(self.get(pos))
+        } else {
+            None
+        }
+    }
+
+    /// Items from the top of the stack down.
+    pub fn iter_top_down(&self) -> impl ExactSizeIterator<Item = &U256> {
+        self.0.iter().rev()
+    }
+
     pub fn get_mut(&mut self, pos: usize) -> &mut U256 {
         let pos = self.get_pos(pos);
         &mut self.0[pos]
@@ -54,33 +80,271 @@ impl Stack {
 
 pub type Memory = Vec<u8>;
 
+fn fmt_stack_len(stack: &Stack, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(&stack.len(), f)
+}
+
+fn fmt_memory_len(memory: &Memory, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(&memory.len(), f)
+}
+
+fn fmt_bytes_len(bytes: &Bytes, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(&bytes.len(), f)
+}
+
+fn fmt_message_summary(message: &Message, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+        f,
+        "{:?} depth={} recipient={:?} input_len={}",
+        message.kind,
+        message.depth,
+        message.recipient,
+        message.input_data.len()
+    )
+}
+
+/// Gas charges recorded with the `gas-trace` feature, and the instruction they are
+/// attributed to.
+#[cfg(feature = "gas-trace")]
+#[derive(Clone, Debug)]
+pub(crate) struct GasTrace {
+    pub(crate) pc: usize,
+    pub(crate) opcode: OpCode,
+    pub(crate) charges: Vec<GasCharge>,
+}
+
 /// EVM execution state.
-#[derive(Clone, Debug, Getters, MutGetters)]
+///
+/// `Debug` output only shows sizes of the stack, memory and buffers, so that it stays
+/// readable for any state. Use [`ExecutionState::verbose`] to print their contents.
+#[derive(Clone, Educe, Getters, MutGetters)]
+#[educe(Debug)]
 pub struct ExecutionState {
     #[getset(get = "pub", get_mut = "pub")]
     pub(crate) gas_left: i64,
     #[getset(get = "pub", get_mut = "pub")]
+    pub(crate) gas_refund: i64,
+    #[getset(get = "pub", get_mut = "pub")]
+    #[educe(Debug(name = "stack_len", method = "fmt_stack_len"))]
     pub(crate) stack: Stack,
     #[getset(get = "pub", get_mut = "pub")]
+    #[educe(Debug(name = "memory_len", method = "fmt_memory_len"))]
     pub(crate) memory: Memory,
-    pub(crate) message: Message,
+    #[educe(Debug(method = "fmt_message_summary"))]
+    pub(crate) message: Arc<Message>,
     pub(crate) evm_revision: Revision,
     #[getset(get = "pub", get_mut = "pub")]
+    #[educe(Debug(name = "return_data_len", method = "fmt_bytes_len"))]
     pub(crate) return_data: Bytes,
+    #[educe(Debug(name = "output_data_len", method = "fmt_bytes_len"))]
     pub(crate) output_data: Bytes,
+    /// Whether running out of gas terminates execution, see `ExecutionOptions::metering`.
+    pub(crate) metering: bool,
+    /// See `ExecutionOptions::truncate_output_on_oog_return`.
+    pub(crate) truncate_output_on_oog_return: bool,
+    pub(crate) output_truncated: bool,
+    /// Program counter and gas left at the start of the current instruction.
+    pub(crate) instruction_start: (usize, i64),
+    pub(crate) mutation_observed: Option<MutationKind>,
+    pub(crate) accesses: AccessCounts,
+    pub(crate) capabilities: CapabilityMask,
+    /// See `ExecutionOptions::keccak`.
+    #[educe(Debug(ignore))]
+    pub(crate) keccak: Arc<dyn KeccakProvider>,
+    #[cfg(feature = "gas-trace")]
+    #[educe(Debug(ignore))]
+    pub(crate) gas_trace: GasTrace,
+}
+
+/// Full `Debug` view of [`ExecutionState`], including stack and memory contents.
+pub struct VerboseExecutionState<'a>(&'a ExecutionState);
+
+impl fmt::Debug for VerboseExecutionState<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.0;
+        f.debug_struct("ExecutionState")
+            .field("gas_left", &state.gas_left)
+            .field("gas_refund", &state.gas_refund)
+            .field("stack", &state.stack)
+            .field("memory", &state.memory)
+            .field("message", &state.message)
+            .field("evm_revision", &state.evm_revision)
+            .field("return_data", &state.return_data)
+            .field("output_data", &state.output_data)
+            .finish()
+    }
 }
 
 impl ExecutionState {
-    pub fn new(message: Message, evm_revision: Revision) -> Self {
+    pub fn new(mut message: Message, evm_revision: Revision) -> Self {
+        // Init code is executed as code, so create frames have no input, even if the message
+        // carries the init code as its input.
+        if matches!(message.kind, CallKind::Create | CallKind::Create2 { .. }) {
+            message.input_data = Bytes::new();
+        }
+        let capabilities = CapabilityMask::for_frame(&message, CapabilityMask::default());
         Self {
             gas_left: message.gas,
+            gas_refund: 0,
             stack: Default::default(),
             memory: Memory::with_capacity(4 * 1024),
-            message,
+            message: Arc::new(message),
             evm_revision,
             return_data: Default::default(),
             output_data: Bytes::new(),
+            metering: true,
+            truncate_output_on_oog_return: false,
+            output_truncated: false,
+            instruction_start: (0, 0),
+            mutation_observed: None,
+            accesses: AccessCounts::default(),
+            capabilities,
+            keccak: Arc::new(Sha3Keccak),
+            #[cfg(feature = "gas-trace")]
+            gas_trace: GasTrace {
+                pc: 0,
+                opcode: OpCode::STOP,
+                charges: Vec::new(),
+            },
+        }
+    }
+
+    /// Whether execution must terminate with `StatusCode::OutOfGas`.
+    pub(crate) fn out_of_gas(&self) -> bool {
+        self.metering && self.gas_left < 0
+    }
+
+    /// Call frame as seen by the driver of an interrupt raised by the current instruction.
+    pub(crate) fn frame(&self) -> CallFrame {
+        CallFrame {
+            message: self.message.clone(),
+            gas_left: self.gas_left,
+            pc: self.instruction_start.0,
+        }
+    }
+
+    /// Record a request to the host which may change persistent state, unless one has
+    /// already been recorded.
+    pub(crate) fn observe_mutation(&mut self, kind: MutationKind) {
+        self.mutation_observed.get_or_insert(kind);
+    }
+
+    /// Count an account access, which costs `surcharge` if cold.
+    pub(crate) fn count_account_access(&mut self, status: AccessStatus, surcharge: u16) {
+        match status {
+            AccessStatus::Cold => {
+                self.accesses.cold_account_accesses += 1;
+                self.accesses.cold_surcharge_gas_total += i64::from(surcharge);
+            }
+            AccessStatus::Warm => self.accesses.warm_account_accesses += 1,
+        }
+    }
+
+    /// Count a storage access, which costs `surcharge` if cold.
+    pub(crate) fn count_storage_access(&mut self, status: AccessStatus, surcharge: u16) {
+        match status {
+            AccessStatus::Cold => {
+                self.accesses.cold_storage_accesses += 1;
+                self.accesses.cold_surcharge_gas_total += i64::from(surcharge);
+            }
+            AccessStatus::Warm => self.accesses.warm_storage_accesses += 1,
+        }
+    }
+
+    /// Deduct gas.
+    pub(crate) fn charge_gas(&mut self, amount: i64, reason: GasReason) {
+        self.gas_left -= amount;
+        self.trace_gas(amount, reason);
+    }
+
+    /// Fail with `StatusCode::StaticModeViolation` unless the frame has `capability`.
+    pub(crate) fn require_capability(&self, capability: Capability) -> Result<(), StatusCode> {
+        if self.capabilities.contains(capability) {
+            Ok(())
+        } else {
+            Err(StatusCode::StaticModeViolation)
+        }
+    }
+
+    /// Deduct gas, failing with `StatusCode::OutOfGas` if it runs out while metering.
+    pub(crate) fn use_gas(&mut self, amount: i64, reason: GasReason) -> Result<(), StatusCode> {
+        let out_of_gas = self
+            .gas_left
+            .checked_sub(amount)
+            .map_or(true, |left| left < 0);
+        self.charge_gas(amount, reason);
+        if out_of_gas && self.metering {
+            return Err(StatusCode::OutOfGas);
         }
+        Ok(())
+    }
+
+    /// Give gas back.
+    pub(crate) fn credit_gas(&mut self, amount: i64, reason: GasReason) {
+        self.gas_left += amount;
+        self.trace_gas(-amount, reason);
+    }
+
+    /// Add to the refund counter.
+    pub(crate) fn add_refund(&mut self, amount: i64, source: RefundSource) {
+        self.gas_refund += amount;
+        self.trace_gas(amount, GasReason::Refund { source });
+    }
+
+    /// Record a gas change already applied to the state.
+    #[cfg(feature = "gas-trace")]
+    pub(crate) fn trace_gas(&mut self, amount: i64, reason: GasReason) {
+        if amount != 0 {
+            self.gas_trace.charges.push(GasCharge {
+                pc: self.gas_trace.pc,
+                opcode: self.gas_trace.opcode,
+                amount,
+                reason,
+            });
+        }
+    }
+
+    #[cfg(not(feature = "gas-trace"))]
+    pub(crate) fn trace_gas(&mut self, _: i64, _: GasReason) {}
+
+    /// Read `len` bytes of memory at `offset`. Bytes past the end of memory read as zeros,
+    /// as in EVM. Memory is borrowed if the range is within it.
+    ///
+    /// `len` is clamped to the memory size, so that the zero padding of a read far past
+    /// the end of memory does not allocate more than memory itself.
+    ///
+    /// This only observes the state: memory is not expanded and no gas is charged.
+    pub fn memory_read(&self, offset: usize, len: usize) -> Cow<'_, [u8]> {
+        self.read_padded(offset, len.min(self.memory.len()))
+    }
+
+    /// Read `n` words of memory at `offset`, see [`ExecutionState::memory_read`].
+    ///
+    /// `n` is clamped to the number of words needed to hold memory.
+    pub fn peek_words(&self, offset: usize, n: usize) -> Vec<U256> {
+        let n = n.min((self.memory.len() + 31) / 32);
+        self.read_padded(offset, n * 32)
+            .chunks(32)
+            .map(U256::from_big_endian)
+            .collect()
+    }
+
+    fn read_padded(&self, offset: usize, len: usize) -> Cow<'_, [u8]> {
+        let end = offset.saturating_add(len);
+        if end <= self.memory.len() {
+            return Cow::Borrowed(&self.memory[offset..end]);
+        }
+
+        let mut data = vec![0; len];
+        if let Some(available) = self.memory.get(offset..) {
+            data[..available.len()].copy_from_slice(available);
+        }
+        Cow::Owned(data)
+    }
+
+    /// View of the state whose `Debug` output includes stack and memory contents.
+    pub fn verbose(&self) -> VerboseExecutionState<'_> {
+        VerboseExecutionState(self)
     }
 }
 
@@ -88,6 +352,20 @@ impl ExecutionState {
 mod tests {
     use super::*;
 
+    fn message() -> Message {
+        Message {
+            kind: crate::CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas: 1_000_000,
+            recipient: Default::default(),
+            code_address: Default::default(),
+            sender: Default::default(),
+            input_data: Default::default(),
+            value: U256::zero(),
+        }
+    }
+
     #[test]
     fn stack() {
         let mut stack = Stack::default();
@@ -104,5 +382,92 @@ mod tests {
         assert_eq!(stack.pop(), 0xef.into());
 
         assert_eq!(*stack.get(2), 0xde.into());
+
+        assert_eq!(stack.try_get(2), Some(&0xde.into()));
+        assert_eq!(stack.try_get(3), None);
+        assert_eq!(stack.try_get(usize::MAX), None);
+        assert_eq!(
+            stack.iter_top_down().copied().collect::<Vec<_>>(),
+            [0xbe.into(), 0xad.into(), 0xde.into()]
+        );
+    }
+
+    #[test]
+    fn use_gas() {
+        let mut state = ExecutionState::new(message(), Revision::latest());
+        state.gas_left = 10;
+        assert_eq!(state.use_gas(3, GasReason::TableBase), Ok(()));
+        assert_eq!(state.use_gas(7, GasReason::TableBase), Ok(()));
+        assert_eq!(state.gas_left, 0);
+        assert_eq!(state.use_gas(0, GasReason::TableBase), Ok(()));
+        assert_eq!(
+            state.use_gas(1, GasReason::TableBase),
+            Err(StatusCode::OutOfGas)
+        );
+
+        state.gas_left = -1;
+        assert_eq!(
+            state.use_gas(i64::MAX, GasReason::TableBase),
+            Err(StatusCode::OutOfGas)
+        );
+
+        // Without metering gas left goes negative instead.
+        state.gas_left = 0;
+        state.metering = false;
+        assert_eq!(state.use_gas(5, GasReason::TableBase), Ok(()));
+        assert_eq!(state.gas_left, -5);
+    }
+
+    #[test]
+    fn memory_read() {
+        let mut state = ExecutionState::new(message(), Revision::latest());
+        state.memory = (1..=64).collect();
+
+        let read = state.memory_read(30, 4);
+        assert!(matches!(read, Cow::Borrowed(_)));
+        assert_eq!(*read, [31, 32, 33, 34]);
+
+        let read = state.memory_read(62, 4);
+        assert!(matches!(read, Cow::Owned(_)));
+        assert_eq!(*read, [63, 64, 0, 0]);
+
+        assert_eq!(*state.memory_read(100, 3), [0, 0, 0]);
+        assert_eq!(*state.memory_read(usize::MAX, 2), [0, 0]);
+        assert!(state.memory_read(64, 0).is_empty());
+
+        assert_eq!(
+            state.peek_words(32, 2),
+            [
+                U256::from_big_endian(&(33..=64).collect::<Vec<_>>()),
+                U256::zero()
+            ]
+        );
+
+        // Reads longer than memory are clamped to its size.
+        assert_eq!(state.memory_read(0, usize::MAX).len(), 64);
+        assert_eq!(*state.memory_read(usize::MAX, usize::MAX), [0; 64]);
+        assert_eq!(state.peek_words(0, usize::MAX).len(), 2);
+        assert_eq!(state.peek_words(usize::MAX, usize::MAX), [U256::zero(); 2]);
+    }
+
+    #[test]
+    fn debug_is_bounded() {
+        let message = Message {
+            input_data: vec![0xaa; 1024 * 1024].into(),
+            ..message()
+        };
+        let mut state = ExecutionState::new(message, Revision::latest());
+        state.memory.resize(1024 * 1024, 0xbb);
+        for i in 0..Stack::limit() {
+            state.stack.push(i.into());
+        }
+        state.return_data = vec![0xcc; 1024 * 1024].into();
+
+        let debug = format!("{:?}", state);
+        assert!(debug.len() < 512, "{}", debug);
+        assert!(debug.contains("memory_len: 1048576"), "{}", debug);
+        assert!(debug.contains("stack_len: 1024"), "{}", debug);
+
+        assert!(format!("{:?}", state.verbose()).len() > 1024 * 1024);
     }
 }
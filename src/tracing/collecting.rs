@@ -0,0 +1,55 @@
+use super::*;
+use std::{cell::RefCell, rc::Rc};
+
+/// Single instruction step recorded by `CollectingTracer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    /// Call depth of the executing frame.
+    pub depth: i32,
+    /// Program counter.
+    pub pc: usize,
+    /// Executed opcode.
+    pub opcode: OpCode,
+    /// Gas left before the instruction.
+    pub gas_left: i64,
+    /// Stack height before the instruction.
+    pub stack_height: usize,
+}
+
+/// Tracer which records executed steps in memory instead of printing them.
+///
+/// Clones share the same record, so a clone can be handed over to the executor
+/// and the steps read back from the original afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct CollectingTracer {
+    steps: Rc<RefCell<Vec<TraceStep>>>,
+    depths: Rc<RefCell<Vec<i32>>>,
+}
+
+impl CollectingTracer {
+    /// Steps recorded so far, in execution order.
+    pub fn steps(&self) -> Vec<TraceStep> {
+        self.steps.borrow().clone()
+    }
+}
+
+impl Tracer for CollectingTracer {
+    fn notify_execution_start(&mut self, _: Revision, message: Message, _: Bytes) {
+        self.depths.borrow_mut().push(message.depth);
+    }
+
+    fn notify_instruction_start(&mut self, pc: usize, opcode: OpCode, state: &ExecutionState) {
+        let depth = self.depths.borrow().last().copied().unwrap_or_default();
+        self.steps.borrow_mut().push(TraceStep {
+            depth,
+            pc,
+            opcode,
+            gas_left: state.gas_left,
+            stack_height: state.stack.len(),
+        });
+    }
+
+    fn notify_execution_end(&mut self, _: &Output) {
+        self.depths.borrow_mut().pop();
+    }
+}
@@ -0,0 +1,139 @@
+use super::*;
+use ethereum_types::U256;
+
+/// Destination of a JUMPI as far as static analysis can tell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JumpTarget {
+    /// Destination is pushed by the immediately preceding PUSH instruction.
+    Static(U256),
+    /// Destination is computed at runtime.
+    Dynamic,
+}
+
+/// Coverage of both successors of a single JUMPI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BranchCoverage {
+    /// Position of the JUMPI instruction.
+    pub jumpi_pc: usize,
+    /// Statically resolved jump destination.
+    pub target: JumpTarget,
+    /// Whether the jump has been taken.
+    pub taken_hit: bool,
+    /// Whether execution has fallen through to the next instruction.
+    pub fallthrough_hit: bool,
+}
+
+/// Tracer which records executed instructions and JUMPI outcomes of the outermost call frame.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageMap {
+    depth: usize,
+    code: Bytes,
+    last_pc: Option<usize>,
+    hit: Vec<bool>,
+    taken: Vec<bool>,
+    fallthrough: Vec<bool>,
+}
+
+impl CoverageMap {
+    /// Whether the instruction at `pc` has been executed.
+    pub fn is_hit(&self, pc: usize) -> bool {
+        self.hit.get(pc).copied().unwrap_or(false)
+    }
+
+    fn record_jumpi_outcome(&mut self, next_pc: Option<usize>) {
+        if let Some(last_pc) = self.last_pc {
+            if OpCode(self.code[last_pc]) == OpCode::JUMPI {
+                if next_pc.unwrap_or(last_pc + 1) == last_pc + 1 {
+                    self.fallthrough[last_pc] = true;
+                } else {
+                    self.taken[last_pc] = true;
+                }
+            }
+        }
+    }
+
+    /// Coverage of every JUMPI in the code, in program order.
+    pub fn branch_coverage(&self, code: &AnalyzedCode) -> Vec<BranchCoverage> {
+        let code = code.code();
+
+        let mut branches = Vec::new();
+        let mut prev_push = None;
+        let mut pc = 0;
+        while pc < code.len() {
+            let op = OpCode(code[pc]);
+
+            if op == OpCode::JUMPI {
+                branches.push(BranchCoverage {
+                    jumpi_pc: pc,
+                    target: prev_push.map_or(JumpTarget::Dynamic, JumpTarget::Static),
+                    taken_hit: self.taken.get(pc).copied().unwrap_or(false),
+                    fallthrough_hit: self.fallthrough.get(pc).copied().unwrap_or(false),
+                });
+            }
+
+            prev_push = None;
+            if let Some(push_len) = op.push_size() {
+                let push_len = usize::from(push_len);
+                if let Some(data) = code.get(pc + 1..pc + 1 + push_len) {
+                    prev_push = Some(U256::from_big_endian(data));
+                }
+                pc += push_len;
+            }
+            pc += 1;
+        }
+
+        branches
+    }
+
+    /// Share of JUMPI successors visited, from `0.0` to `1.0`.
+    ///
+    /// Code without any JUMPI is considered fully covered.
+    pub fn branch_coverage_ratio(&self, code: &AnalyzedCode) -> f64 {
+        let branches = self.branch_coverage(code);
+        if branches.is_empty() {
+            return 1.0;
+        }
+
+        let hit = branches
+            .iter()
+            .map(|b| usize::from(b.taken_hit) + usize::from(b.fallthrough_hit))
+            .sum::<usize>();
+
+        hit as f64 / (2 * branches.len()) as f64
+    }
+}
+
+impl Tracer for CoverageMap {
+    fn notify_execution_start(&mut self, _: Revision, _: Message, code: Bytes) {
+        self.depth += 1;
+        if self.depth == 1 {
+            // Coverage accumulates over executions of the same code.
+            if self.code != code {
+                self.hit = vec![false; code.len()];
+                self.taken = vec![false; code.len()];
+                self.fallthrough = vec![false; code.len()];
+                self.code = code;
+            }
+            self.last_pc = None;
+        }
+    }
+
+    fn notify_instruction_start(&mut self, pc: usize, _: OpCode, _: &ExecutionState) {
+        if self.depth != 1 {
+            return;
+        }
+
+        self.record_jumpi_outcome(Some(pc));
+        self.hit[pc] = true;
+        self.last_pc = Some(pc);
+    }
+
+    fn notify_execution_end(&mut self, output: &Output) {
+        if self.depth == 1 && output.status_code == StatusCode::Success {
+            // Falling through past the end of code does not start a new instruction.
+            self.record_jumpi_outcome(None);
+        }
+        self.last_pc = None;
+        self.depth -= 1;
+    }
+}
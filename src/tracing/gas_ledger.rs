@@ -0,0 +1,84 @@
+use super::*;
+
+/// Gas figures of a single call frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameGas {
+    /// Call depth of the frame.
+    pub depth: i32,
+    /// Gas made available to the frame.
+    pub gas_supplied: i64,
+    /// Gas returned to the caller when the frame ended.
+    pub gas_returned: i64,
+}
+
+impl FrameGas {
+    /// Gas consumed by the frame, including its nested calls.
+    pub fn gas_consumed(&self) -> i64 {
+        self.gas_supplied - self.gas_returned
+    }
+}
+
+/// Tracer which builds a ledger of gas supplied to and returned by each call frame.
+///
+/// The outermost frame is recorded on execution start and end, nested frames on call start
+/// and end. Frames deeper than the first level are only visible if the host executes them
+/// with this same tracer.
+#[derive(Clone, Debug, Default)]
+pub struct GasLedger {
+    frames: Vec<FrameGas>,
+    open: Vec<usize>,
+    executions: usize,
+}
+
+impl GasLedger {
+    /// All frames in the order they were entered.
+    pub fn frames(&self) -> &[FrameGas] {
+        &self.frames
+    }
+
+    /// Frames at provided call depth, in the order they were entered.
+    pub fn frames_at_depth(&self, depth: i32) -> impl Iterator<Item = &FrameGas> {
+        self.frames.iter().filter(move |frame| frame.depth == depth)
+    }
+
+    fn enter(&mut self, message: &Message) {
+        self.open.push(self.frames.len());
+        self.frames.push(FrameGas {
+            depth: message.depth,
+            gas_supplied: message.gas,
+            gas_returned: 0,
+        });
+    }
+
+    fn leave(&mut self, output: &Output) {
+        if let Some(frame) = self.open.pop() {
+            self.frames[frame].gas_returned = output.gas_left;
+        }
+    }
+}
+
+impl Tracer for GasLedger {
+    fn notify_execution_start(&mut self, _: Revision, message: Message, _: Bytes) {
+        if self.executions == 0 {
+            self.enter(&message);
+        }
+        self.executions += 1;
+    }
+
+    fn notify_instruction_start(&mut self, _: usize, _: OpCode, _: &ExecutionState) {}
+
+    fn notify_execution_end(&mut self, output: &Output) {
+        self.executions -= 1;
+        if self.executions == 0 {
+            self.leave(output);
+        }
+    }
+
+    fn notify_call_start(&mut self, message: &Message) {
+        self.enter(message);
+    }
+
+    fn notify_call_end(&mut self, output: &Output) {
+        self.leave(output);
+    }
+}
@@ -1,7 +1,22 @@
 use super::*;
 use crate::state::*;
+use ethereum_types::U256;
 use serde::Serialize;
 
+mod collecting;
+mod coverage;
+mod gas_ledger;
+mod on_failure;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+
+pub use collecting::*;
+pub use coverage::*;
+pub use gas_ledger::*;
+pub use on_failure::*;
+#[cfg(feature = "telemetry")]
+pub use telemetry::*;
+
 /// Passed into execution context to collect metrics.
 pub trait Tracer {
     #[doc(hidden)]
@@ -13,6 +28,10 @@ pub trait Tracer {
     fn notify_instruction_start(&mut self, pc: usize, opcode: OpCode, state: &ExecutionState);
     /// Called when execution ends.
     fn notify_execution_end(&mut self, output: &Output);
+    /// Called before a nested call or create is passed to the host.
+    fn notify_call_start(&mut self, _message: &Message) {}
+    /// Called when a nested call or create is returned from the host.
+    fn notify_call_end(&mut self, _output: &Output) {}
 }
 
 /// Tracer which does nothing.
@@ -38,12 +57,13 @@ struct ExecutionStart {
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct InstructionStart {
+pub(crate) struct InstructionStart<'a> {
     pub pc: usize,
     pub op: u8,
     pub op_name: &'static str,
     pub gas: i64,
-    pub stack: Stack,
+    /// From the top down.
+    pub stack: Vec<&'a U256>,
     pub memory_size: usize,
 }
 
@@ -91,7 +111,7 @@ impl Tracer for StdoutTracer {
                 op: opcode.0,
                 op_name: opcode.name(),
                 gas: state.gas_left,
-                stack: state.stack.clone(),
+                stack: state.stack.iter_top_down().collect(),
                 memory_size: state.memory.len()
             })
             .unwrap()
@@ -0,0 +1,90 @@
+use super::*;
+
+enum Event {
+    ExecutionStart(Revision, Message, Bytes),
+    InstructionStart(usize, OpCode, Box<ExecutionState>),
+    ExecutionEnd(Output),
+    CallStart(Message),
+    CallEnd(Output),
+}
+
+/// Tracer which buffers all notifications and forwards them to the inner tracer only if the
+/// outermost execution ends with a status other than `StatusCode::Success`.
+///
+/// A copy of the execution state is kept for every step until the outcome is known, so this
+/// trades memory for quiet logs in the common successful case.
+pub struct OnFailureTracer<T> {
+    inner: T,
+    buffer: Vec<Event>,
+    executions: usize,
+}
+
+impl<T> OnFailureTracer<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+            executions: 0,
+        }
+    }
+
+    /// Tracer which receives the notifications of failed executions.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Tracer> OnFailureTracer<T> {
+    fn flush(&mut self) {
+        for event in self.buffer.drain(..) {
+            match event {
+                Event::ExecutionStart(revision, message, code) => {
+                    self.inner.notify_execution_start(revision, message, code)
+                }
+                Event::InstructionStart(pc, opcode, state) => {
+                    self.inner.notify_instruction_start(pc, opcode, &state)
+                }
+                Event::ExecutionEnd(output) => self.inner.notify_execution_end(&output),
+                Event::CallStart(message) => self.inner.notify_call_start(&message),
+                Event::CallEnd(output) => self.inner.notify_call_end(&output),
+            }
+        }
+    }
+}
+
+impl<T: Tracer> Tracer for OnFailureTracer<T> {
+    fn notify_execution_start(&mut self, revision: Revision, message: Message, code: Bytes) {
+        self.executions += 1;
+        self.buffer
+            .push(Event::ExecutionStart(revision, message, code));
+    }
+
+    fn notify_instruction_start(&mut self, pc: usize, opcode: OpCode, state: &ExecutionState) {
+        self.buffer
+            .push(Event::InstructionStart(pc, opcode, Box::new(state.clone())));
+    }
+
+    fn notify_execution_end(&mut self, output: &Output) {
+        self.buffer.push(Event::ExecutionEnd(output.clone()));
+        self.executions -= 1;
+        if self.executions == 0 {
+            if output.status_code == StatusCode::Success {
+                self.buffer.clear();
+            } else {
+                self.flush();
+            }
+        }
+    }
+
+    fn notify_call_start(&mut self, message: &Message) {
+        self.buffer.push(Event::CallStart(message.clone()));
+    }
+
+    fn notify_call_end(&mut self, output: &Output) {
+        self.buffer.push(Event::CallEnd(output.clone()));
+    }
+}
@@ -0,0 +1,69 @@
+use super::*;
+use ::tracing::{span::EnteredSpan, Level};
+
+/// Tracer which reports execution to the `tracing` crate.
+///
+/// Every call frame gets an `evm_frame` span with its depth, kind and gas, closed with its
+/// status and gas left. The outermost frame is opened on execution start, nested frames on
+/// call start, so spans nest as the call tree does. Instructions are reported as `TRACE`
+/// events of the innermost span, so they can be filtered out by the subscriber.
+#[derive(Debug, Default)]
+pub struct TracingSubscriberTracer {
+    spans: Vec<EnteredSpan>,
+    executions: usize,
+}
+
+impl TracingSubscriberTracer {
+    fn enter(&mut self, message: &Message) {
+        let span = ::tracing::span!(
+            Level::DEBUG,
+            "evm_frame",
+            depth = message.depth,
+            kind = ?message.kind,
+            gas = message.gas,
+            status = ::tracing::field::Empty,
+            gas_left = ::tracing::field::Empty,
+        );
+        self.spans.push(span.entered());
+    }
+
+    fn leave(&mut self, output: &Output) {
+        if let Some(span) = self.spans.pop() {
+            span.record("status", &::tracing::field::display(&output.status_code));
+            span.record("gas_left", &output.gas_left);
+        }
+    }
+}
+
+impl Tracer for TracingSubscriberTracer {
+    fn notify_execution_start(&mut self, _: Revision, message: Message, _: Bytes) {
+        if self.executions == 0 {
+            self.enter(&message);
+        }
+        self.executions += 1;
+    }
+
+    fn notify_instruction_start(&mut self, pc: usize, opcode: OpCode, state: &ExecutionState) {
+        ::tracing::trace!(
+            pc,
+            op = opcode.name(),
+            gas_left = state.gas_left,
+            stack_height = state.stack.len()
+        );
+    }
+
+    fn notify_execution_end(&mut self, output: &Output) {
+        self.executions -= 1;
+        if self.executions == 0 {
+            self.leave(output);
+        }
+    }
+
+    fn notify_call_start(&mut self, message: &Message) {
+        self.enter(message);
+    }
+
+    fn notify_call_end(&mut self, output: &Output) {
+        self.leave(output);
+    }
+}
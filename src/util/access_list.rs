@@ -0,0 +1,36 @@
+use crate::{host::AccessStatus, util::mocked_host::*};
+use ethereum_types::{Address, H256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// EIP-2930 access list covering the accounts and storage slots `host` has seen accessed,
+/// sorted by address and key.
+///
+/// Precompiles are left out as they are always warm. The sender, the recipient and the
+/// coinbase are included if they were accessed, so a caller building a transaction may
+/// remove them. Only the first 200 account accesses are recorded by `MockedHost`.
+pub fn derive_access_list(host: &MockedHost) -> Vec<(Address, Vec<H256>)> {
+    let mut list = BTreeMap::<Address, BTreeSet<H256>>::new();
+
+    for &address in &host.records().account_accesses {
+        list.entry(address).or_default();
+    }
+
+    for (&address, account) in &host.accounts {
+        for (key, value) in &account.storage {
+            if value.access_status == AccessStatus::Warm {
+                let mut slot = H256::zero();
+                key.to_big_endian(&mut slot.0);
+                list.entry(address).or_default().insert(slot);
+            }
+        }
+    }
+
+    list.into_iter()
+        .filter(|(address, _)| !is_precompile(*address))
+        .map(|(address, keys)| (address, keys.into_iter().collect()))
+        .collect()
+}
+
+fn is_precompile(address: Address) -> bool {
+    address >= Address::from_low_u64_be(1) && address <= Address::from_low_u64_be(9)
+}
@@ -0,0 +1,66 @@
+use crate::{
+    instructions::PROPERTIES,
+    tracing::NoopTracer,
+    util::{mocked_host::MockedHost, Bytecode},
+    *,
+};
+use ethereum_types::{Address, U256};
+use std::time::{Duration, Instant};
+
+/// Time `iterations` executions of `op` with `operands` on the stack, `operands[0]` on top.
+///
+/// Code is `iterations` copies of operand pushes, `op` itself and POPs of everything it leaves
+/// on the stack, so the time includes those pushes and POPs. Compare opcodes with the same
+/// operands, or subtract the time of a cheap opcode such as ADD. Analysis is not timed.
+///
+/// Control flow instructions are not supported. Panics if `operands` do not match the stack
+/// inputs of `op`, or if execution fails.
+pub fn bench_opcode(
+    op: OpCode,
+    operands: &[U256],
+    iterations: usize,
+    revision: Revision,
+) -> Duration {
+    let properties = PROPERTIES[op.to_usize()].unwrap();
+    assert_eq!(
+        operands.len(),
+        usize::from(properties.stack_height_required),
+        "{} takes {} operands",
+        op,
+        properties.stack_height_required
+    );
+    let outputs = (i16::from(properties.stack_height_required)
+        + i16::from(properties.stack_height_change)) as usize;
+
+    let mut step = Bytecode::new();
+    for &operand in operands.iter().rev() {
+        step = step.pushv(operand);
+    }
+    step = step.opcode(op);
+    for _ in 0..outputs {
+        step = step.opcode(OpCode::POP);
+    }
+    let step = step.build();
+
+    let code = AnalyzedCode::analyze(step.repeat(iterations));
+    let message = Message {
+        kind: CallKind::Call,
+        is_static: false,
+        depth: 0,
+        gas: i64::MAX,
+        recipient: Address::zero(),
+        sender: Address::zero(),
+        input_data: Default::default(),
+        value: U256::zero(),
+        code_address: Address::zero(),
+    };
+    let mut host = MockedHost::default();
+
+    let start = Instant::now();
+    let output = code.execute(&mut host, &mut NoopTracer, None, message, revision);
+    let elapsed = start.elapsed();
+
+    assert_eq!(output.status_code, StatusCode::Success, "{}", op);
+
+    elapsed
+}
@@ -0,0 +1,95 @@
+use crate::{util::EvmTester, Output, StatusCode};
+
+/// Number of evenly spaced gas values checked for monotonicity.
+const MONOTONICITY_SAMPLES: i64 = 16;
+
+/// Result of `boundary_probe`.
+#[derive(Clone, Debug)]
+pub struct BoundaryReport {
+    /// Minimal gas at which execution does not run out of gas, if the status changes within the range.
+    pub boundary: Option<i64>,
+    /// Output with one gas less than the boundary.
+    pub below: Option<Output>,
+    /// Output with gas exactly at the boundary.
+    pub at: Option<Output>,
+    /// Probed gas values whose outcome contradicts the boundary.
+    pub non_monotonic: Vec<i64>,
+}
+
+fn probe(tester: &EvmTester, gas: i64) -> Output {
+    tester.clone().gas(gas).check_and_get_result()
+}
+
+fn out_of_gas(output: &Output) -> bool {
+    output.status_code == StatusCode::OutOfGas
+}
+
+/// Bisect gas within `[lo, hi]` to find the minimal amount at which execution stops running out of gas.
+///
+/// Every probe runs on a fresh clone of `tester`, so host state does not leak between probes.
+/// The tester must not have status or gas checks set. Once the boundary is found, a sample of
+/// gas values across the range is checked to agree with it, and disagreeing ones are reported
+/// in `BoundaryReport::non_monotonic`.
+pub fn boundary_probe(tester: EvmTester, lo: i64, hi: i64) -> BoundaryReport {
+    assert!(lo < hi, "empty gas range [{}, {}]", lo, hi);
+
+    let mut report = BoundaryReport {
+        boundary: None,
+        below: None,
+        at: None,
+        non_monotonic: vec![],
+    };
+
+    let mut below = probe(&tester, lo);
+    let mut at = probe(&tester, hi);
+    if !out_of_gas(&below) || out_of_gas(&at) {
+        return report;
+    }
+
+    // Invariant: execution with `l` gas runs out of gas, with `h` gas it does not.
+    let (mut l, mut h) = (lo, hi);
+    while h - l > 1 {
+        let mid = l + (h - l) / 2;
+        let output = probe(&tester, mid);
+        if out_of_gas(&output) {
+            l = mid;
+            below = output;
+        } else {
+            h = mid;
+            at = output;
+        }
+    }
+
+    let step = ((hi - lo) / MONOTONICITY_SAMPLES).max(1);
+    let mut gas = lo;
+    while gas <= hi {
+        if out_of_gas(&probe(&tester, gas)) != (gas < h) {
+            report.non_monotonic.push(gas);
+        }
+        gas += step;
+    }
+
+    report.boundary = Some(h);
+    report.below = Some(below);
+    report.at = Some(at);
+    report
+}
+
+/// Assert that `tester` runs out of gas with less than `expected` gas and does not with exactly `expected`.
+///
+/// The gas range `[0, 2 * expected]` is probed with [`boundary_probe`](crate::util::boundary_probe).
+/// Evaluates to the `BoundaryReport`.
+#[macro_export]
+macro_rules! assert_gas_boundary {
+    ($tester:expr, $expected:expr) => {{
+        let expected: i64 = $expected;
+        let report = $crate::util::boundary_probe($tester, 0, expected * 2);
+        assert_eq!(report.boundary, Some(expected), "gas boundary mismatch");
+        assert!(
+            report.non_monotonic.is_empty(),
+            "non-monotonic status at gas {:?}",
+            report.non_monotonic
+        );
+        report
+    }};
+}
@@ -1,17 +1,48 @@
-use crate::opcode::*;
+use crate::{opcode::*, Revision};
 use core::iter::repeat;
 use ethereum_types::U256;
-use std::ops::{Add, Mul};
+use std::{
+    collections::BTreeMap,
+    ops::{Add, Mul},
+};
+
+/// Width of label offsets, enough for any code within `MAX_CODE_SIZE`.
+const LABEL_WIDTH: usize = 2;
+
+/// Jump target.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Label {
+    Named(String),
+    /// Label of a control flow combinator. Numbers are unique within a `Bytecode` and are
+    /// shifted when it is appended to another one.
+    Generated(usize),
+}
 
 /// EVM bytecode builder.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Bytecode {
     inner: Vec<u8>,
+    revision: Option<Revision>,
+    labels: BTreeMap<Label, usize>,
+    fixups: Vec<(usize, Label)>,
+    generated_labels: usize,
 }
 
 impl Bytecode {
     pub const fn new() -> Self {
-        Self { inner: Vec::new() }
+        Self {
+            inner: Vec::new(),
+            revision: None,
+            labels: BTreeMap::new(),
+            fixups: Vec::new(),
+            generated_labels: 0,
+        }
+    }
+
+    /// Target code at the provided revision, enabling revision-specific encodings.
+    pub fn revision(mut self, revision: Revision) -> Self {
+        self.revision = Some(revision);
+        self
     }
 
     pub fn append(mut self, b: impl IntoIterator<Item = u8>) -> Self {
@@ -20,17 +51,48 @@ impl Bytecode {
     }
 
     pub fn append_bc(mut self, b: impl Into<Self>) -> Self {
-        self.inner.append(&mut b.into().build());
+        let Self {
+            mut inner,
+            revision: _,
+            labels,
+            fixups,
+            generated_labels,
+        } = b.into();
+
+        let base = self.inner.len();
+        let label_base = self.generated_labels;
+        let relocate = |label| match label {
+            Label::Generated(n) => Label::Generated(label_base + n),
+            named => named,
+        };
+        self.generated_labels += generated_labels;
+
+        self.inner.append(&mut inner);
+        for (label, offset) in labels {
+            self = self.define_label(relocate(label), base + offset);
+        }
+        for (offset, label) in fixups {
+            self = self.use_label(base + offset, relocate(label));
+        }
         self
     }
 
     pub fn repeat(mut self, n: usize) -> Self {
+        assert!(
+            self.labels.is_empty(),
+            "code with labels cannot be repeated"
+        );
         self.inner = repeat(self.inner.into_iter()).take(n).flatten().collect();
         self
     }
 
+    /// Push value with the shortest encoding. Zero is pushed with `Bytecode::push0`.
     pub fn pushv(self, value: impl Into<U256>) -> Self {
         let value = value.into();
+        if value.is_zero() {
+            return self.push0();
+        }
+
         let b = <[u8; 32]>::from(value)
             .iter()
             .skip_while(|&&v| v == 0)
@@ -40,6 +102,36 @@ impl Bytecode {
         self.pushb(b)
     }
 
+    /// Push value using exactly `width` bytes, regardless of its magnitude.
+    ///
+    /// Panics if the value does not fit.
+    pub fn pushv_width(self, value: impl Into<U256>, width: usize) -> Self {
+        let value = value.into();
+        assert!((1..=32).contains(&width), "invalid push width: {}", width);
+        assert!(
+            value.bits() <= width * 8,
+            "value {} does not fit into {} bytes",
+            value,
+            width
+        );
+
+        self.pushb(<[u8; 32]>::from(value)[32 - width..].to_vec())
+    }
+
+    /// Push value as PUSH32.
+    pub fn push32(self, value: impl Into<U256>) -> Self {
+        self.pushv_width(value, 32)
+    }
+
+    /// Push zero: PUSH0 if targeting Shanghai or later, `PUSH1 00` otherwise.
+    pub fn push0(self) -> Self {
+        if self.revision.map_or(false, |r| r >= Revision::Shanghai) {
+            self.opcode(OpCode::PUSH0)
+        } else {
+            self.pushv_width(0, 1)
+        }
+    }
+
     pub fn pushb(mut self, b: impl IntoIterator<Item = u8>) -> Self {
         let mut b = b.into_iter().collect::<Vec<_>>();
 
@@ -108,8 +200,8 @@ impl Bytecode {
     }
 
     pub fn jumpi(self, target: impl Into<Bytecode>, condition: impl Into<Bytecode>) -> Self {
-        self.append(condition.into().build())
-            .append(target.into().build())
+        self.append_bc(condition)
+            .append_bc(target)
             .opcode(OpCode::JUMPI)
     }
 
@@ -121,7 +213,224 @@ impl Bytecode {
         self.pushv(index).opcode(OpCode::SLOAD)
     }
 
+    fn copy_op(
+        self,
+        opcode: OpCode,
+        dst: impl Into<U256>,
+        src: impl Into<U256>,
+        len: impl Into<U256>,
+    ) -> Self {
+        self.pushv(len).pushv(src).pushv(dst).opcode(opcode)
+    }
+
+    pub fn calldatacopy(
+        self,
+        dst: impl Into<U256>,
+        src: impl Into<U256>,
+        len: impl Into<U256>,
+    ) -> Self {
+        self.copy_op(OpCode::CALLDATACOPY, dst, src, len)
+    }
+
+    pub fn mcopy(self, dst: impl Into<U256>, src: impl Into<U256>, len: impl Into<U256>) -> Self {
+        self.copy_op(OpCode::MCOPY, dst, src, len)
+    }
+
+    pub fn returndatacopy(
+        self,
+        dst: impl Into<U256>,
+        src: impl Into<U256>,
+        len: impl Into<U256>,
+    ) -> Self {
+        self.copy_op(OpCode::RETURNDATACOPY, dst, src, len)
+    }
+
+    pub fn codecopy(
+        self,
+        dst: impl Into<U256>,
+        src: impl Into<U256>,
+        len: impl Into<U256>,
+    ) -> Self {
+        self.copy_op(OpCode::CODECOPY, dst, src, len)
+    }
+
+    /// Init code which copies `runtime`, stored right after it, into memory and returns it.
+    pub fn deploy(runtime: impl Into<Vec<u8>>) -> Self {
+        let runtime = runtime.into();
+        let prefix = |offset: usize| {
+            Self::new()
+                .codecopy(0, offset, runtime.len())
+                .ret(0, runtime.len())
+        };
+        // Prefix is shorter than 256 bytes, so the offset always takes a single byte to push.
+        let offset = prefix(0).len();
+        prefix(offset).append(runtime)
+    }
+
+    /// Mark current position with JUMPDEST that can be targeted by `push_label`.
+    pub fn label(self, label: impl Into<String>) -> Self {
+        self.mark(Label::Named(label.into()))
+    }
+
+    /// Push label offset. Offset is always encoded with fixed width, so it stays stable.
+    pub fn push_label(self, label: impl Into<String>) -> Self {
+        self.push_target(Label::Named(label.into()))
+    }
+
+    /// Jump to label.
+    pub fn jump_to(self, label: impl Into<String>) -> Self {
+        self.push_label(label).opcode(OpCode::JUMP)
+    }
+
+    /// Jump to label if the value on top of the stack is non-zero.
+    pub fn jumpi_to(self, label: impl Into<String>) -> Self {
+        self.push_label(label).opcode(OpCode::JUMPI)
+    }
+
+    /// Execute `then_` if `cond` pushes a non-zero value, `else_` otherwise.
+    pub fn if_else(
+        mut self,
+        cond: impl Into<Bytecode>,
+        then_: impl Into<Bytecode>,
+        else_: impl Into<Bytecode>,
+    ) -> Self {
+        let then_label = self.new_label();
+        let end = self.new_label();
+        self.append_bc(cond)
+            .push_target(then_label.clone())
+            .opcode(OpCode::JUMPI)
+            .append_bc(else_)
+            .push_target(end.clone())
+            .opcode(OpCode::JUMP)
+            .mark(then_label)
+            .append_bc(then_)
+            .mark(end)
+    }
+
+    /// Execute `body` as long as `cond` pushes a non-zero value.
+    pub fn while_loop(mut self, cond: impl Into<Bytecode>, body: impl Into<Bytecode>) -> Self {
+        let start = self.new_label();
+        let end = self.new_label();
+        self.mark(start.clone())
+            .append_bc(cond)
+            .opcode(OpCode::ISZERO)
+            .push_target(end.clone())
+            .opcode(OpCode::JUMPI)
+            .append_bc(body)
+            .push_target(start)
+            .opcode(OpCode::JUMP)
+            .mark(end)
+    }
+
+    /// Execute a body `n` times, with the index counting from zero on top of the stack.
+    ///
+    /// `body` is given code pushing the index, which is valid while the stack is as at the
+    /// start of the body. The body must leave the stack as it has found it.
+    pub fn for_range(self, n: impl Into<U256>, body: impl FnOnce(Bytecode) -> Bytecode) -> Self {
+        let mut index = Self::new();
+        index.revision = self.revision;
+        let body = (body)(index.opcode(OpCode::DUP1));
+
+        self.pushv(0)
+            .while_loop(
+                Self::new().opcode(OpCode::DUP1).pushv(n).opcode(OpCode::GT),
+                body.pushv(1).opcode(OpCode::ADD),
+            )
+            .opcode(OpCode::POP)
+    }
+
+    /// Execute the arm whose value equals the one pushed by `selector`, if any.
+    pub fn switch<V: Into<U256>>(
+        mut self,
+        selector: impl Into<Bytecode>,
+        arms: impl IntoIterator<Item = (V, Bytecode)>,
+    ) -> Self {
+        let end = self.new_label();
+        let arms = arms
+            .into_iter()
+            .map(|(value, body)| (value.into(), self.new_label(), body))
+            .collect::<Vec<_>>();
+
+        self = self.append_bc(selector);
+        for (value, label, _) in &arms {
+            self = self
+                .opcode(OpCode::DUP1)
+                .pushv(*value)
+                .opcode(OpCode::EQ)
+                .push_target(label.clone())
+                .opcode(OpCode::JUMPI);
+        }
+        self = self
+            .opcode(OpCode::POP)
+            .push_target(end.clone())
+            .opcode(OpCode::JUMP);
+        for (_, label, body) in arms {
+            self = self
+                .mark(label)
+                .opcode(OpCode::POP)
+                .append_bc(body)
+                .push_target(end.clone())
+                .opcode(OpCode::JUMP);
+        }
+        self.mark(end)
+    }
+
+    fn new_label(&mut self) -> Label {
+        let label = Label::Generated(self.generated_labels);
+        self.generated_labels += 1;
+        label
+    }
+
+    fn mark(self, label: Label) -> Self {
+        let offset = self.inner.len();
+        self.define_label(label, offset).opcode(OpCode::JUMPDEST)
+    }
+
+    fn push_target(mut self, label: Label) -> Self {
+        let offset = self.inner.len() + 1;
+        self = self.pushv_width(0, LABEL_WIDTH);
+        self.use_label(offset, label)
+    }
+
+    fn define_label(mut self, label: Label, offset: usize) -> Self {
+        let fixups = std::mem::take(&mut self.fixups);
+        for (fixup, _) in fixups.iter().filter(|(_, l)| *l == label) {
+            self.write_label_offset(*fixup, offset);
+        }
+        self.fixups = fixups.into_iter().filter(|(_, l)| *l != label).collect();
+
+        assert!(
+            self.labels.insert(label.clone(), offset).is_none(),
+            "duplicate label: {:?}",
+            label
+        );
+        self
+    }
+
+    fn use_label(mut self, fixup: usize, label: Label) -> Self {
+        if let Some(&offset) = self.labels.get(&label) {
+            self.write_label_offset(fixup, offset);
+        } else {
+            self.fixups.push((fixup, label));
+        }
+        self
+    }
+
+    fn write_label_offset(&mut self, fixup: usize, offset: usize) {
+        assert!(offset < 1 << (LABEL_WIDTH * 8), "label offset too large");
+        self.inner[fixup..fixup + LABEL_WIDTH]
+            .copy_from_slice(&(offset as u16).to_be_bytes()[2 - LABEL_WIDTH..]);
+    }
+
     pub fn build(self) -> Vec<u8> {
+        assert!(
+            self.fixups.is_empty(),
+            "undefined labels: {:?}",
+            self.fixups
+                .iter()
+                .map(|(_, label)| label)
+                .collect::<Vec<_>>()
+        );
         self.inner
     }
 
@@ -148,15 +457,13 @@ impl From<OpCode> for Bytecode {
 
 impl<const N: usize> From<[u8; N]> for Bytecode {
     fn from(inner: [u8; N]) -> Self {
-        Self {
-            inner: Vec::from(&inner as &[u8]),
-        }
+        Self::new().append(inner)
     }
 }
 
 impl From<Vec<u8>> for Bytecode {
     fn from(inner: Vec<u8>) -> Self {
-        Self { inner }
+        Self::new().append(inner)
     }
 }
 
@@ -297,4 +604,157 @@ mod tests {
                 .opcode(OpCode::POP)
         )
     }
+
+    #[test]
+    fn pushv_width() {
+        assert_eq!(Bytecode::new().pushv(0).build(), [0x60, 0x00]);
+        assert_eq!(
+            Bytecode::new().pushv_width(0, 2).build(),
+            [0x61, 0x00, 0x00]
+        );
+        assert_eq!(Bytecode::new().pushv(0x100).build(), [0x61, 0x01, 0x00]);
+        assert_eq!(
+            Bytecode::new().pushv_width(0x100, 3).build(),
+            [0x62, 0x00, 0x01, 0x00]
+        );
+        assert_eq!(Bytecode::new().pushv_width(0xff, 1).build(), [0x60, 0xff]);
+        assert_eq!(
+            Bytecode::new().pushv_width(0xffff, 2).build(),
+            [0x61, 0xff, 0xff]
+        );
+
+        let push32 = Bytecode::new().push32(1).build();
+        assert_eq!(push32.len(), 33);
+        assert_eq!(push32[0], OpCode::PUSH32.to_u8());
+        assert_eq!(push32[32], 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pushv_width_too_small() {
+        let _ = Bytecode::new().pushv_width(0x100, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pushv_width_too_small_boundary() {
+        let _ = Bytecode::new().pushv_width(0x10000, 2);
+    }
+
+    #[test]
+    fn push0() {
+        assert_eq!(Bytecode::new().push0().build(), [0x60, 0x00]);
+        assert_eq!(
+            Bytecode::new().revision(Revision::London).push0().build(),
+            [0x60, 0x00]
+        );
+        assert_eq!(
+            Bytecode::new().revision(Revision::Shanghai).push0().build(),
+            [OpCode::PUSH0.to_u8()]
+        );
+    }
+
+    #[test]
+    fn pushv_zero() {
+        assert_eq!(Bytecode::new().pushv(0).build(), [0x60, 0x00]);
+        assert_eq!(
+            Bytecode::new().revision(Revision::London).pushv(0).build(),
+            [0x60, 0x00]
+        );
+        assert_eq!(
+            Bytecode::new()
+                .revision(Revision::Shanghai)
+                .pushv(0)
+                .build(),
+            [OpCode::PUSH0.to_u8()]
+        );
+        assert_eq!(
+            Bytecode::new()
+                .revision(Revision::Shanghai)
+                .pushv(1)
+                .build(),
+            [0x60, 0x01]
+        );
+    }
+
+    #[test]
+    fn labels() {
+        // Forward reference.
+        assert_eq!(
+            Bytecode::new()
+                .jump_to("end")
+                .opcode(OpCode::INVALID)
+                .label("end")
+                .build(),
+            [0x61, 0x00, 0x05, 0x56, 0xfe, 0x5b]
+        );
+
+        // Backward reference.
+        assert_eq!(
+            Bytecode::new()
+                .opcode(OpCode::STOP)
+                .label("start")
+                .jump_to("start")
+                .build(),
+            [0x00, 0x5b, 0x61, 0x00, 0x01, 0x56]
+        );
+
+        // Reference across appended fragments.
+        assert_eq!(
+            Bytecode::new()
+                .jump_to("end")
+                .append_bc(Bytecode::new().opcode(OpCode::INVALID).label("end"))
+                .build(),
+            [0x61, 0x00, 0x05, 0x56, 0xfe, 0x5b]
+        );
+    }
+
+    #[test]
+    fn generated_labels_do_not_collide() {
+        let loop_ =
+            || Bytecode::new().while_loop(OpCode::CALLVALUE, Bytecode::new().opcode(OpCode::STOP));
+
+        // Loops built separately get distinct labels once appended.
+        let code = (loop_() + loop_()).build();
+        assert_eq!(code.len(), 2 * loop_().len());
+        assert_eq!(code[loop_().len()..], {
+            let mut second = loop_().build();
+            // Jump targets of the second loop are shifted by the length of the first one.
+            for fixup in [5, 10] {
+                second[fixup] += loop_().len() as u8;
+            }
+            second
+        });
+
+        // Same for loops built in sequence, and named labels stay untouched.
+        let _ = loop_().label("a").append_bc(loop_()).jump_to("a").build();
+    }
+
+    #[test]
+    fn combinators_are_deterministic() {
+        let build = || {
+            Bytecode::new()
+                .for_range(3, |i| {
+                    Bytecode::new().if_else(
+                        i.opcode(OpCode::ISZERO),
+                        OpCode::JUMPDEST,
+                        Bytecode::new().switch(OpCode::CALLVALUE, [(1, OpCode::JUMPDEST.into())]),
+                    )
+                })
+                .build()
+        };
+        assert_eq!(build(), build());
+    }
+
+    #[test]
+    #[should_panic]
+    fn undefined_label() {
+        let _ = Bytecode::new().jump_to("nowhere").build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn duplicate_label() {
+        let _ = Bytecode::new().label("a").label("a");
+    }
 }
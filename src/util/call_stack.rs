@@ -0,0 +1,79 @@
+use crate::{
+    continuation::{
+        interrupt::{CallInterrupt, InterruptVariant},
+        interrupt_data::Call,
+        resume_data::CallOutput,
+        Interrupt,
+    },
+    interpreter::drive,
+    tracing::NoopTracer,
+    *,
+};
+
+/// Executes nested calls in-process without growing the native stack.
+///
+/// Calls made by the running frame pause it on an explicit stack of frames, and the callee
+/// is executed with code from [`Host::get_code`]. When the callee completes, its output
+/// resumes the caller. Native stack usage therefore does not depend on call depth, and
+/// memory grows with the number of paused frames only.
+///
+/// Only code is executed: balances are not moved and state changes of failed frames are not
+/// reverted. CREATE and CREATE2 are still passed to [`Host::call`].
+pub struct CallStackExecutor<'h, H> {
+    host: &'h mut H,
+    revision: Revision,
+    max_depth: usize,
+}
+
+impl<'h, H: Host> CallStackExecutor<'h, H> {
+    pub fn new(host: &'h mut H, revision: Revision) -> Self {
+        Self {
+            host,
+            revision,
+            max_depth: 0,
+        }
+    }
+
+    /// Deepest stack of paused frames seen so far.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Execute `message` and every call it makes.
+    pub fn execute(&mut self, message: Message) -> Output {
+        let mut paused: Vec<CallInterrupt> = Vec::new();
+        let mut interrupt = self.start(message);
+
+        loop {
+            match drive(
+                interrupt,
+                &mut *self.host,
+                &mut NoopTracer,
+                None,
+                &ExecutionOptions::default(),
+                |i| matches!(i, InterruptVariant::Call(i) if matches!(i.data(), Call::Call(_))),
+            ) {
+                Ok(output) => match paused.pop() {
+                    Some(caller) => interrupt = caller.resume(CallOutput { output }),
+                    None => return output,
+                },
+                Err(InterruptVariant::Call(call)) => {
+                    let message = match call.data() {
+                        Call::Call(message) => message.clone(),
+                        Call::Create(_) => unreachable!("only calls are breakpoints"),
+                    };
+                    paused.push(call);
+                    self.max_depth = self.max_depth.max(paused.len());
+                    interrupt = self.start(message);
+                }
+                Err(_) => unreachable!("only calls are breakpoints"),
+            }
+        }
+    }
+
+    fn start(&self, message: Message) -> InterruptVariant {
+        AnalyzedCode::analyze(self.host.get_code(message.code_address).to_vec())
+            .execute_resumable(false, message, self.revision)
+            .resume(())
+    }
+}
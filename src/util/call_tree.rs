@@ -0,0 +1,139 @@
+use crate::{util::mocked_host::*, *};
+use std::fmt::Write;
+
+/// Number of leading input and output bytes shown for every frame.
+const DATA_PREVIEW_LEN: usize = 4;
+
+/// Call frame with the frames it has spawned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallTreeNode {
+    pub message: Message,
+    /// Result of the frame, if known.
+    pub output: Option<Output>,
+    pub children: Vec<CallTreeNode>,
+}
+
+impl CallTreeNode {
+    /// Best-effort call tree from calls recorded by `host`, nested by their depth.
+    ///
+    /// `MockedHost` does not execute calls, so every frame gets its fixed `call_result`.
+    pub fn from_recording(host: &MockedHost) -> Vec<Self> {
+        fn attach(frames: &mut Vec<CallTreeNode>, frame: CallTreeNode) {
+            match frames.last_mut() {
+                Some(parent) if parent.message.depth < frame.message.depth => {
+                    attach(&mut parent.children, frame)
+                }
+                _ => frames.push(frame),
+            }
+        }
+
+        let mut frames = Vec::new();
+        for message in &host.records().calls {
+            attach(
+                &mut frames,
+                Self {
+                    message: message.clone(),
+                    output: Some(host.call_result.clone()),
+                    children: Vec::new(),
+                },
+            );
+        }
+        frames
+    }
+
+    fn failed(&self) -> bool {
+        self.output
+            .as_ref()
+            .map_or(false, |output| output.status_code != StatusCode::Success)
+    }
+}
+
+fn kind(message: &Message) -> &'static str {
+    match message.kind {
+        CallKind::Call if message.is_static => "STATICCALL",
+        CallKind::Call => "CALL",
+        CallKind::DelegateCall => "DELEGATECALL",
+        CallKind::CallCode => "CALLCODE",
+        CallKind::Create => "CREATE",
+        CallKind::Create2 { .. } => "CREATE2",
+    }
+}
+
+fn preview(data: &[u8]) -> String {
+    let mut s = format!(
+        "0x{}",
+        hex::encode(&data[..data.len().min(DATA_PREVIEW_LEN)])
+    );
+    if data.len() > DATA_PREVIEW_LEN {
+        s.push_str("..");
+    }
+    s
+}
+
+fn render(
+    out: &mut String,
+    frame: &CallTreeNode,
+    level: usize,
+    max_depth: usize,
+    reverted: bool,
+) -> std::fmt::Result {
+    let reverted = reverted || frame.failed();
+    let indent = "  ".repeat(level);
+    let marker = if reverted { 'x' } else { '-' };
+
+    write!(
+        out,
+        "{}{} {} {:?} value={} gas={}",
+        indent,
+        marker,
+        kind(&frame.message),
+        frame.message.recipient,
+        frame.message.value,
+        frame.message.gas
+    )?;
+    match &frame.output {
+        Some(output) => write!(
+            out,
+            "->{} {} input={} output={}",
+            output.gas_left,
+            output.status_code,
+            preview(&frame.message.input_data),
+            preview(&output.output_data)
+        )?,
+        None => write!(out, " pending input={}", preview(&frame.message.input_data))?,
+    }
+    writeln!(out)?;
+
+    if frame.children.is_empty() {
+        return Ok(());
+    }
+
+    if level + 1 >= max_depth {
+        fn count(frames: &[CallTreeNode]) -> usize {
+            frames.iter().map(|f| 1 + count(&f.children)).sum()
+        }
+        return writeln!(
+            out,
+            "{}  ... {} nested frame(s)",
+            indent,
+            count(&frame.children)
+        );
+    }
+
+    for child in &frame.children {
+        render(out, child, level + 1, max_depth, reverted)?;
+    }
+
+    Ok(())
+}
+
+/// Render call frames as an indented tree, one line per frame, down to `max_depth` levels.
+///
+/// Frames that failed, and all frames below them, are marked with `x` instead of `-`.
+pub fn render_call_tree(frames: &[CallTreeNode], max_depth: usize) -> String {
+    let mut out = String::new();
+    for frame in frames {
+        render(&mut out, frame, 0, max_depth, false).unwrap();
+    }
+    out
+}
@@ -0,0 +1,134 @@
+use crate::{host::*, *};
+use bytes::Bytes;
+use ethereum_types::{Address, H256, U256};
+
+/// Host forwarding every method to `inner`, except those replaced with a hook.
+///
+/// Meant for tests which change how a few host methods behave, e.g. executing callees
+/// in-process, without reimplementing the rest of `Host`. Hooks are plain functions taking
+/// the whole host, so that a `call` hook can execute the callee against it. State they need
+/// is kept in `state`.
+pub struct DelegatingHost<H, S = ()> {
+    pub inner: H,
+    /// State of the hooks.
+    pub state: S,
+    call: Option<fn(&mut Self, &Message) -> Output>,
+    extended_context: Option<fn(&Self, u32) -> anyhow::Result<U256>>,
+}
+
+impl<H: Default, S: Default> Default for DelegatingHost<H, S> {
+    fn default() -> Self {
+        Self::new(H::default(), S::default())
+    }
+}
+
+impl<H, S> DelegatingHost<H, S> {
+    pub fn new(inner: H, state: S) -> Self {
+        Self {
+            inner,
+            state,
+            call: None,
+            extended_context: None,
+        }
+    }
+
+    /// Serve `Host::call` with `hook`.
+    pub fn on_call(mut self, hook: fn(&mut Self, &Message) -> Output) -> Self {
+        self.call = Some(hook);
+        self
+    }
+
+    /// Serve `Host::get_extended_context` with `hook`.
+    pub fn on_extended_context(mut self, hook: fn(&Self, u32) -> anyhow::Result<U256>) -> Self {
+        self.extended_context = Some(hook);
+        self
+    }
+}
+
+impl<H: Host, S> Host for DelegatingHost<H, S> {
+    fn account_exists(&self, address: Address) -> bool {
+        self.inner.account_exists(address)
+    }
+
+    fn get_storage(&self, address: Address, key: U256) -> U256 {
+        self.inner.get_storage(address, key)
+    }
+
+    fn get_storage_many(&self, address: Address, keys: &[H256]) -> Vec<H256> {
+        self.inner.get_storage_many(address, keys)
+    }
+
+    fn set_storage(&mut self, address: Address, key: U256, value: U256) -> StorageStatus {
+        self.inner.set_storage(address, key, value)
+    }
+
+    fn get_transient_storage(&self, address: Address, key: U256) -> U256 {
+        self.inner.get_transient_storage(address, key)
+    }
+
+    fn set_transient_storage(&mut self, address: Address, key: U256, value: U256) {
+        self.inner.set_transient_storage(address, key, value)
+    }
+
+    fn get_balance(&self, address: Address) -> U256 {
+        self.inner.get_balance(address)
+    }
+
+    fn get_balances(&self, addresses: &[Address]) -> Vec<U256> {
+        self.inner.get_balances(addresses)
+    }
+
+    fn get_code_size(&self, address: Address) -> U256 {
+        self.inner.get_code_size(address)
+    }
+
+    fn get_code_hash(&self, address: Address) -> U256 {
+        self.inner.get_code_hash(address)
+    }
+
+    fn copy_code(&self, address: Address, offset: usize, buffer: &mut [u8]) -> usize {
+        self.inner.copy_code(address, offset, buffer)
+    }
+
+    fn get_code(&self, address: Address) -> Bytes {
+        self.inner.get_code(address)
+    }
+
+    fn selfdestruct(&mut self, address: Address, beneficiary: Address) -> bool {
+        self.inner.selfdestruct(address, beneficiary)
+    }
+
+    fn call(&mut self, msg: &Message) -> Output {
+        match self.call {
+            Some(hook) => hook(self, msg),
+            None => self.inner.call(msg),
+        }
+    }
+
+    fn get_tx_context(&self) -> TxContext {
+        self.inner.get_tx_context()
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> U256 {
+        self.inner.get_block_hash(block_number)
+    }
+
+    fn emit_log(&mut self, address: Address, data: &[u8], topics: &[U256]) {
+        self.inner.emit_log(address, data, topics)
+    }
+
+    fn access_account(&mut self, address: Address) -> AccessStatus {
+        self.inner.access_account(address)
+    }
+
+    fn access_storage(&mut self, address: Address, key: U256) -> AccessStatus {
+        self.inner.access_storage(address, key)
+    }
+
+    fn get_extended_context(&self, key: u32) -> anyhow::Result<U256> {
+        match self.extended_context {
+            Some(hook) => hook(self, key),
+            None => self.inner.get_extended_context(key),
+        }
+    }
+}
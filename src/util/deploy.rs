@@ -0,0 +1,119 @@
+use crate::{instructions::fee::CODE_DEPOSIT_COST, tracing::NoopTracer, util::mocked_host::*, *};
+use bytes::Bytes;
+use ethereum_types::{Address, U256};
+use sha3::{Digest, Keccak256};
+
+/// Address of the contract created by `sender` with `nonce`, as in CREATE.
+pub fn create_address(sender: Address, nonce: u64) -> Address {
+    let nonce_bytes = nonce.to_be_bytes();
+    let nonce_bytes = &nonce_bytes[nonce_bytes.iter().take_while(|&&b| b == 0).count()..];
+
+    // RLP of [sender, nonce].
+    let mut payload = vec![0x80 + 20];
+    payload.extend_from_slice(sender.as_bytes());
+    match nonce_bytes {
+        [b] if *b < 0x80 => payload.push(*b),
+        _ => {
+            payload.push(0x80 + nonce_bytes.len() as u8);
+            payload.extend_from_slice(nonce_bytes);
+        }
+    }
+
+    let mut rlp = vec![0xc0 + payload.len() as u8];
+    rlp.append(&mut payload);
+
+    Address::from_slice(&Keccak256::digest(&rlp)[12..])
+}
+
+/// Run init code as a top-level CREATE from the zero address and deploy the returned code
+/// into `host`.
+///
+/// Returns the output of init code execution and the address of the deployed contract
+/// if deployment has been successful.
+pub fn deploy(
+    init_code: impl Into<Bytecode>,
+    host: &mut MockedHost,
+    revision: Revision,
+) -> (Output, Option<Address>) {
+    let sender = Address::zero();
+    let sender_account = host.accounts.entry(sender).or_default();
+    let nonce = sender_account.nonce;
+    sender_account.nonce += 1;
+
+    let address = create_address(sender, nonce);
+
+    let message = Message {
+        kind: CallKind::Create,
+        is_static: false,
+        depth: 0,
+        gas: i64::MAX,
+        recipient: address,
+        code_address: address,
+        sender,
+        input_data: Bytes::new(),
+        value: U256::zero(),
+    };
+
+    host.warm_up(revision, sender, address);
+
+    run_create(init_code.into().build(), host, message, revision)
+}
+
+/// Execute init code for `message` and deploy the returned code at its recipient.
+pub(crate) fn run_create(
+    init_code: Vec<u8>,
+    host: &mut MockedHost,
+    message: Message,
+    revision: Revision,
+) -> (Output, Option<Address>) {
+    let address = message.recipient;
+
+    let mut output =
+        AnalyzedCode::analyze(init_code).execute(host, &mut NoopTracer, None, message, revision);
+
+    if output.status_code != StatusCode::Success {
+        output.stats.create_failure = Some(CreateFailure::InitFailed);
+        return (output, None);
+    }
+
+    let mut code = output.output_data.clone();
+    if revision >= Revision::Spurious && code.len() > MAX_CODE_SIZE {
+        output.status_code = StatusCode::OutOfGas;
+        output.gas_left = 0;
+        output.stats.create_failure = Some(CreateFailure::CodeTooLarge);
+        return (output, None);
+    }
+
+    // EIP-3541.
+    if revision >= Revision::London && code.first() == Some(&0xef) {
+        output.status_code = StatusCode::ContractValidationFailure;
+        output.gas_left = 0;
+        output.stats.create_failure = Some(CreateFailure::InvalidCodePrefix);
+        return (output, None);
+    }
+
+    let deposit_cost = code.len() as i64 * i64::from(CODE_DEPOSIT_COST);
+    if output.gas_left < deposit_cost {
+        if revision >= Revision::Homestead {
+            output.status_code = StatusCode::OutOfGas;
+            output.gas_left = 0;
+            output.stats.create_failure = Some(CreateFailure::DepositOutOfGas);
+            return (output, None);
+        }
+        // Frontier deploys empty code instead.
+        code = Bytes::new();
+    } else {
+        output.gas_left -= deposit_cost;
+    }
+
+    let account = host.accounts.entry(address).or_default();
+    if revision >= Revision::Spurious {
+        account.nonce = 1;
+    }
+    account.code_hash = U256::from_big_endian(&Keccak256::digest(&code));
+    account.code = code;
+
+    output.create_address = Some(address);
+
+    (output, Some(address))
+}
@@ -0,0 +1,54 @@
+use crate::tracing::TraceStep;
+
+/// Field of `TraceStep` in which two traces diverge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceField {
+    Depth,
+    Pc,
+    Opcode,
+    GasLeft,
+    StackHeight,
+    /// One of the traces has ended while the other has not.
+    Length,
+}
+
+/// First divergence between two traces, see `diff_executions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the first differing step.
+    pub step: usize,
+    /// First differing field of that step.
+    pub field: TraceField,
+}
+
+/// Find the first divergence between two traces, e.g. recorded by `CollectingTracer`.
+///
+/// Returns `None` if traces are identical.
+pub fn diff_executions(a: &[TraceStep], b: &[TraceStep]) -> Option<Divergence> {
+    for (step, (a, b)) in a.iter().zip(b).enumerate() {
+        let field = if a.depth != b.depth {
+            TraceField::Depth
+        } else if a.pc != b.pc {
+            TraceField::Pc
+        } else if a.opcode != b.opcode {
+            TraceField::Opcode
+        } else if a.gas_left != b.gas_left {
+            TraceField::GasLeft
+        } else if a.stack_height != b.stack_height {
+            TraceField::StackHeight
+        } else {
+            continue;
+        };
+
+        return Some(Divergence { step, field });
+    }
+
+    if a.len() != b.len() {
+        return Some(Divergence {
+            step: a.len().min(b.len()),
+            field: TraceField::Length,
+        });
+    }
+
+    None
+}
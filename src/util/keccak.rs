@@ -0,0 +1,32 @@
+use crate::{KeccakProvider, Sha3Keccak};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Keccak provider counting the hashes computed by the inner provider.
+///
+/// Set an `Arc` of it in `ExecutionOptions::keccak` and keep a clone to read the count.
+#[derive(Debug, Default)]
+pub struct CountingKeccak<P = Sha3Keccak> {
+    inner: P,
+    count: AtomicU64,
+}
+
+impl<P> CountingKeccak<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of hashes computed so far.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+impl<P: KeccakProvider> KeccakProvider for CountingKeccak<P> {
+    fn keccak256(&self, data: &[u8]) -> [u8; 32] {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.inner.keccak256(data)
+    }
+}
@@ -3,7 +3,7 @@ use bytes::Bytes;
 use ethereum_types::*;
 use hex_literal::hex;
 use parking_lot::Mutex;
-use std::{cmp::min, collections::HashMap};
+use std::{cmp::min, collections::HashMap, ops::Deref};
 
 /// LOG record.
 #[derive(Clone, Debug, PartialEq)]
@@ -27,14 +27,17 @@ pub struct SelfdestructRecord {
     pub beneficiary: Address,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct StorageValue {
     pub value: U256,
     pub dirty: bool,
+    /// Value before the first modification, recorded by `set_storage`. A slot marked dirty
+    /// without it is treated as modified again on every write.
+    pub original: Option<U256>,
     pub access_status: AccessStatus,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Account {
     /// The account nonce.
     pub nonce: u64,
@@ -49,15 +52,29 @@ pub struct Account {
 }
 
 const MAX_RECORDED_ACCOUNT_ACCESSES: usize = 200;
+const MAX_RECORDED_STORAGE_READS: usize = 200;
+const MAX_RECORDED_STORAGE_BATCHES: usize = 100;
 const MAX_RECORDED_CALLS: usize = 100;
-
-#[derive(Clone, Debug, Default)]
+const MAX_RECORDED_CODE_FETCHES: usize = 100;
+
+/// Host interactions recorded by `MockedHost`.
+///
+/// Every list is appended to in the order the host methods are invoked, which is execution
+/// order. Tests may rely on this, e.g. interleaved BALANCE and CALL record their accounts in
+/// the order the instructions were executed.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Records {
     /// The copy of call inputs for the recorded_calls record.
     pub call_inputs: Vec<Bytes>,
 
     pub blockhashes: Vec<u64>,
     pub account_accesses: Vec<Address>,
+    /// Keys read with `Host::get_storage`.
+    pub storage_reads: Vec<(Address, U256)>,
+    /// Keys read with `Host::get_storage_many`, one entry per call.
+    pub storage_batches: Vec<(Address, Vec<H256>)>,
+    /// Accounts whose code has been read with `Host::get_code`.
+    pub code_fetches: Vec<Address>,
     pub calls: Vec<Message>,
     pub logs: Vec<LogRecord>,
     pub selfdestructs: Vec<SelfdestructRecord>,
@@ -69,6 +86,11 @@ pub struct MockedHost {
     pub tx_context: TxContext,
     pub block_hash: U256,
     pub call_result: Output,
+    /// Values of `Host::get_extended_context`, other keys are not supported.
+    pub extended_context: HashMap<u32, U256>,
+    /// Transient storage (EIP-1153) of the current transaction, see
+    /// [`MockedHost::clear_transient_storage`].
+    pub transient_storage: HashMap<(Address, U256), U256>,
     pub recorded: Mutex<Records>,
 }
 
@@ -79,6 +101,8 @@ impl Clone for MockedHost {
             tx_context: self.tx_context.clone(),
             block_hash: self.block_hash,
             call_result: self.call_result.clone(),
+            extended_context: self.extended_context.clone(),
+            transient_storage: self.transient_storage.clone(),
             recorded: Mutex::new(self.recorded.lock().clone()),
         }
     }
@@ -88,29 +112,61 @@ impl Default for MockedHost {
     fn default() -> Self {
         Self {
             accounts: Default::default(),
-            tx_context: TxContext {
-                tx_gas_price: U256::zero(),
-                tx_origin: Address::zero(),
-                block_coinbase: Address::zero(),
-                block_number: 0,
-                block_timestamp: 0,
-                block_gas_limit: 0,
-                block_difficulty: U256::zero(),
-                chain_id: U256::zero(),
-                block_base_fee: U256::zero(),
-            },
+            tx_context: TxContext::default(),
             block_hash: U256::zero(),
             call_result: Output {
                 status_code: StatusCode::Success,
                 gas_left: 0,
+                gas_refund: 0,
                 output_data: Bytes::new(),
                 create_address: Some(Address::zero()),
+                stats: Default::default(),
             },
+            extended_context: Default::default(),
+            transient_storage: Default::default(),
             recorded: Default::default(),
         }
     }
 }
 
+impl MockedHost {
+    /// Read-only view of recorded host interactions.
+    pub fn records(&self) -> impl Deref<Target = Records> + '_ {
+        self.recorded.lock()
+    }
+
+    /// Discard transient storage, as at the end of a transaction.
+    pub fn clear_transient_storage(&mut self) {
+        self.transient_storage.clear();
+    }
+
+    /// Accounts warm at the start of a transaction from `sender` to `recipient`.
+    pub(crate) fn warm_accounts(
+        &self,
+        revision: Revision,
+        sender: Address,
+        recipient: Address,
+    ) -> Vec<Address> {
+        let mut accounts = Vec::new();
+        // EIP-2929.
+        if revision >= Revision::Berlin {
+            accounts.extend([sender, recipient]);
+        }
+        // EIP-3651.
+        if revision >= Revision::Shanghai {
+            accounts.push(self.tx_context.block_coinbase);
+        }
+        accounts
+    }
+
+    /// Access every account of `MockedHost::warm_accounts`.
+    pub(crate) fn warm_up(&mut self, revision: Revision, sender: Address, recipient: Address) {
+        for address in self.warm_accounts(revision, sender, recipient) {
+            self.access_account(address);
+        }
+    }
+}
+
 impl Records {
     fn record_account_access(&mut self, address: Address) {
         if self.account_accesses.len() < MAX_RECORDED_ACCOUNT_ACCESSES {
@@ -126,7 +182,11 @@ impl crate::Host for MockedHost {
     }
 
     fn get_storage(&self, address: ethereum_types::Address, key: U256) -> U256 {
-        self.recorded.lock().record_account_access(address);
+        let mut r = self.recorded.lock();
+        r.record_account_access(address);
+        if r.storage_reads.len() < MAX_RECORDED_STORAGE_READS {
+            r.storage_reads.push((address, key));
+        }
 
         self.accounts
             .get(&address)
@@ -134,6 +194,24 @@ impl crate::Host for MockedHost {
             .unwrap_or_else(U256::zero)
     }
 
+    fn get_storage_many(&self, address: Address, keys: &[H256]) -> Vec<H256> {
+        let mut r = self.recorded.lock();
+        r.record_account_access(address);
+        if r.storage_batches.len() < MAX_RECORDED_STORAGE_BATCHES {
+            r.storage_batches.push((address, keys.to_vec()));
+        }
+
+        let storage = self.accounts.get(&address).map(|account| &account.storage);
+        keys.iter()
+            .map(|key| {
+                let value = storage
+                    .and_then(|storage| storage.get(&U256::from_big_endian(&key.0)))
+                    .map_or_else(U256::zero, |value| value.value);
+                H256(value.into())
+            })
+            .collect()
+    }
+
     fn set_storage(
         &mut self,
         address: ethereum_types::Address,
@@ -154,24 +232,47 @@ impl crate::Host for MockedHost {
             .entry(key)
             .or_default();
 
-        // Follow https://eips.ethereum.org/EIPS/eip-1283 specification.
-        // WARNING! This is not complete implementation as refund is not handled here.
+        // Follow https://eips.ethereum.org/EIPS/eip-2200 specification.
 
         if old.value == value {
             return StorageStatus::Unchanged;
         }
 
-        let status = if !old.dirty {
+        let original = if old.dirty {
+            old.original
+        } else {
             old.dirty = true;
-            if old.value.is_zero() {
-                StorageStatus::Added
-            } else if !value.is_zero() {
-                StorageStatus::Modified
-            } else {
-                StorageStatus::Deleted
+            old.original = Some(old.value);
+            old.original
+        };
+
+        let status = match original {
+            Some(original) if original == old.value => {
+                if original.is_zero() {
+                    StorageStatus::Added
+                } else if !value.is_zero() {
+                    StorageStatus::Modified
+                } else {
+                    StorageStatus::Deleted
+                }
             }
-        } else {
-            StorageStatus::ModifiedAgain
+            Some(original) if !original.is_zero() => {
+                if old.value.is_zero() {
+                    if value == original {
+                        StorageStatus::DeletedRestored
+                    } else {
+                        StorageStatus::DeletedAdded
+                    }
+                } else if value.is_zero() {
+                    StorageStatus::ModifiedDeleted
+                } else if value == original {
+                    StorageStatus::ModifiedRestored
+                } else {
+                    StorageStatus::ModifiedAgain
+                }
+            }
+            Some(_) if value.is_zero() => StorageStatus::AddedDeleted,
+            _ => StorageStatus::ModifiedAgain,
         };
 
         old.value = value;
@@ -179,6 +280,21 @@ impl crate::Host for MockedHost {
         status
     }
 
+    fn get_transient_storage(&self, address: Address, key: U256) -> U256 {
+        self.transient_storage
+            .get(&(address, key))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn set_transient_storage(&mut self, address: Address, key: U256, value: U256) {
+        if value.is_zero() {
+            self.transient_storage.remove(&(address, key));
+        } else {
+            self.transient_storage.insert((address, key), value);
+        }
+    }
+
     fn get_balance(&self, address: ethereum_types::Address) -> ethereum_types::U256 {
         self.recorded.lock().record_account_access(address);
 
@@ -227,18 +343,33 @@ impl crate::Host for MockedHost {
             .unwrap_or(0)
     }
 
+    fn get_code(&self, address: Address) -> Bytes {
+        let mut r = self.recorded.lock();
+        r.record_account_access(address);
+        if r.code_fetches.len() < MAX_RECORDED_CODE_FETCHES {
+            r.code_fetches.push(address);
+        }
+
+        self.accounts
+            .get(&address)
+            .map(|acc| acc.code.clone())
+            .unwrap_or_default()
+    }
+
     fn selfdestruct(
         &mut self,
         address: ethereum_types::Address,
         beneficiary: ethereum_types::Address,
-    ) {
+    ) -> bool {
         let mut r = self.recorded.lock();
 
         r.record_account_access(address);
+        let first = !r.selfdestructs.iter().any(|s| s.selfdestructed == address);
         r.selfdestructs.push(SelfdestructRecord {
             selfdestructed: address,
             beneficiary,
         });
+        first
     }
 
     fn call(&mut self, msg: &Message) -> Output {
@@ -265,6 +396,13 @@ impl crate::Host for MockedHost {
         self.block_hash
     }
 
+    fn get_extended_context(&self, key: u32) -> anyhow::Result<U256> {
+        self.extended_context
+            .get(&key)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("extended context key {} is not set", key))
+    }
+
     fn emit_log(&mut self, address: ethereum_types::Address, data: &[u8], topics: &[U256]) {
         self.recorded.lock().logs.push(LogRecord {
             creator: address,
@@ -1,6 +1,31 @@
+mod access_list;
+mod bench;
+mod boundary;
 mod bytecode;
+mod call_stack;
+mod call_tree;
+mod delegating_host;
+mod deploy;
+mod diff;
+mod keccak;
 pub mod mocked_host;
+mod opcodes;
+pub mod reference_vm;
+mod shared_host;
 mod tester;
+mod transaction;
 
+pub use access_list::*;
+pub use bench::*;
+pub use boundary::*;
 pub use bytecode::*;
+pub use call_stack::*;
+pub use call_tree::*;
+pub use delegating_host::*;
+pub use deploy::*;
+pub use diff::*;
+pub use keccak::*;
+pub use opcodes::*;
+pub use shared_host::*;
 pub use tester::*;
+pub use transaction::*;
@@ -0,0 +1,17 @@
+use crate::OpCode;
+use std::collections::BTreeSet;
+
+/// Distinct opcodes in `code`, skipping PUSH data.
+///
+/// Bytes which are not defined instructions are reported too. Unreachable code is not told
+/// apart, so a contract may use fewer opcodes than reported, but never more.
+pub fn opcodes_used(code: &[u8]) -> BTreeSet<OpCode> {
+    let mut opcodes = BTreeSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = OpCode(code[pc]);
+        opcodes.insert(op);
+        pc += 1 + op.push_size().map_or(0, usize::from);
+    }
+    opcodes
+}
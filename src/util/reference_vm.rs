@@ -0,0 +1,551 @@
+//! Naive reference interpreter for the host-independent subset of EVM instructions.
+//!
+//! Written for clarity rather than speed: values are kept as `BigUint` and reduced modulo
+//! 2^256 after every operation. It shares no code with the optimized interpreter, so the two
+//! can be run side by side to cross-check each other.
+
+use crate::{opcode::OpCode, StatusCode};
+use ethereum_types::U256;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, ToPrimitive, Zero};
+use sha3::{Digest, Keccak256};
+
+const STACK_LIMIT: usize = 1024;
+
+/// Outcome of execution by `execute_pure` which ended with STOP, RETURN or REVERT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PureOutcome {
+    /// Execution ended with REVERT.
+    pub reverted: bool,
+    /// Gas left after execution.
+    pub gas_left: i64,
+    /// Returned or reverted data.
+    pub output: Vec<u8>,
+    /// Stack as seen by the terminating instruction, bottom first.
+    pub stack: Vec<U256>,
+}
+
+fn modulus() -> BigUint {
+    BigUint::one() << 256
+}
+
+fn to_u256(v: &BigUint) -> U256 {
+    U256::from_big_endian(&v.to_bytes_be())
+}
+
+fn to_signed(v: &BigUint) -> BigInt {
+    if v.bit(255) {
+        BigInt::from(v.clone()) - BigInt::from(modulus())
+    } else {
+        BigInt::from(v.clone())
+    }
+}
+
+fn from_signed(v: BigInt) -> BigUint {
+    let m = BigInt::from(modulus());
+    (((v % &m) + &m) % &m).to_biguint().unwrap()
+}
+
+fn from_bool(v: bool) -> BigUint {
+    if v {
+        BigUint::one()
+    } else {
+        BigUint::zero()
+    }
+}
+
+fn words(size: &BigUint) -> BigUint {
+    (size + 31u32) / 32u32
+}
+
+fn memory_cost(words: &BigUint) -> BigUint {
+    words * 3u32 + words * words / 512u32
+}
+
+/// Static gas cost, number of stack inputs and number of stack outputs.
+fn properties(op: OpCode) -> Option<(i64, usize, usize)> {
+    Some(match op {
+        OpCode::STOP | OpCode::INVALID => (0, 0, 0),
+        OpCode::ADD | OpCode::SUB => (3, 2, 1),
+        OpCode::MUL | OpCode::DIV | OpCode::SDIV | OpCode::MOD | OpCode::SMOD => (5, 2, 1),
+        OpCode::SIGNEXTEND => (5, 2, 1),
+        OpCode::ADDMOD | OpCode::MULMOD => (8, 3, 1),
+        OpCode::EXP => (10, 2, 1),
+        OpCode::LT | OpCode::GT | OpCode::SLT | OpCode::SGT | OpCode::EQ => (3, 2, 1),
+        OpCode::AND | OpCode::OR | OpCode::XOR | OpCode::BYTE => (3, 2, 1),
+        OpCode::SHL | OpCode::SHR | OpCode::SAR => (3, 2, 1),
+        OpCode::ISZERO | OpCode::NOT => (3, 1, 1),
+        OpCode::KECCAK256 => (30, 2, 1),
+        OpCode::CALLDATALOAD | OpCode::MLOAD => (3, 1, 1),
+        OpCode::CALLDATASIZE
+        | OpCode::CODESIZE
+        | OpCode::PC
+        | OpCode::MSIZE
+        | OpCode::GAS
+        | OpCode::PUSH0 => (2, 0, 1),
+        OpCode::CALLDATACOPY | OpCode::CODECOPY | OpCode::MCOPY => (3, 3, 0),
+        OpCode::POP => (2, 1, 0),
+        OpCode::MSTORE | OpCode::MSTORE8 => (3, 2, 0),
+        OpCode::JUMP => (8, 1, 0),
+        OpCode::JUMPI => (10, 2, 0),
+        OpCode::JUMPDEST => (1, 0, 0),
+        OpCode::RETURN | OpCode::REVERT => (0, 2, 0),
+        op if op.push_size().is_some() => (3, 0, 1),
+        op if (OpCode::DUP1.0..=OpCode::DUP16.0).contains(&op.0) => {
+            let n = usize::from(op.0 - OpCode::DUP1.0) + 1;
+            (3, n, n + 1)
+        }
+        op if (OpCode::SWAP1.0..=OpCode::SWAP16.0).contains(&op.0) => {
+            let n = usize::from(op.0 - OpCode::SWAP1.0) + 1;
+            (3, n + 1, n + 1)
+        }
+        _ => return None,
+    })
+}
+
+/// Instructions which depend on the host or the message beyond its input.
+fn is_impure(op: OpCode) -> bool {
+    matches!(
+        op,
+        OpCode::ADDRESS
+            | OpCode::BALANCE
+            | OpCode::ORIGIN
+            | OpCode::CALLER
+            | OpCode::CALLVALUE
+            | OpCode::GASPRICE
+            | OpCode::EXTCODESIZE
+            | OpCode::EXTCODECOPY
+            | OpCode::RETURNDATASIZE
+            | OpCode::RETURNDATACOPY
+            | OpCode::EXTCODEHASH
+            | OpCode::BLOCKHASH
+            | OpCode::COINBASE
+            | OpCode::TIMESTAMP
+            | OpCode::NUMBER
+            | OpCode::DIFFICULTY
+            | OpCode::GASLIMIT
+            | OpCode::CHAINID
+            | OpCode::SELFBALANCE
+            | OpCode::BASEFEE
+            | OpCode::SLOAD
+            | OpCode::SSTORE
+            | OpCode::TLOAD
+            | OpCode::TSTORE
+            | OpCode::LOG0
+            | OpCode::LOG1
+            | OpCode::LOG2
+            | OpCode::LOG3
+            | OpCode::LOG4
+            | OpCode::CREATE
+            | OpCode::CALL
+            | OpCode::CALLCODE
+            | OpCode::DELEGATECALL
+            | OpCode::CREATE2
+            | OpCode::STATICCALL
+            | OpCode::SELFDESTRUCT
+    )
+}
+
+/// Positions of instructions, skipping PUSH data.
+fn instructions(code: &[u8]) -> impl Iterator<Item = (usize, OpCode)> + '_ {
+    let mut pc = 0;
+    std::iter::from_fn(move || {
+        let op = OpCode(*code.get(pc)?);
+        let position = pc;
+        pc += 1 + op.push_size().map_or(0, usize::from);
+        Some((position, op))
+    })
+}
+
+struct Vm<'a> {
+    code: &'a [u8],
+    input: &'a [u8],
+    jumpdests: Vec<bool>,
+    gas_left: i64,
+    stack: Vec<BigUint>,
+    memory: Vec<u8>,
+}
+
+impl Vm<'_> {
+    fn charge(&mut self, cost: &BigUint) -> Result<(), StatusCode> {
+        match cost.to_i64() {
+            Some(cost) if cost <= self.gas_left => {
+                self.gas_left -= cost;
+                Ok(())
+            }
+            _ => Err(StatusCode::OutOfGas),
+        }
+    }
+
+    fn pop(&mut self) -> BigUint {
+        self.stack.pop().unwrap()
+    }
+
+    fn push(&mut self, v: BigUint) {
+        self.stack.push(v % modulus());
+    }
+
+    /// Expand memory to cover the region, charging for the expansion.
+    fn expand_memory(&mut self, offset: &BigUint, size: &BigUint) -> Result<(), StatusCode> {
+        if size.is_zero() {
+            return Ok(());
+        }
+
+        // Same limit as in the optimized interpreter: such regions are never affordable.
+        let limit = BigUint::from(u32::MAX);
+        if *offset > limit || *size > limit {
+            return Err(StatusCode::OutOfGas);
+        }
+
+        let current_words = BigUint::from(self.memory.len() / 32);
+        let new_words = words(&(offset + size));
+        if new_words > current_words {
+            self.charge(&(memory_cost(&new_words) - memory_cost(&current_words)))?;
+            self.memory.resize(new_words.to_usize().unwrap() * 32, 0);
+        }
+
+        Ok(())
+    }
+
+    /// Read memory region which must have been expanded before.
+    fn read_memory(&self, offset: &BigUint, size: &BigUint) -> Vec<u8> {
+        if size.is_zero() {
+            return vec![];
+        }
+        let offset = offset.to_usize().unwrap();
+        self.memory[offset..offset + size.to_usize().unwrap()].to_vec()
+    }
+
+    /// Copy `size` bytes of `data` starting from `src` to memory at `dst`, padding with zeros.
+    fn copy_to_memory(
+        &mut self,
+        data: &[u8],
+        dst: &BigUint,
+        src: &BigUint,
+        size: &BigUint,
+    ) -> Result<(), StatusCode> {
+        self.expand_memory(dst, size)?;
+        self.charge(&(words(size) * 3u32))?;
+
+        if !size.is_zero() {
+            let dst = dst.to_usize().unwrap();
+            for i in 0..size.to_usize().unwrap() {
+                let byte = (src + i)
+                    .to_usize()
+                    .and_then(|pos| data.get(pos))
+                    .copied()
+                    .unwrap_or(0);
+                self.memory[dst + i] = byte;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stack_u256(&self) -> Vec<U256> {
+        self.stack.iter().map(to_u256).collect()
+    }
+
+    fn jump(&self, dst: &BigUint) -> Result<usize, StatusCode> {
+        dst.to_usize()
+            .filter(|&dst| self.jumpdests.get(dst).copied().unwrap_or(false))
+            .ok_or(StatusCode::BadJumpDestination)
+    }
+
+    fn run(&mut self) -> Result<PureOutcome, StatusCode> {
+        let mut pc = 0;
+        loop {
+            // Code is implicitly followed by STOP.
+            let op = OpCode(self.code.get(pc).copied().unwrap_or(0));
+
+            let (cost, inputs, outputs) = properties(op).ok_or(StatusCode::UndefinedInstruction)?;
+            self.charge(&BigUint::from(cost as u64))?;
+            if self.stack.len() < inputs {
+                return Err(StatusCode::StackUnderflow);
+            }
+            if self.stack.len() - inputs + outputs > STACK_LIMIT {
+                return Err(StatusCode::StackOverflow);
+            }
+
+            let mut next_pc = pc + 1;
+            match op {
+                OpCode::STOP => {
+                    return Ok(PureOutcome {
+                        reverted: false,
+                        gas_left: self.gas_left,
+                        output: vec![],
+                        stack: self.stack_u256(),
+                    });
+                }
+                OpCode::RETURN | OpCode::REVERT => {
+                    let stack = self.stack_u256();
+                    let offset = self.pop();
+                    let size = self.pop();
+                    self.expand_memory(&offset, &size)?;
+                    return Ok(PureOutcome {
+                        reverted: op == OpCode::REVERT,
+                        gas_left: self.gas_left,
+                        output: self.read_memory(&offset, &size),
+                        stack,
+                    });
+                }
+                OpCode::INVALID => return Err(StatusCode::InvalidInstruction),
+                OpCode::ADD => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(a + b);
+                }
+                OpCode::MUL => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(a * b);
+                }
+                OpCode::SUB => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(a + modulus() - b);
+                }
+                OpCode::DIV => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(if b.is_zero() { b } else { a / b });
+                }
+                OpCode::SDIV => {
+                    let (a, b) = (to_signed(&self.pop()), to_signed(&self.pop()));
+                    // BigInt division truncates toward zero, as EVM requires.
+                    let v = if b.is_zero() { b } else { a / b };
+                    self.push(from_signed(v));
+                }
+                OpCode::MOD => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(if b.is_zero() { b } else { a % b });
+                }
+                OpCode::SMOD => {
+                    let (a, b) = (to_signed(&self.pop()), to_signed(&self.pop()));
+                    // BigInt remainder takes the sign of the dividend, as EVM requires.
+                    let v = if b.is_zero() { b } else { a % b };
+                    self.push(from_signed(v));
+                }
+                OpCode::ADDMOD => {
+                    let (a, b, n) = (self.pop(), self.pop(), self.pop());
+                    self.push(if n.is_zero() { n } else { (a + b) % n });
+                }
+                OpCode::MULMOD => {
+                    let (a, b, n) = (self.pop(), self.pop(), self.pop());
+                    self.push(if n.is_zero() { n } else { (a * b) % n });
+                }
+                OpCode::EXP => {
+                    let (base, exponent) = (self.pop(), self.pop());
+                    let exponent_bytes = exponent.bits().div_ceil(8);
+                    self.charge(&BigUint::from(50 * exponent_bytes))?;
+                    self.push(base.modpow(&exponent, &modulus()));
+                }
+                OpCode::SIGNEXTEND => {
+                    let (b, x) = (self.pop(), self.pop());
+                    let v = match b.to_u64() {
+                        Some(b) if b < 31 => {
+                            let sign_bit = 8 * b + 7;
+                            let mask = (BigUint::one() << (sign_bit + 1)) - 1u32;
+                            if x.bit(sign_bit) {
+                                x | (modulus() - 1u32 - &mask)
+                            } else {
+                                x & mask
+                            }
+                        }
+                        _ => x,
+                    };
+                    self.push(v);
+                }
+                OpCode::LT => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(from_bool(a < b));
+                }
+                OpCode::GT => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(from_bool(a > b));
+                }
+                OpCode::SLT => {
+                    let (a, b) = (to_signed(&self.pop()), to_signed(&self.pop()));
+                    self.push(from_bool(a < b));
+                }
+                OpCode::SGT => {
+                    let (a, b) = (to_signed(&self.pop()), to_signed(&self.pop()));
+                    self.push(from_bool(a > b));
+                }
+                OpCode::EQ => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(from_bool(a == b));
+                }
+                OpCode::ISZERO => {
+                    let a = self.pop();
+                    self.push(from_bool(a.is_zero()));
+                }
+                OpCode::AND => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(a & b);
+                }
+                OpCode::OR => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(a | b);
+                }
+                OpCode::XOR => {
+                    let (a, b) = (self.pop(), self.pop());
+                    self.push(a ^ b);
+                }
+                OpCode::NOT => {
+                    let a = self.pop();
+                    self.push(modulus() - 1u32 - a);
+                }
+                OpCode::BYTE => {
+                    let (i, x) = (self.pop(), self.pop());
+                    let v = match i.to_u64() {
+                        // Byte 0 is the most significant one.
+                        Some(i) if i < 32 => (x >> (8 * (31 - i))) & BigUint::from(0xffu32),
+                        _ => BigUint::zero(),
+                    };
+                    self.push(v);
+                }
+                OpCode::SHL => {
+                    let (shift, x) = (self.pop(), self.pop());
+                    let v = match shift.to_u64() {
+                        Some(shift) if shift < 256 => x << shift,
+                        _ => BigUint::zero(),
+                    };
+                    self.push(v);
+                }
+                OpCode::SHR => {
+                    let (shift, x) = (self.pop(), self.pop());
+                    let v = match shift.to_u64() {
+                        Some(shift) if shift < 256 => x >> shift,
+                        _ => BigUint::zero(),
+                    };
+                    self.push(v);
+                }
+                OpCode::SAR => {
+                    let (shift, x) = (self.pop(), to_signed(&self.pop()));
+                    let shift = shift.to_u64().unwrap_or(u64::MAX).min(256);
+                    // BigInt right shift rounds toward negative infinity, as EVM requires.
+                    self.push(from_signed(x >> shift));
+                }
+                OpCode::KECCAK256 => {
+                    let (offset, size) = (self.pop(), self.pop());
+                    self.expand_memory(&offset, &size)?;
+                    self.charge(&(words(&size) * 6u32))?;
+                    let hash = Keccak256::digest(&self.read_memory(&offset, &size));
+                    self.push(BigUint::from_bytes_be(&hash));
+                }
+                OpCode::CALLDATALOAD => {
+                    let offset = self.pop();
+                    let mut word = [0; 32];
+                    for (i, byte) in word.iter_mut().enumerate() {
+                        if let Some(&v) = (&offset + i).to_usize().and_then(|p| self.input.get(p)) {
+                            *byte = v;
+                        }
+                    }
+                    self.push(BigUint::from_bytes_be(&word));
+                }
+                OpCode::CALLDATASIZE => self.push(BigUint::from(self.input.len())),
+                OpCode::CALLDATACOPY => {
+                    let (dst, src, size) = (self.pop(), self.pop(), self.pop());
+                    self.copy_to_memory(self.input, &dst, &src, &size)?;
+                }
+                OpCode::CODESIZE => self.push(BigUint::from(self.code.len())),
+                OpCode::CODECOPY => {
+                    let (dst, src, size) = (self.pop(), self.pop(), self.pop());
+                    self.copy_to_memory(self.code, &dst, &src, &size)?;
+                }
+                OpCode::MCOPY => {
+                    let (dst, src, size) = (self.pop(), self.pop(), self.pop());
+                    self.expand_memory(&src, &size)?;
+                    let data = self.read_memory(&src, &size);
+                    self.copy_to_memory(&data, &dst, &BigUint::zero(), &size)?;
+                }
+                OpCode::POP => {
+                    self.pop();
+                }
+                OpCode::MLOAD => {
+                    let offset = self.pop();
+                    let size = BigUint::from(32u32);
+                    self.expand_memory(&offset, &size)?;
+                    let word = self.read_memory(&offset, &size);
+                    self.push(BigUint::from_bytes_be(&word));
+                }
+                OpCode::MSTORE => {
+                    let (offset, value) = (self.pop(), self.pop());
+                    self.expand_memory(&offset, &BigUint::from(32u32))?;
+                    let offset = offset.to_usize().unwrap();
+                    let mut word = [0; 32];
+                    to_u256(&value).to_big_endian(&mut word);
+                    self.memory[offset..offset + 32].copy_from_slice(&word);
+                }
+                OpCode::MSTORE8 => {
+                    let (offset, value) = (self.pop(), self.pop());
+                    self.expand_memory(&offset, &BigUint::one())?;
+                    self.memory[offset.to_usize().unwrap()] = (value % 256u32).to_u8().unwrap();
+                }
+                OpCode::JUMP => {
+                    let dst = self.pop();
+                    next_pc = self.jump(&dst)?;
+                }
+                OpCode::JUMPI => {
+                    let (dst, condition) = (self.pop(), self.pop());
+                    if !condition.is_zero() {
+                        next_pc = self.jump(&dst)?;
+                    }
+                }
+                OpCode::PC => self.push(BigUint::from(pc)),
+                OpCode::PUSH0 => self.push(BigUint::from(0u32)),
+                OpCode::MSIZE => self.push(BigUint::from(self.memory.len())),
+                OpCode::GAS => self.push(BigUint::from(self.gas_left as u64)),
+                OpCode::JUMPDEST => {}
+                op if op.push_size().is_some() => {
+                    let size = usize::from(op.push_size().unwrap());
+                    let mut data = vec![0; size];
+                    for (i, byte) in data.iter_mut().enumerate() {
+                        *byte = self.code.get(pc + 1 + i).copied().unwrap_or(0);
+                    }
+                    self.push(BigUint::from_bytes_be(&data));
+                    next_pc += size;
+                }
+                op if (OpCode::DUP1.0..=OpCode::DUP16.0).contains(&op.0) => {
+                    let n = usize::from(op.0 - OpCode::DUP1.0) + 1;
+                    let v = self.stack[self.stack.len() - n].clone();
+                    self.push(v);
+                }
+                op if (OpCode::SWAP1.0..=OpCode::SWAP16.0).contains(&op.0) => {
+                    let n = usize::from(op.0 - OpCode::SWAP1.0) + 1;
+                    let top = self.stack.len() - 1;
+                    self.stack.swap(top, top - n);
+                }
+                op => unreachable!("{} has properties but no implementation", op.name()),
+            }
+            pc = next_pc;
+        }
+    }
+}
+
+/// Execute code which only uses host-independent instructions, at the latest revision.
+///
+/// Programs containing instructions which depend on the host or message context other than
+/// input data are refused with `StatusCode::InternalError`. Bytes which are not instructions
+/// fail with `StatusCode::UndefinedInstruction` when executed, as usual.
+pub fn execute_pure(code: &[u8], input: &[u8], gas: i64) -> Result<PureOutcome, StatusCode> {
+    let mut jumpdests = vec![false; code.len()];
+    for (pc, op) in instructions(code) {
+        if is_impure(op) {
+            return Err(StatusCode::InternalError(format!(
+                "{} is not in the pure subset",
+                op.name()
+            )));
+        }
+        if op == OpCode::JUMPDEST {
+            jumpdests[pc] = true;
+        }
+    }
+
+    Vm {
+        code,
+        input,
+        jumpdests,
+        gas_left: gas,
+        stack: vec![],
+        memory: vec![],
+    }
+    .run()
+}
@@ -0,0 +1,198 @@
+use crate::{host::*, *};
+use bytes::Bytes;
+use ethereum_types::{Address, H256, U256};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    thread::{self, ThreadId},
+};
+
+/// Host shared between executions behind a read-write lock.
+///
+/// `Host` methods taking `&self` take a read lock and the rest take a write lock, each for
+/// the duration of the method. `call` keeps the write lock while the inner host executes the
+/// callee, so nested calls of concurrent executions are serialized.
+///
+/// The inner host's `call` may execute the callee through another clone of this `SharedHost`.
+/// Taking the lock again on that thread would deadlock, so such re-entrant `call` returns
+/// [`StatusCode::InternalError`] instead.
+///
+/// Lock ordering: the thread currently inside `call` is recorded only while the host lock
+/// is held, and is looked up before the host lock is taken, so the two locks are never
+/// waited on in opposite order.
+///
+/// # Panics
+///
+/// Every other `Host` method, as well as [`SharedHost::read`] and [`SharedHost::write`],
+/// panics when used from within the inner host's `call` on the same thread. These methods
+/// have no way to report a failure, and waiting for the lock would deadlock. A panic in the
+/// inner `call` releases the host, so later uses succeed.
+pub struct SharedHost<H> {
+    host: Arc<RwLock<H>>,
+    calling_thread: Arc<Mutex<Option<ThreadId>>>,
+}
+
+impl<H> Clone for SharedHost<H> {
+    fn clone(&self) -> Self {
+        Self {
+            host: self.host.clone(),
+            calling_thread: self.calling_thread.clone(),
+        }
+    }
+}
+
+impl<H> SharedHost<H> {
+    pub fn new(host: H) -> Self {
+        Self {
+            host: Arc::new(RwLock::new(host)),
+            calling_thread: Default::default(),
+        }
+    }
+
+    /// Read-only view of the inner host.
+    pub fn read(&self) -> impl Deref<Target = H> + '_ {
+        self.check_reentrancy();
+        self.host.read()
+    }
+
+    /// Mutable view of the inner host.
+    pub fn write(&self) -> impl DerefMut<Target = H> + '_ {
+        self.check_reentrancy();
+        self.host.write()
+    }
+
+    fn is_reentrant(&self) -> bool {
+        *self.calling_thread.lock() == Some(thread::current().id())
+    }
+
+    /// Record the current thread as the one inside `call` until the guard is dropped.
+    fn enter_call(&self) -> CallGuard<'_> {
+        *self.calling_thread.lock() = Some(thread::current().id());
+        CallGuard(&self.calling_thread)
+    }
+
+    fn check_reentrancy(&self) {
+        assert!(
+            !self.is_reentrant(),
+            "SharedHost accessed from within its own call"
+        );
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&H) -> T) -> T {
+        self.check_reentrancy();
+        (f)(&self.host.read())
+    }
+
+    fn with_mut<T>(&self, f: impl FnOnce(&mut H) -> T) -> T {
+        self.check_reentrancy();
+        (f)(&mut self.host.write())
+    }
+}
+
+/// Clears the thread recorded by `SharedHost::enter_call`, also when `call` unwinds.
+struct CallGuard<'a>(&'a Mutex<Option<ThreadId>>);
+
+impl Drop for CallGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.lock() = None;
+    }
+}
+
+impl<H: Host> Host for SharedHost<H> {
+    fn account_exists(&self, address: Address) -> bool {
+        self.with(|host| host.account_exists(address))
+    }
+
+    fn get_storage(&self, address: Address, key: U256) -> U256 {
+        self.with(|host| host.get_storage(address, key))
+    }
+
+    fn get_storage_many(&self, address: Address, keys: &[H256]) -> Vec<H256> {
+        self.with(|host| host.get_storage_many(address, keys))
+    }
+
+    fn set_storage(&mut self, address: Address, key: U256, value: U256) -> StorageStatus {
+        self.with_mut(|host| host.set_storage(address, key, value))
+    }
+
+    fn get_transient_storage(&self, address: Address, key: U256) -> U256 {
+        self.with(|host| host.get_transient_storage(address, key))
+    }
+
+    fn set_transient_storage(&mut self, address: Address, key: U256, value: U256) {
+        self.with_mut(|host| host.set_transient_storage(address, key, value))
+    }
+
+    fn get_balance(&self, address: Address) -> U256 {
+        self.with(|host| host.get_balance(address))
+    }
+
+    fn get_balances(&self, addresses: &[Address]) -> Vec<U256> {
+        self.with(|host| host.get_balances(addresses))
+    }
+
+    fn get_code_size(&self, address: Address) -> U256 {
+        self.with(|host| host.get_code_size(address))
+    }
+
+    fn get_code_hash(&self, address: Address) -> U256 {
+        self.with(|host| host.get_code_hash(address))
+    }
+
+    fn copy_code(&self, address: Address, offset: usize, buffer: &mut [u8]) -> usize {
+        self.with(|host| host.copy_code(address, offset, buffer))
+    }
+
+    fn get_code(&self, address: Address) -> Bytes {
+        self.with(|host| host.get_code(address))
+    }
+
+    fn selfdestruct(&mut self, address: Address, beneficiary: Address) -> bool {
+        self.with_mut(|host| host.selfdestruct(address, beneficiary))
+    }
+
+    fn call(&mut self, msg: &Message) -> Output {
+        if self.is_reentrant() {
+            return Output {
+                status_code: StatusCode::InternalError(
+                    "re-entrant call into SharedHost".to_string(),
+                ),
+                gas_left: 0,
+                gas_refund: 0,
+                output_data: Bytes::new(),
+                create_address: None,
+                stats: Default::default(),
+            };
+        }
+
+        let mut host = self.host.write();
+        // Dropped before the host lock is released.
+        let _guard = self.enter_call();
+        host.call(msg)
+    }
+
+    fn get_tx_context(&self) -> TxContext {
+        self.with(|host| host.get_tx_context())
+    }
+
+    fn get_extended_context(&self, key: u32) -> anyhow::Result<U256> {
+        self.with(|host| host.get_extended_context(key))
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> U256 {
+        self.with(|host| host.get_block_hash(block_number))
+    }
+
+    fn emit_log(&mut self, address: Address, data: &[u8], topics: &[U256]) {
+        self.with_mut(|host| host.emit_log(address, data, topics))
+    }
+
+    fn access_account(&mut self, address: Address) -> AccessStatus {
+        self.with_mut(|host| host.access_account(address))
+    }
+
+    fn access_storage(&mut self, address: Address, key: U256) -> AccessStatus {
+        self.with_mut(|host| host.access_storage(address, key))
+    }
+}
@@ -6,32 +6,75 @@ use crate::{
 use bytes::Bytes;
 use educe::Educe;
 use ethereum_types::{Address, U256};
-use std::sync::Arc;
+use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
 
+type ExecFn =
+    dyn Fn(&mut MockedHost, &AnalyzedCode, Message, Revision, &ExecutionOptions) -> Output;
+
+/// Records gas left when execution first reaches each instruction it is notified about.
+#[derive(Default)]
+struct GasProbes(BTreeMap<usize, i64>);
+
+impl Tracer for GasProbes {
+    fn notify_execution_start(&mut self, _: Revision, _: Message, _: Bytes) {}
+
+    fn notify_instruction_start(&mut self, pc: usize, _: OpCode, state: &ExecutionState) {
+        self.0.entry(pc).or_insert(state.gas_left);
+    }
+
+    fn notify_execution_end(&mut self, _: &Output) {}
+}
+
+/// Execute code, returning output and gas left at `options.instruction_start_pcs`.
 fn exec(
     host: &mut MockedHost,
     revision: Revision,
     message: Message,
     code: Vec<u8>,
     collect_traces: bool,
-) -> Output {
-    // Add EIP-2929 tweak.
-    if revision >= Revision::Berlin {
-        host.access_account(message.sender);
-        host.access_account(message.recipient);
-    }
+    exec_fn: Option<&ExecFn>,
+    options: &ExecutionOptions,
+) -> (Output, BTreeMap<usize, i64>) {
+    host.warm_up(revision, message.sender, message.recipient);
     let code = AnalyzedCode::analyze(code);
 
-    if collect_traces {
-        code.execute(host, &mut StdoutTracer::default(), None, message, revision)
-    } else {
-        code.execute(host, &mut NoopTracer, None, message, revision)
+    if !options.instruction_start_pcs.is_empty() {
+        assert!(
+            exec_fn.is_none() && !collect_traces,
+            "gas probes do not work with a custom tracer or trace collection"
+        );
+        // Pause only at probed instructions, without tracing the others.
+        let mut gas_probes = GasProbes::default();
+        let output = code
+            .execute_resumable_with_options(false, message, revision, options)
+            .run_to_completion_with_options(host, &mut gas_probes, None, options);
+        return (output, gas_probes.0);
     }
+
+    let output = if let Some(exec_fn) = exec_fn {
+        (exec_fn)(host, &code, message, revision, options)
+    } else if collect_traces {
+        code.execute_with_options(
+            host,
+            &mut StdoutTracer::default(),
+            None,
+            message,
+            revision,
+            options,
+        )
+    } else {
+        code.execute_with_options(host, &mut NoopTracer, None, message, revision, options)
+    };
+    (output, BTreeMap::new())
 }
 
+/// Nesting levels of recorded calls shown when a check fails.
+const MAX_RENDERED_CALL_DEPTH: usize = 8;
+
 #[derive(Clone, Copy, Debug)]
 enum GasCheck {
     Used(i64),
+    UsedBetween(i64, i64),
     Left(i64),
 }
 
@@ -55,7 +98,13 @@ pub struct EvmTester {
     gas_check: Option<GasCheck>,
     expected_status_codes: Option<Vec<StatusCode>>,
     expected_output_data: Option<Vec<u8>>,
+    expected_accesses: Option<AccessCounts>,
     collect_traces: bool,
+    expect_no_mutation: bool,
+    expect_no_host_interaction: bool,
+    #[educe(Debug(ignore))]
+    exec_fn: Option<Arc<ExecFn>>,
+    options: ExecutionOptions,
 }
 
 impl Default for EvmTester {
@@ -89,7 +138,12 @@ impl EvmTester {
             gas_check: None,
             expected_status_codes: None,
             expected_output_data: None,
+            expected_accesses: None,
             collect_traces: false,
+            expect_no_mutation: false,
+            expect_no_host_interaction: false,
+            exec_fn: None,
+            options: ExecutionOptions::default(),
         }
     }
 
@@ -100,11 +154,20 @@ impl EvmTester {
     }
 
     /// Queue function that will modify the host before execution.
+    ///
+    /// Queued functions are applied in the order they were queued, so later ones see and may
+    /// override changes of earlier ones.
     pub fn apply_host_fn(mut self, host_fn: impl Fn(&mut MockedHost, &Message) + 'static) -> Self {
         self.apply_host_fns.push(Arc::new(host_fn));
         self
     }
 
+    /// Remove all functions queued with [`EvmTester::apply_host_fn`].
+    pub fn clear_host_fns(mut self) -> Self {
+        self.apply_host_fns.clear();
+        self
+    }
+
     /// Set EVM revision for this tester.
     pub fn revision(mut self, revision: Revision) -> Self {
         self.revision = revision;
@@ -129,9 +192,10 @@ impl EvmTester {
         self
     }
 
-    /// Set message destination.
+    /// Set message destination. Code is assumed to belong to the destination account.
     pub fn destination(mut self, destination: impl Into<Address>) -> Self {
         self.message.recipient = destination.into();
+        self.message.code_address = self.message.recipient;
         self
     }
 
@@ -153,6 +217,12 @@ impl EvmTester {
         self
     }
 
+    /// Check that used gas falls within the inclusive range. Mutually exclusive with `EvmTester::gas_used`.
+    pub fn gas_used_between(mut self, min: i64, max: i64) -> Self {
+        self.gas_check = Some(GasCheck::UsedBetween(min, max));
+        self
+    }
+
     /// Check how much gas will be left after execution. Mutually exclusive with `EvmTester::gas_used`.
     pub fn gas_left(mut self, expected_gas_left: i64) -> Self {
         self.gas_check = Some(GasCheck::Left(expected_gas_left));
@@ -191,6 +261,27 @@ impl EvmTester {
         self
     }
 
+    /// Check account and storage access counts, see `ExecutionStats::accesses`.
+    pub fn accesses(mut self, expected: AccessCounts) -> Self {
+        self.expected_accesses = Some(expected);
+        self
+    }
+
+    /// Check that execution has not attempted to change persistent state, see
+    /// `ExecutionStats::mutation_observed`.
+    pub fn expect_no_mutation(mut self) -> Self {
+        self.expect_no_mutation = true;
+        self
+    }
+
+    /// Check that execution has not interacted with the host in any way recorded by
+    /// `MockedHost`: no calls, account or storage accesses, logs, block hash requests
+    /// or self-destructs.
+    pub fn expect_no_host_interaction(mut self) -> Self {
+        self.expect_no_host_interaction = true;
+        self
+    }
+
     /// Inspect output with provided function.
     pub fn inspect_output(mut self, inspect_output_fn: impl Fn(&[u8]) + 'static) -> Self {
         self.inspect_output_fn = Arc::new(inspect_output_fn);
@@ -209,6 +300,66 @@ impl EvmTester {
         self
     }
 
+    /// Set execution options.
+    pub fn options(mut self, options: ExecutionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Pre-populate the return data buffer, as if left by a call preceding execution.
+    pub fn seed_return_data(mut self, data: impl Into<Bytes>) -> Self {
+        self.options.initial_return_data = data.into();
+        self
+    }
+
+    /// Set a value of `Host::get_extended_context` on the mocked host.
+    pub fn extended_context(self, key: u32, value: impl Into<U256>) -> Self {
+        let value = value.into();
+        self.apply_host_fn(move |host, _| {
+            host.extended_context.insert(key, value);
+        })
+    }
+
+    /// Pre-populate memory, as if written by instructions preceding execution.
+    pub fn seed_memory(mut self, image: impl Into<Bytes>) -> Self {
+        self.options.initial_memory = image.into();
+        self
+    }
+
+    /// Execute with provided tracer instead of the default quiet one.
+    ///
+    /// Takes precedence over `EvmTester::collect_traces`.
+    pub fn tracer(mut self, tracer: impl Tracer + 'static) -> Self {
+        let tracer = RefCell::new(tracer);
+        self.exec_fn = Some(Arc::new(
+            move |host: &mut MockedHost,
+                  code: &AnalyzedCode,
+                  message: Message,
+                  revision: Revision,
+                  options: &ExecutionOptions| {
+                code.execute_with_options(
+                    host,
+                    &mut *tracer.borrow_mut(),
+                    None,
+                    message,
+                    revision,
+                    options,
+                )
+            },
+        ));
+        self
+    }
+
+    /// Record gas left when execution first reaches the instruction at `pc`, before it is
+    /// charged. Recorded values are returned by [`EvmTester::check_and_get_probed_gas`].
+    ///
+    /// Does not work together with [`EvmTester::tracer`] or [`EvmTester::collect_traces`].
+    pub fn probe_gas_at_pc(mut self, pc: usize) -> Self {
+        self.options.instruction_start_pcs.insert(pc);
+        self
+    }
+
+    /// Print executed code and trace every instruction to stdout. Off by default.
     pub fn collect_traces(mut self, doit: bool) -> Self {
         self.collect_traces = doit;
         self
@@ -216,6 +367,12 @@ impl EvmTester {
 
     /// Execute provided code, run checks and return bytecode returned by EVM.
     pub fn check_and_get_result(self) -> Output {
+        self.check_and_get_probed_gas().0
+    }
+
+    /// Execute provided code, run checks and return output together with gas left at
+    /// every pc set with [`EvmTester::probe_gas_at_pc`] which execution has reached.
+    pub fn check_and_get_probed_gas(self) -> (Output, BTreeMap<usize, i64>) {
         if self.collect_traces {
             println!("Executing code: {}", hex::encode(&self.code));
         }
@@ -223,39 +380,111 @@ impl EvmTester {
         for f in self.apply_host_fns {
             (f)(&mut host, &self.message);
         }
-        let output = exec(
+        let mut records_before = host.records().clone();
+        let (output, probed_gas) = exec(
             &mut host,
             self.revision,
             self.message.clone(),
             self.code,
             self.collect_traces,
+            self.exec_fn.as_deref(),
+            &self.options,
         );
 
+        // Appended to failure messages, so that nested calls are visible.
+        let call_tree = || {
+            let frames = CallTreeNode::from_recording(&host);
+            if frames.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "\nRecorded calls:\n{}",
+                    render_call_tree(&frames, MAX_RENDERED_CALL_DEPTH)
+                )
+            }
+        };
+
         if let Some(status_codes) = self.expected_status_codes {
             assert!(
                 status_codes.iter().any(|s| *s == output.status_code),
-                "Status code mismatch: {}, but must be one of {:?}",
+                "Status code mismatch: {}, but must be one of {:?}{}",
                 output.status_code,
-                status_codes
+                status_codes,
+                call_tree()
+            );
+        }
+
+        if let Some(expected) = self.expected_accesses {
+            assert_eq!(
+                output.stats.accesses,
+                expected,
+                "Access counts mismatch{}",
+                call_tree()
+            );
+        }
+
+        if self.expect_no_mutation {
+            assert_eq!(
+                output.stats.mutation_observed,
+                None,
+                "State mutation observed{}",
+                call_tree()
+            );
+        }
+
+        if self.expect_no_host_interaction {
+            records_before.account_accesses.extend(host.warm_accounts(
+                self.revision,
+                self.message.sender,
+                self.message.recipient,
+            ));
+            assert_eq!(
+                *host.records(),
+                records_before,
+                "Host interaction observed{}",
+                call_tree()
             );
         }
 
         if let Some(gas_check) = self.gas_check {
             match gas_check {
-                GasCheck::Used(used) => assert_eq!(self.message.gas - output.gas_left, used),
-                GasCheck::Left(left) => assert_eq!(output.gas_left, left),
+                GasCheck::Used(used) => assert_eq!(
+                    self.message.gas - output.gas_left,
+                    used,
+                    "Gas used mismatch{}",
+                    call_tree()
+                ),
+                GasCheck::UsedBetween(min, max) => {
+                    let used = self.message.gas - output.gas_left;
+                    assert!(
+                        (min..=max).contains(&used),
+                        "Gas used {} is not within [{}, {}]{}",
+                        used,
+                        min,
+                        max,
+                        call_tree()
+                    );
+                }
+                GasCheck::Left(left) => {
+                    assert_eq!(output.gas_left, left, "Gas left mismatch{}", call_tree())
+                }
             }
         }
 
         if let Some(expected_data) = &self.expected_output_data {
-            assert_eq!(&*output.output_data, expected_data);
+            assert_eq!(
+                &*output.output_data,
+                expected_data,
+                "Output data mismatch{}",
+                call_tree()
+            );
         }
 
         (self.inspect_output_fn)(&*output.output_data);
         (self.inspect_host_fn)(&host, &self.message);
         (self.inspect_fn)(&host, &self.message, &*output.output_data);
 
-        output
+        (output, probed_gas)
     }
 
     /// Execute provided code and run checks.
@@ -0,0 +1,287 @@
+use super::{create_address, deploy::run_create, mocked_host::*};
+use crate::{tracing::NoopTracer, *};
+use bytes::Bytes;
+use ethereum_types::{Address, Bloom, BloomInput, U256};
+use std::collections::{BTreeMap, HashMap};
+
+/// Base cost of any transaction.
+const TX_GAS: i64 = 21000;
+/// Additional cost of a contract creation transaction (since Homestead).
+const TX_CREATE_GAS: i64 = 32000;
+/// Cost of a zero byte of transaction input.
+const TX_DATA_ZERO_GAS: i64 = 4;
+/// Cost of an access list entry (EIP-2930).
+const ACCESS_LIST_ADDRESS_GAS: i64 = 2400;
+/// Cost of a storage key in an access list entry (EIP-2930).
+const ACCESS_LIST_STORAGE_KEY_GAS: i64 = 1900;
+
+/// Transaction to be executed by [`execute_transaction`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransactionEnv {
+    pub sender: Address,
+    /// Recipient of the transaction, `None` for contract creation.
+    pub to: Option<Address>,
+    pub value: U256,
+    /// Call data, or init code for contract creation.
+    pub input: Bytes,
+    pub gas_limit: i64,
+    pub gas_price: U256,
+    /// Addresses and storage keys to pre-warm (EIP-2930).
+    pub access_list: Vec<(Address, Vec<U256>)>,
+}
+
+/// Result of a transaction executed by [`execute_transaction`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Receipt {
+    pub status_code: StatusCode,
+    /// Gas charged to the sender, including intrinsic gas and net of refund.
+    pub gas_used: i64,
+    /// Logs emitted by the transaction, empty if it has failed.
+    pub logs: Vec<LogRecord>,
+    pub logs_bloom: Bloom,
+    /// Address of the created contract for successful creation transactions.
+    pub create_address: Option<Address>,
+    pub output: Bytes,
+    /// Accounts changed by the transaction, including the gas payment and the fee.
+    pub state_diff: BTreeMap<Address, AccountDiff>,
+}
+
+/// Change of an account made by a transaction, see [`Receipt::state_diff`].
+///
+/// Changed fields hold the values before and after the transaction. An absent account is
+/// the same as an empty one, and an unset storage slot is zero.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountDiff {
+    pub balance: Option<(U256, U256)>,
+    pub nonce: Option<(u64, u64)>,
+    pub code: Option<(Bytes, Bytes)>,
+    pub storage: BTreeMap<U256, (U256, U256)>,
+}
+
+impl AccountDiff {
+    fn new(before: &Account, after: &Account) -> Self {
+        fn changed<T: PartialEq + Clone>(before: &T, after: &T) -> Option<(T, T)> {
+            (before != after).then(|| (before.clone(), after.clone()))
+        }
+
+        let value = |account: &Account, key: &U256| {
+            account
+                .storage
+                .get(key)
+                .map_or_else(U256::zero, |value| value.value)
+        };
+        let storage = before
+            .storage
+            .keys()
+            .chain(after.storage.keys())
+            .filter_map(|key| {
+                changed(&value(before, key), &value(after, key)).map(|values| (*key, values))
+            })
+            .collect();
+
+        Self {
+            balance: changed(&before.balance, &after.balance),
+            nonce: changed(&before.nonce, &after.nonce),
+            code: changed(&before.code, &after.code),
+            storage,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+fn state_diff(
+    before: &HashMap<Address, Account>,
+    after: &HashMap<Address, Account>,
+) -> BTreeMap<Address, AccountDiff> {
+    let empty = Account::default();
+    before
+        .keys()
+        .chain(after.keys())
+        .filter_map(|address| {
+            let diff = AccountDiff::new(
+                before.get(address).unwrap_or(&empty),
+                after.get(address).unwrap_or(&empty),
+            );
+            (!diff.is_empty()).then(|| (*address, diff))
+        })
+        .collect()
+}
+
+/// Gas charged before the transaction starts executing.
+pub fn intrinsic_gas(tx: &TransactionEnv, revision: Revision) -> i64 {
+    let mut gas = TX_GAS;
+    if tx.to.is_none() && revision >= Revision::Homestead {
+        gas += TX_CREATE_GAS;
+    }
+
+    let non_zero_gas = if revision >= Revision::Istanbul {
+        16
+    } else {
+        68
+    };
+    for &b in tx.input.iter() {
+        gas += if b == 0 {
+            TX_DATA_ZERO_GAS
+        } else {
+            non_zero_gas
+        };
+    }
+
+    if revision >= Revision::Berlin {
+        for (_, keys) in &tx.access_list {
+            gas += ACCESS_LIST_ADDRESS_GAS + keys.len() as i64 * ACCESS_LIST_STORAGE_KEY_GAS;
+        }
+    }
+
+    gas
+}
+
+/// Bloom filter over log creators and topics.
+pub fn logs_bloom(logs: &[LogRecord]) -> Bloom {
+    let mut bloom = Bloom::zero();
+    for log in logs {
+        bloom.accrue(BloomInput::Raw(log.creator.as_bytes()));
+        for topic in &log.topics {
+            let mut bytes = [0; 32];
+            topic.to_big_endian(&mut bytes);
+            bloom.accrue(BloomInput::Raw(&bytes));
+        }
+    }
+    bloom
+}
+
+/// Execute a transaction against the accounts in `host`.
+///
+/// Buys gas from the sender up front, bumps its nonce, runs the call or creation and
+/// refunds unused gas. Changes made by execution are discarded if it fails, while the gas
+/// payment and nonce bump stay. The coinbase in `host.tx_context` is credited with the fee,
+/// less the burnt base fee since London.
+///
+/// Fails with [`StatusCode::InsufficientBalance`] if the sender can't pay for gas and value,
+/// and with [`StatusCode::OutOfGas`] if the gas limit doesn't cover intrinsic gas. The host
+/// is left untouched in both cases.
+///
+/// Only the code of the transaction's target is executed. Nested calls and creations are
+/// answered by [`MockedHost::call`] with its canned `call_result`, so transactions relying
+/// on the outcome of other contracts are not reproduced faithfully.
+pub fn execute_transaction(
+    host: &mut MockedHost,
+    tx: TransactionEnv,
+    revision: Revision,
+) -> Result<Receipt, StatusCode> {
+    let intrinsic_gas = intrinsic_gas(&tx, revision);
+    if tx.gas_limit < intrinsic_gas {
+        return Err(StatusCode::OutOfGas);
+    }
+
+    // No balance can pay for an amount which overflows.
+    let gas_cost = U256::from(tx.gas_limit)
+        .checked_mul(tx.gas_price)
+        .ok_or(StatusCode::InsufficientBalance)?;
+    let total_cost = gas_cost
+        .checked_add(tx.value)
+        .ok_or(StatusCode::InsufficientBalance)?;
+    let balance = host
+        .accounts
+        .get(&tx.sender)
+        .map_or_else(U256::zero, |account| account.balance);
+    if balance < total_cost {
+        return Err(StatusCode::InsufficientBalance);
+    }
+    let pre_state = host.accounts.clone();
+    // The sender may not exist if it pays nothing.
+    let sender = host.accounts.entry(tx.sender).or_default();
+    sender.balance -= gas_cost;
+    let nonce = sender.nonce;
+    sender.nonce += 1;
+
+    host.tx_context.tx_origin = tx.sender;
+    host.tx_context.tx_gas_price = tx.gas_price;
+
+    let recipient = tx.to.unwrap_or_else(|| create_address(tx.sender, nonce));
+
+    host.warm_up(revision, tx.sender, recipient);
+    if revision >= Revision::Berlin {
+        for (address, keys) in &tx.access_list {
+            host.access_account(*address);
+            for &key in keys {
+                host.access_storage(*address, key);
+            }
+        }
+    }
+
+    let snapshot = host.accounts.clone();
+    let logs_before = host.recorded.lock().logs.len();
+
+    host.accounts.entry(tx.sender).or_default().balance -= tx.value;
+    host.accounts.entry(recipient).or_default().balance += tx.value;
+
+    let message = Message {
+        kind: if tx.to.is_some() {
+            CallKind::Call
+        } else {
+            CallKind::Create
+        },
+        is_static: false,
+        depth: 0,
+        gas: tx.gas_limit - intrinsic_gas,
+        recipient,
+        code_address: recipient,
+        sender: tx.sender,
+        input_data: if tx.to.is_some() {
+            tx.input.clone()
+        } else {
+            Bytes::new()
+        },
+        value: tx.value,
+    };
+
+    let (output, create_address) = if tx.to.is_some() {
+        let code = host.accounts[&recipient].code.clone();
+        let output =
+            AnalyzedCode::analyze(code).execute(host, &mut NoopTracer, None, message, revision);
+        (output, None)
+    } else {
+        run_create(tx.input.to_vec(), host, message, revision)
+    };
+
+    host.clear_transient_storage();
+
+    let logs = if output.status_code == StatusCode::Success {
+        host.recorded.lock().logs[logs_before..].to_vec()
+    } else {
+        host.accounts = snapshot;
+        host.recorded.lock().logs.truncate(logs_before);
+        Vec::new()
+    };
+
+    let mut gas_used = tx.gas_limit - output.gas_left;
+    if output.status_code == StatusCode::Success {
+        let max_refund_quotient = if revision >= Revision::London { 5 } else { 2 };
+        gas_used -= output.gas_refund.min(gas_used / max_refund_quotient);
+    }
+
+    let sender = host.accounts.entry(tx.sender).or_default();
+    sender.balance += U256::from(tx.gas_limit - gas_used) * tx.gas_price;
+
+    let priority_fee = if revision >= Revision::London {
+        tx.gas_price.saturating_sub(host.tx_context.block_base_fee)
+    } else {
+        tx.gas_price
+    };
+    let coinbase = host.tx_context.block_coinbase;
+    host.accounts.entry(coinbase).or_default().balance += U256::from(gas_used) * priority_fee;
+
+    Ok(Receipt {
+        status_code: output.status_code,
+        gas_used,
+        logs_bloom: logs_bloom(&logs),
+        logs,
+        create_address,
+        output: output.output_data,
+        state_diff: state_diff(&pre_state, &host.accounts),
+    })
+}
@@ -0,0 +1,269 @@
+use ethereum_types::{Address, U256};
+use evmodin::{
+    opcode::*,
+    tracing::NoopTracer,
+    util::{mocked_host::MockedHost, *},
+    *,
+};
+use hex_literal::hex;
+
+#[test]
+fn strict_accepts_valid_code() {
+    let code = Bytecode::new()
+        .jump(3)
+        .opcode(OpCode::JUMPDEST)
+        .pushv(1)
+        .opcode(OpCode::POP);
+    assert!(AnalyzedCode::analyze_strict(code.build()).is_ok());
+}
+
+#[test]
+fn strict_truncated_push() {
+    assert!(AnalyzedCode::analyze_strict(hex!("6001600203").to_vec()).is_ok());
+    assert_eq!(
+        AnalyzedCode::analyze_strict(hex!("600161ff").to_vec()).err(),
+        Some(AnalysisError::TruncatedPush { pc: 2 })
+    );
+    assert_eq!(
+        AnalyzedCode::analyze_strict(hex!("60").to_vec()).err(),
+        Some(AnalysisError::TruncatedPush { pc: 0 })
+    );
+}
+
+#[test]
+fn strict_invalid_jumpdest() {
+    for op in [OpCode::JUMP, OpCode::JUMPI] {
+        let code = Bytecode::new()
+            .pushv(1)
+            .pushv(4)
+            .opcode(op)
+            .opcode(OpCode::STOP);
+        assert_eq!(
+            AnalyzedCode::analyze_strict(code.build()).err(),
+            Some(AnalysisError::InvalidJumpdest { pc: 2 })
+        );
+    }
+
+    // JUMPDEST inside PUSH data is not a valid destination.
+    assert!(AnalyzedCode::analyze_strict(hex!("6003565b").to_vec()).is_ok());
+    assert_eq!(
+        AnalyzedCode::analyze_strict(hex!("600456605b").to_vec()).err(),
+        Some(AnalysisError::InvalidJumpdest { pc: 0 })
+    );
+}
+
+#[test]
+fn strict_oversize_code() {
+    assert!(AnalyzedCode::analyze_strict(vec![0; MAX_CODE_SIZE]).is_ok());
+    assert_eq!(
+        AnalyzedCode::analyze_strict(vec![0; MAX_CODE_SIZE + 1]).err(),
+        Some(AnalysisError::OversizeCode {
+            size: MAX_CODE_SIZE + 1
+        })
+    );
+}
+
+#[test]
+fn lenient_analysis_accepts_malformed_code() {
+    for code in [&hex!("61ff")[..], &hex!("600456605b")[..]] {
+        let _ = AnalyzedCode::analyze(code);
+    }
+}
+
+#[test]
+fn eof_valid() {
+    // Code section: PUSH1 0 PUSH1 0 RETURN, data section: 0xaa.
+    assert!(AnalyzedCode::analyze_eof(&hex!("ef00010100050200010060006000f3aa")).is_ok());
+    assert!(AnalyzedCode::analyze_eof(&hex!("ef00010100010000")).is_ok());
+}
+
+#[test]
+fn eof_errors() {
+    for (container, error) in [
+        (&hex!("ef01010100010000")[..], EofError::InvalidPrefix),
+        (&hex!("00")[..], EofError::InvalidPrefix),
+        (&hex!("ef00020100010000")[..], EofError::UnknownVersion),
+        (&hex!("ef00")[..], EofError::UnknownVersion),
+        (&hex!("ef000101")[..], EofError::IncompleteSectionSize),
+        (&hex!("ef00010100000000")[..], EofError::ZeroSectionSize),
+        (&hex!("ef00010300010000")[..], EofError::UnknownSectionId),
+        (&hex!("ef000100")[..], EofError::CodeSectionMissing),
+        (
+            &hex!("ef0001020001000000")[..],
+            EofError::CodeSectionMissing,
+        ),
+        (
+            &hex!("ef0001010001010001000000")[..],
+            EofError::MultipleCodeSections,
+        ),
+        (
+            &hex!("ef000101000102000102000100000000")[..],
+            EofError::MultipleDataSections,
+        ),
+        (
+            &hex!("ef0001010001")[..],
+            EofError::SectionHeadersNotTerminated,
+        ),
+        (
+            &hex!("ef000101000200")[..],
+            EofError::InvalidSectionBodiesSize,
+        ),
+        (
+            &hex!("ef00010100010000aa")[..],
+            EofError::InvalidSectionBodiesSize,
+        ),
+        (
+            &hex!("ef0001010001000c")[..],
+            EofError::UndefinedInstruction,
+        ),
+        (
+            &hex!("ef0001010002006100")[..],
+            EofError::TruncatedImmediate,
+        ),
+    ] {
+        assert_eq!(
+            AnalyzedCode::analyze_eof(container).err(),
+            Some(AnalysisError::Eof(error))
+        );
+    }
+}
+
+#[test]
+fn eof_oversize_code() {
+    let mut container = hex!("ef0001010001000000").to_vec();
+    container.resize(MAX_CODE_SIZE + 1, 0);
+    assert_eq!(
+        AnalyzedCode::analyze_eof(&container).err(),
+        Some(AnalysisError::OversizeCode {
+            size: MAX_CODE_SIZE + 1
+        })
+    );
+}
+
+#[test]
+fn jumpdests() {
+    // JUMPDEST PUSH1 0x5b JUMPDEST PUSH2 0x5b5b STOP JUMPDEST
+    let code = AnalyzedCode::analyze(hex!("5b605b5b615b5b005b").to_vec());
+    assert_eq!(code.jumpdests(), [0, 3, 8]);
+
+    assert!(AnalyzedCode::analyze(hex!("605b").to_vec())
+        .jumpdests()
+        .is_empty());
+}
+
+fn run(code: &AnalyzedCode, input: U256) -> Output {
+    let mut input_data = [0; 32];
+    input.to_big_endian(&mut input_data);
+    code.execute(
+        &mut MockedHost::default(),
+        &mut NoopTracer,
+        None,
+        Message {
+            kind: CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas: 1_000_000,
+            recipient: Address::zero(),
+            sender: Address::zero(),
+            input_data: input_data.to_vec().into(),
+            value: U256::zero(),
+            code_address: Address::zero(),
+        },
+        Revision::latest(),
+    )
+}
+
+#[test]
+fn serialization_round_trip() {
+    // Jump to the input: 0x5b in PUSH data at 4, JUMPDEST at 9. Code ends with a truncated PUSH2.
+    let code = Bytecode::new()
+        .pushv(0)
+        .opcode(OpCode::CALLDATALOAD)
+        .pushv(0x5b)
+        .opcode(OpCode::POP)
+        .opcode(OpCode::JUMP)
+        .pushv(1)
+        .opcode(OpCode::JUMPDEST)
+        .pushv(2)
+        .ret_top()
+        .append(hex!("6100"))
+        .build();
+
+    let analyzed = AnalyzedCode::analyze(code.clone());
+    let data = analyzed.to_bytes();
+    for verify in [false, true] {
+        let loaded = AnalyzedCode::from_bytes(&data, verify).unwrap();
+        assert_eq!(loaded.code(), analyzed.code());
+        assert_eq!(loaded.jumpdests(), analyzed.jumpdests());
+        assert_eq!(loaded.to_bytes(), data);
+
+        for dst in 0..code.len() + 2 {
+            assert_eq!(
+                run(&loaded, dst.into()),
+                run(&analyzed, dst.into()),
+                "{}",
+                dst
+            );
+        }
+    }
+
+    let empty = AnalyzedCode::analyze(Vec::new()).to_bytes();
+    assert!(AnalyzedCode::from_bytes(&empty, true)
+        .unwrap()
+        .code()
+        .is_empty());
+}
+
+#[test]
+fn serialization_corruption() {
+    // PUSH1 4 JUMP JUMPDEST JUMPDEST STOP
+    let data = AnalyzedCode::analyze(hex!("6004565b5b00").to_vec()).to_bytes();
+
+    for i in 1..data.len() {
+        let mut corrupted = data.clone();
+        corrupted[i] ^= 0x01;
+        assert!(
+            AnalyzedCode::from_bytes(&corrupted, false).is_err(),
+            "{}",
+            i
+        );
+    }
+
+    assert_eq!(
+        AnalyzedCode::from_bytes(&data[..data.len() - 1], false).err(),
+        Some(DecodeError::Truncated)
+    );
+    assert_eq!(
+        AnalyzedCode::from_bytes(&[&data[..], &[0]].concat(), false).err(),
+        Some(DecodeError::TrailingBytes)
+    );
+    assert_eq!(
+        AnalyzedCode::from_bytes(&[], false).err(),
+        Some(DecodeError::Truncated)
+    );
+}
+
+#[test]
+fn serialization_version_mismatch() {
+    let mut data = AnalyzedCode::analyze(hex!("5b00").to_vec()).to_bytes();
+    data[0] = 2;
+    assert_eq!(
+        AnalyzedCode::from_bytes(&data, false).err(),
+        Some(DecodeError::UnsupportedVersion { version: 2 })
+    );
+}
+
+#[test]
+fn stats() {
+    // PUSH1 4 JUMP JUMPDEST JUMPDEST PUSH2 0x5b5b (not jumpdests) STOP PUSH32 truncated to 1 byte.
+    let code = AnalyzedCode::analyze(hex!("6004565b5b615b5b007f00"));
+
+    assert_eq!(
+        code.stats(),
+        AnalysisStats {
+            instructions: 7,
+            jumpdests: 2,
+            push_bytes: 1 + 2 + 1,
+        }
+    );
+}
@@ -0,0 +1,7 @@
+use evmodin::{opcode::*, util::*, *};
+
+#[test]
+fn add() {
+    let elapsed = bench_opcode(OpCode::ADD, &[1.into(), 2.into()], 1000, Revision::latest());
+    assert!(elapsed.as_nanos() > 0);
+}
@@ -1,8 +1,11 @@
 use bytes::Bytes;
-use core::iter::repeat_with;
 use ethereum_types::*;
 use evmodin::{opcode::*, util::*, *};
 use hex_literal::hex;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 #[test]
 fn delegatecall() {
@@ -39,6 +42,30 @@ fn delegatecall() {
         .check()
 }
 
+/// STATICCALL forwards exactly 63/64 of the remaining gas, without stipend.
+#[test]
+fn staticcall_gas_cap() {
+    // 6 pushes + STATICCALL base cost.
+    let gas_left = 10000 - 6 * 3 - 700;
+    let forwarded = gas_left - gas_left / 64;
+
+    EvmTester::new()
+        .code(Bytecode::new() + CallInstruction::staticcall(0).gas(0xffffff))
+        .gas(10000)
+        .gas_used(6 * 3 + 700 + forwarded)
+        .status(StatusCode::Success)
+        .inspect_host(move |host, _| {
+            let r = host.recorded.lock();
+
+            assert_eq!(r.calls.len(), 1);
+            let call_msg = r.calls.last().unwrap();
+            assert_eq!(call_msg.gas, forwarded);
+            assert!(call_msg.is_static);
+            assert!(call_msg.value.is_zero());
+        })
+        .check()
+}
+
 /// Checks if DELEGATECALL forwards the "static" flag.
 #[test]
 fn delegatecall_static() {
@@ -140,6 +167,34 @@ fn create_gas() {
     }
 }
 
+#[test]
+fn create_gas_cap_by_revision() {
+    const GAS: i64 = 100_000;
+    // PUSH1 0, DUP1, DUP1 and CREATE.
+    let gas_left = GAS - 3 * 3 - 32000;
+
+    for rev in Revision::iter() {
+        // EIP-150 caps gas of CREATE at 63/64 of the gas left, as for calls.
+        let expected = if rev >= Revision::Tangerine {
+            gas_left - gas_left / 64
+        } else {
+            gas_left
+        };
+
+        EvmTester::new()
+            .revision(rev)
+            .gas(GAS)
+            .code(hex!("60008080f0"))
+            .status(StatusCode::Success)
+            .inspect_host(move |host, _| {
+                let r = host.recorded.lock();
+                assert_eq!(r.calls.len(), 1);
+                assert_eq!(r.calls[0].gas, expected, "{:?}", rev);
+            })
+            .check()
+    }
+}
+
 #[test]
 fn create2() {
     let address = Address::zero();
@@ -184,9 +239,10 @@ fn create2_salt_cost() {
         .revision(Revision::Constantinople)
         .code(hex!("600060208180f5"));
 
+    assert_gas_boundary!(t.clone(), 32021);
+
     t.clone()
         .gas(32021)
-        .status(StatusCode::Success)
         .gas_left(0)
         .inspect_host(|host, _| {
             let r = host.recorded.lock();
@@ -201,8 +257,6 @@ fn create2_salt_cost() {
         .check();
 
     t.gas(32021 - 1)
-        .status(StatusCode::OutOfGas)
-        .gas_left(0)
         .inspect_host(|host, _| {
             // No another CREATE2.
             assert_eq!(host.recorded.lock().calls.len(), 0)
@@ -1047,6 +1101,52 @@ fn create_oog_after() {
     }
 }
 
+/// Performs a CREATE or CREATE2 then OOG in the same code block.
+#[test]
+fn create_then_oog() {
+    for (op, cost_before) in [(OpCode::CREATE, 32009), (OpCode::CREATE2, 32012)] {
+        let mut code = Bytecode::new();
+        if op == OpCode::CREATE2 {
+            code = code.pushv(0);
+        }
+        code = code.pushv(0).pushv(0).pushv(0).opcode(op);
+        for _ in 0..4 {
+            code = code.opcode(OpCode::DUP1).opcode(OpCode::ADD);
+        }
+        code = code.opcode(OpCode::POP);
+        let cost_after = 4 * (3 + 3) + 2;
+
+        // Child gets 1000 - 1000 / 64 = 985 gas.
+        let t = EvmTester::new()
+            .revision(Revision::Constantinople)
+            .code(code)
+            .gas(cost_before + 1000)
+            .inspect_host(|host, _| {
+                assert_eq!(host.recorded.lock().calls.len(), 1);
+                assert_eq!(host.recorded.lock().calls[0].gas, 985);
+            });
+
+        // Child consumes all its gas: the remaining 15 gas are not enough to continue.
+        t.clone()
+            .apply_host_fn(|host, _| {
+                host.call_result.status_code = StatusCode::Failure;
+                host.call_result.gas_left = 0;
+            })
+            .status(StatusCode::OutOfGas)
+            .gas_left(0)
+            .check();
+
+        // Child returns all its gas: execution continues to the end.
+        t.apply_host_fn(|host, _| {
+            host.call_result.status_code = StatusCode::Failure;
+            host.call_result.gas_left = 985;
+        })
+        .status(StatusCode::Success)
+        .gas_used(cost_before + cost_after)
+        .check();
+    }
+}
+
 #[test]
 fn returndatasize_before_call() {
     EvmTester::new()
@@ -1062,10 +1162,8 @@ fn returndatasize() {
 
     let t = EvmTester::new()
         .apply_host_fn(move |host, _| {
-            host.call_result.output_data = repeat_with(rand::random)
-                .take(call_res_output_len as usize)
-                .collect::<Vec<u8>>()
-                .into()
+            host.call_result.output_data =
+                (1..=call_res_output_len as u8).collect::<Vec<u8>>().into()
         })
         .code(
             Bytecode::new()
@@ -1136,8 +1234,37 @@ fn returndatacopy_cost() {
         .apply_host_fn(|host, _| {
             host.call_result.output_data = vec![0].into();
         });
-    t.clone().gas(736).status(StatusCode::Success).check();
-    t.gas(735).status(StatusCode::OutOfGas).check();
+    let report = assert_gas_boundary!(t, 736);
+    assert_eq!(report.at.unwrap().status_code, StatusCode::Success);
+}
+
+#[test]
+fn seeded_return_data() {
+    let data = hex!("0102030405060708090a");
+
+    let mut expected = [0; 0x40];
+    expected[..8].copy_from_slice(&data[2..]);
+    expected[0x3f] = data.len() as u8;
+
+    EvmTester::new()
+        .seed_return_data(data.to_vec())
+        .code(
+            Bytecode::new()
+                .opcode(OpCode::RETURNDATASIZE)
+                .mstore(0x20)
+                .returndatacopy(0, 2, 8)
+                .ret(0, 0x40),
+        )
+        .status(StatusCode::Success)
+        .output_data(expected)
+        .check();
+
+    // Seeded data bounds RETURNDATACOPY just like call output.
+    EvmTester::new()
+        .seed_return_data(data.to_vec())
+        .code(Bytecode::new().returndatacopy(0, 3, 8))
+        .status(StatusCode::InvalidMemoryAccess)
+        .check();
 }
 
 #[test]
@@ -1157,3 +1284,533 @@ fn returndatacopy_outofrange() {
             .check()
     }
 }
+
+/// `GasLedger` shared between nested executions.
+#[derive(Clone, Default)]
+struct SharedLedger(Rc<RefCell<tracing::GasLedger>>);
+
+impl tracing::Tracer for SharedLedger {
+    fn notify_execution_start(&mut self, revision: Revision, message: Message, code: Bytes) {
+        self.0
+            .borrow_mut()
+            .notify_execution_start(revision, message, code)
+    }
+
+    fn notify_instruction_start(&mut self, pc: usize, opcode: OpCode, state: &ExecutionState) {
+        self.0
+            .borrow_mut()
+            .notify_instruction_start(pc, opcode, state)
+    }
+
+    fn notify_execution_end(&mut self, output: &Output) {
+        self.0.borrow_mut().notify_execution_end(output)
+    }
+
+    fn notify_call_start(&mut self, message: &Message) {
+        self.0.borrow_mut().notify_call_start(message)
+    }
+
+    fn notify_call_end(&mut self, output: &Output) {
+        self.0.borrow_mut().notify_call_end(output)
+    }
+}
+
+/// Host which executes calls against accounts of `MockedHost`, tracing them with the ledger
+/// kept in its state.
+fn reentrant_host() -> DelegatingHost<util::mocked_host::MockedHost, SharedLedger> {
+    DelegatingHost::default().on_call(|host, msg| {
+        let code = host.get_code(msg.code_address);
+        let mut ledger = host.state.clone();
+        AnalyzedCode::analyze(code.to_vec()).execute(
+            host,
+            &mut ledger,
+            None,
+            msg.clone(),
+            Revision::Byzantium,
+        )
+    })
+}
+
+#[test]
+fn call_kinds_code_address() {
+    let origin = Address::from_low_u64_be(0xdd);
+    let recipient = Address::from_low_u64_be(0xcc);
+    let callee = Address::from_low_u64_be(0xaa);
+
+    for (call, expected_recipient, expected_sender) in [
+        (CallInstruction::call(0xaa), callee, recipient),
+        (CallInstruction::callcode(0xaa), recipient, recipient),
+        (CallInstruction::delegatecall(0xaa), recipient, origin),
+        (CallInstruction::staticcall(0xaa), callee, recipient),
+    ] {
+        let op = call.opcode();
+        EvmTester::new()
+            .sender(origin)
+            .destination(recipient)
+            .code(Bytecode::new() + call.gas(1))
+            .status(StatusCode::Success)
+            .inspect_host(move |host, _| {
+                let r = host.recorded.lock();
+
+                assert_eq!(r.calls.len(), 1, "{}", op);
+                let call_msg = r.calls.last().unwrap();
+                assert_eq!(call_msg.code_address, callee, "{}", op);
+                assert_eq!(call_msg.recipient, expected_recipient, "{}", op);
+                assert_eq!(call_msg.sender, expected_sender, "{}", op);
+            })
+            .check()
+    }
+}
+
+#[test]
+fn extcodesize_address_under_delegatecall() {
+    let caller = Address::from_low_u64_be(0xcc);
+    let library = Address::from_low_u64_be(0xaa);
+
+    let caller_code = (Bytecode::new()
+        + CallInstruction::delegatecall(0xaa)
+            .gas(0xffff)
+            .output(0, 0x20))
+    .ret(0, 0x20)
+    .build();
+    let caller_code_size = caller_code.len();
+
+    let mut host = reentrant_host();
+    host.inner.accounts.entry(caller).or_default().code = caller_code.clone().into();
+    // The library reports code size of the account it runs on behalf of.
+    host.inner.accounts.entry(library).or_default().code = Bytecode::new()
+        .opcode(OpCode::ADDRESS)
+        .opcode(OpCode::EXTCODESIZE)
+        .ret_top()
+        .build()
+        .into();
+
+    let output = AnalyzedCode::analyze(caller_code).execute(
+        &mut host,
+        &mut tracing::NoopTracer,
+        None,
+        Message {
+            kind: CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas: 1_000_000,
+            recipient: caller,
+            sender: Address::zero(),
+            input_data: Bytes::new(),
+            value: U256::zero(),
+            code_address: caller,
+        },
+        Revision::Byzantium,
+    );
+
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(
+        U256::from_big_endian(&output.output_data),
+        U256::from(caller_code_size)
+    );
+}
+
+#[test]
+fn gas_ledger_nested_frames() {
+    let callee_a = Address::from_low_u64_be(0xaa);
+    let callee_b = Address::from_low_u64_be(0xbb);
+
+    let mut host = reentrant_host();
+    // A calls B, B executes 10 JUMPDESTs.
+    host.inner.accounts.entry(callee_a).or_default().code =
+        (Bytecode::new() + CallInstruction::call(0xbb).gas(10000) + OpCode::POP)
+            .build()
+            .into();
+    host.inner.accounts.entry(callee_b).or_default().code = (10 * OpCode::JUMPDEST).build().into();
+
+    let mut ledger = host.state.clone();
+    let output = AnalyzedCode::analyze(
+        (Bytecode::new() + CallInstruction::call(0xaa).gas(50000) + OpCode::POP).build(),
+    )
+    .execute(
+        &mut host,
+        &mut ledger,
+        None,
+        Message {
+            kind: CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas: 1_000_000,
+            recipient: Address::zero(),
+            sender: Address::zero(),
+            input_data: Bytes::new(),
+            value: U256::zero(),
+            code_address: Address::zero(),
+        },
+        Revision::Byzantium,
+    );
+    assert_eq!(output.status_code, StatusCode::Success);
+
+    // 7 pushes, CALL and POP around the nested call.
+    let call_overhead = 7 * 3 + 700 + 2;
+    let b_consumed = 10;
+    let a_consumed = call_overhead + b_consumed;
+    let root_consumed = call_overhead + a_consumed;
+
+    let ledger = ledger.0.borrow();
+    assert_eq!(
+        ledger.frames(),
+        [
+            tracing::FrameGas {
+                depth: 0,
+                gas_supplied: 1_000_000,
+                gas_returned: 1_000_000 - root_consumed,
+            },
+            tracing::FrameGas {
+                depth: 1,
+                gas_supplied: 50000,
+                gas_returned: 50000 - a_consumed,
+            },
+            tracing::FrameGas {
+                depth: 2,
+                gas_supplied: 10000,
+                gas_returned: 10000 - b_consumed,
+            },
+        ]
+    );
+    assert_eq!(ledger.frames_at_depth(2).next().unwrap().gas_consumed(), 10);
+    assert_eq!(output.gas_left, 1_000_000 - root_consumed);
+}
+
+/// Tracer collecting outputs of nested calls.
+#[derive(Default)]
+struct CallOutputs(Vec<Output>);
+
+impl tracing::Tracer for CallOutputs {
+    fn notify_execution_start(&mut self, _: Revision, _: Message, _: Bytes) {}
+
+    fn notify_instruction_start(&mut self, _: usize, _: OpCode, _: &ExecutionState) {}
+
+    fn notify_execution_end(&mut self, _: &Output) {}
+
+    fn notify_call_end(&mut self, output: &Output) {
+        self.0.push(output.clone())
+    }
+}
+
+#[test]
+fn resource_maxima_per_frame() {
+    let callee = Address::from_low_u64_be(0xaa);
+
+    let mut host = reentrant_host();
+    // Callee expands memory to 64KB and returns 64 bytes.
+    host.inner.accounts.entry(callee).or_default().code = Bytecode::new()
+        .mstore_value(65536 - 32, 1)
+        .ret(0, 64)
+        .build()
+        .into();
+
+    let mut call_outputs = CallOutputs::default();
+    // Caller expands memory to 1KB before calling.
+    let output = AnalyzedCode::analyze(
+        (Bytecode::new().mstore_value(1024 - 32, 1)
+            + CallInstruction::call(0xaa).gas(100000).output(0, 32)
+            + OpCode::POP)
+            .build(),
+    )
+    .execute(
+        &mut host,
+        &mut call_outputs,
+        None,
+        Message {
+            kind: CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas: 1_000_000,
+            recipient: Address::zero(),
+            sender: Address::zero(),
+            input_data: Bytes::new(),
+            value: U256::zero(),
+            code_address: Address::zero(),
+        },
+        Revision::Byzantium,
+    );
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(output.stats.max_memory_bytes, 1024);
+    assert_eq!(output.stats.max_stack_depth, 7);
+    assert_eq!(output.stats.max_return_data_bytes, 64);
+
+    assert_eq!(call_outputs.0.len(), 1);
+    let child = &call_outputs.0[0];
+    assert_eq!(child.status_code, StatusCode::Success);
+    assert_eq!(child.stats.max_memory_bytes, 65536);
+    assert_eq!(child.stats.max_stack_depth, 2);
+    assert_eq!(child.stats.max_return_data_bytes, 0);
+}
+
+/// CALLCODE and DELEGATECALL run callee code against the caller's storage. DELEGATECALL
+/// keeps the caller's sender and value, CALLCODE makes the caller the sender.
+#[test]
+fn callcode_and_delegatecall_storage_context() {
+    let origin = Address::from_low_u64_be(0x01);
+    let caller = Address::from_low_u64_be(0xaa);
+    let callee = Address::from_low_u64_be(0xbb);
+
+    // Store a constant, CALLER and CALLVALUE.
+    let callee_code = Bytecode::new()
+        .sstore(1, 0x2a)
+        .opcode(OpCode::CALLER)
+        .pushv(2)
+        .opcode(OpCode::SSTORE)
+        .opcode(OpCode::CALLVALUE)
+        .pushv(3)
+        .opcode(OpCode::SSTORE)
+        .build();
+
+    for (call, expected_sender, expected_value) in [
+        (CallInstruction::delegatecall(0xbb), origin, 7),
+        (CallInstruction::callcode(0xbb).value(3), caller, 3),
+    ] {
+        let opcode = call.opcode();
+        let caller_code = (Bytecode::new() + call.gas(100000) + OpCode::POP).build();
+
+        let mut host = reentrant_host();
+        host.inner.accounts.entry(caller).or_default().balance = 10.into();
+        host.inner.accounts.entry(callee).or_default().code = callee_code.clone().into();
+
+        let output = AnalyzedCode::analyze(caller_code).execute(
+            &mut host,
+            &mut tracing::NoopTracer,
+            None,
+            Message {
+                kind: CallKind::Call,
+                is_static: false,
+                depth: 0,
+                gas: 1_000_000,
+                recipient: caller,
+                code_address: caller,
+                sender: origin,
+                input_data: Bytes::new(),
+                value: 7.into(),
+            },
+            Revision::Byzantium,
+        );
+        assert_eq!(output.status_code, StatusCode::Success, "{}", opcode);
+
+        let storage = |address: Address| {
+            host.inner.accounts[&address]
+                .storage
+                .iter()
+                .map(|(k, v)| (*k, v.value))
+                .collect::<std::collections::BTreeMap<_, _>>()
+        };
+        assert_eq!(
+            storage(caller),
+            [
+                (U256::from(1), U256::from(0x2a)),
+                (U256::from(2), U256::from(expected_sender.as_bytes())),
+                (U256::from(3), U256::from(expected_value)),
+            ]
+            .into_iter()
+            .collect(),
+            "{}",
+            opcode
+        );
+        assert!(storage(callee).is_empty(), "{}", opcode);
+    }
+}
+
+#[test]
+fn run_until_first_call() {
+    use evmodin::continuation::Interrupt;
+
+    let mut host = util::mocked_host::MockedHost::default();
+    let code = Bytecode::new().sload(1).opcode(OpCode::POP)
+        + CallInstruction::call(0xaa)
+            .gas(0x1234)
+            .value(7)
+            .input(1, 2)
+            .output(3, 4)
+        + CallInstruction::call(0xbb);
+
+    let paused = AnalyzedCode::analyze(code.clone().build())
+        .execute_resumable(
+            true,
+            Message {
+                kind: CallKind::Call,
+                is_static: false,
+                depth: 0,
+                gas: 1_000_000,
+                recipient: Address::zero(),
+                sender: Address::zero(),
+                input_data: Bytes::new(),
+                value: U256::zero(),
+                code_address: Address::zero(),
+            },
+            Revision::Byzantium,
+        )
+        .run_until_opcode(&mut host, OpCode::CALL)
+        .unwrap();
+
+    // SLOAD has been answered by the host, but no call has been made yet.
+    assert_eq!(host.recorded.lock().calls.len(), 0);
+    assert_eq!(paused.data().opcode, OpCode::CALL);
+    assert_eq!(
+        paused.data().pc,
+        code.len() - Bytecode::from(CallInstruction::call(0xbb)).len() - 1
+    );
+
+    let stack = paused.data().state.stack();
+    assert_eq!(
+        (0..7).map(|i| *stack.get(i)).collect::<Vec<_>>(),
+        [0x1234, 0xaa, 7, 1, 2, 3, 4].map(U256::from).to_vec()
+    );
+}
+
+#[test]
+fn frame_of_paused_interrupt() {
+    use evmodin::continuation::{interrupt::InterruptVariant, Interrupt};
+
+    let message = Message {
+        kind: CallKind::Call,
+        is_static: false,
+        depth: 3,
+        gas: 1000,
+        recipient: Address::from_low_u64_be(0xcc),
+        sender: Address::zero(),
+        input_data: Bytes::new(),
+        value: U256::zero(),
+        code_address: Address::from_low_u64_be(0xcc),
+    };
+
+    let start = AnalyzedCode::analyze(Bytecode::new().sload(1).build()).execute_resumable(
+        false,
+        message.clone(),
+        Revision::Byzantium,
+    );
+    assert_eq!(start.frame().message.depth, 3);
+    assert_eq!(start.frame().gas_left, 1000);
+
+    let paused = match start.resume(()) {
+        InterruptVariant::GetStorage(i) => i,
+        _ => panic!("expected GetStorage"),
+    };
+    let frame = paused.frame();
+    assert_eq!(frame.message.depth, 3);
+    assert_eq!(frame.message.recipient, Address::from_low_u64_be(0xcc));
+    assert_eq!(*frame.message, message);
+    // PUSH1 1 SLOAD, both charged before the host is asked.
+    assert_eq!(frame.pc, 2);
+    assert_eq!(frame.gas_left, 1000 - 3 - 200);
+    assert_eq!(paused.data().address, frame.message.recipient);
+}
+
+#[test]
+fn zero_value_call_to_new_account() {
+    // EIP-161: since Spurious Dragon only value transfers pay for creating an account.
+    for rev in [Revision::Tangerine, Revision::Spurious] {
+        for (call, pushes) in [
+            (CallInstruction::call(0xaa), 7),
+            (CallInstruction::callcode(0xaa), 7),
+            (CallInstruction::delegatecall(0xaa), 6),
+        ] {
+            let op = call.opcode();
+            let new_account_cost = if op == OpCode::CALL && rev < Revision::Spurious {
+                25000
+            } else {
+                0
+            };
+
+            EvmTester::new()
+                .revision(rev)
+                .code(call)
+                .status(StatusCode::Success)
+                .gas_used(pushes * 3 + 700 + new_account_cost)
+                .inspect_host(move |host, _| {
+                    let r = host.recorded.lock();
+                    assert_eq!(r.calls.len(), 1, "{} {:?}", op, rev);
+                    assert_eq!(r.calls[0].value, 0.into(), "{} {:?}", op, rev);
+                })
+                .check();
+        }
+    }
+}
+
+#[test]
+fn probed_gas_matches_hand_computed() {
+    // `delegatecall`: DELEGATECALL at pc 21 leaves 1700 - 736 gas after its base cost.
+    let (_, probed) = EvmTester::new()
+        .code(
+            Bytecode::new()
+                .append(hex!("6001600003600052"))
+                .append(hex!("600560046003600260016103e8f4"))
+                .append(hex!("60086000f3")),
+        )
+        .apply_host_fn(|host, _| host.call_result.gas_left = 1)
+        .gas(1700)
+        .probe_gas_at_pc(21)
+        .gas_used(1690)
+        .status(StatusCode::Success)
+        .check_and_get_probed_gas();
+    assert_eq!(probed[&21] - 700, 1700 - 736);
+
+    // `staticcall_gas_cap`: 6 pushes precede STATICCALL.
+    let code = (Bytecode::new() + CallInstruction::staticcall(0).gas(0xffffff)).build();
+    let (_, probed) = EvmTester::new()
+        .code(code.clone())
+        .gas(10000)
+        .probe_gas_at_pc(code.len() - 1)
+        .probe_gas_at_pc(0)
+        .status(StatusCode::Success)
+        .check_and_get_probed_gas();
+    assert_eq!(probed[&0], 10000);
+    assert_eq!(probed[&(code.len() - 1)] - 700, 10000 - 6 * 3 - 700);
+    assert_eq!(probed.len(), 2);
+}
+
+/// CALL with gas argument `gas` from 10000 available gas, returning gas forwarded to the callee.
+fn forwarded_call_gas(gas: Bytecode) -> i64 {
+    let code = Bytecode::new()
+        .pushv(0)
+        .pushv(0)
+        .pushv(0)
+        .pushv(0)
+        .pushv(0)
+        .pushv(0xaa)
+        .append_bc(gas)
+        .opcode(OpCode::CALL);
+
+    let forwarded = Rc::new(Cell::new(0));
+    EvmTester::new()
+        .revision(Revision::Istanbul)
+        .gas(10000)
+        .code(code)
+        .status(StatusCode::Success)
+        .inspect_host({
+            let forwarded = forwarded.clone();
+            move |host, _| {
+                let r = host.recorded.lock();
+                assert_eq!(r.calls.len(), 1);
+                forwarded.set(r.calls[0].gas);
+            }
+        })
+        .check();
+    forwarded.get()
+}
+
+/// Gas left before the call of `forwarded_call_gas`, capped by EIP-150.
+fn all_but_one_64th() -> i64 {
+    let gas_left = 10000 - 7 * 3 - 700;
+    gas_left - gas_left / 64
+}
+
+#[test]
+fn call_gas_sentinel_forwards_cap() {
+    assert_eq!(
+        forwarded_call_gas(Bytecode::new().pushb(hex!("7fffffffffffffff"))),
+        all_but_one_64th()
+    );
+}
+
+#[test]
+fn call_gas_above_i64_forwards_cap() {
+    assert_eq!(
+        forwarded_call_gas(Bytecode::new().pushv(U256::MAX)),
+        all_but_one_64th()
+    );
+}
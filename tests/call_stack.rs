@@ -0,0 +1,217 @@
+use bytes::Bytes;
+use ethereum_types::{Address, U256};
+use evmodin::{
+    host::*,
+    opcode::*,
+    tracing::NoopTracer,
+    util::{mocked_host::MockedHost, *},
+    *,
+};
+
+const GAS: i64 = 10_000_000_000_000;
+const REVISION: Revision = Revision::Istanbul;
+/// Frames executed: depths 0 to 1024, the CALL at depth 1024 fails.
+const FRAMES: i64 = 1025;
+
+fn contract() -> Address {
+    Address::from_low_u64_be(0xaa)
+}
+
+/// Call itself with all gas left and return one more than the callee has returned.
+fn self_calling_code() -> Bytes {
+    Bytecode::new()
+        .pushv(32)
+        .pushv(0)
+        .pushv(0)
+        .pushv(0)
+        .pushv(0)
+        .opcode(OpCode::ADDRESS)
+        .opcode(OpCode::GAS)
+        .opcode(OpCode::CALL)
+        .opcode(OpCode::POP)
+        .pushv(0)
+        .opcode(OpCode::MLOAD)
+        .pushv(1)
+        .opcode(OpCode::ADD)
+        .ret_top()
+        .build()
+        .into()
+}
+
+fn message() -> Message {
+    Message {
+        kind: CallKind::Call,
+        is_static: false,
+        depth: 0,
+        gas: GAS,
+        recipient: contract(),
+        sender: Address::zero(),
+        input_data: Bytes::new(),
+        value: U256::zero(),
+        code_address: contract(),
+    }
+}
+
+/// Gas left by the top frame of `self_calling_code`.
+fn expected_gas_left() -> i64 {
+    // Five PUSH1, ADDRESS, GAS, CALL and expansion of memory for the output.
+    let before_call = 5 * 3 + 2 + 2 + 700 + 3;
+    // POP, PUSH1, MLOAD, PUSH1, ADD, then MSTORE and RETURN with their pushes.
+    let after_call = 2 + 3 + 3 + 3 + 3 + 4 * 3;
+
+    let mut remaining = Vec::new();
+    let mut gas = GAS;
+    for _ in 0..FRAMES {
+        let r = gas - before_call;
+        remaining.push(r);
+        gas = r - r / 64;
+    }
+
+    // The last frame cannot call, so it keeps all of its gas.
+    let mut gas_left = remaining.pop().unwrap() - after_call;
+    while let Some(r) = remaining.pop() {
+        gas_left += r / 64 - after_call;
+    }
+    gas_left
+}
+
+#[test]
+fn self_call_to_max_depth() {
+    let mut host = MockedHost::default();
+    host.accounts.entry(contract()).or_default().code = self_calling_code();
+
+    let mut executor = CallStackExecutor::new(&mut host, REVISION);
+    let output = executor.execute(message());
+
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(U256::from_big_endian(&output.output_data), FRAMES.into());
+    assert_eq!(output.gas_left, expected_gas_left());
+    assert_eq!(executor.max_depth(), 1024);
+}
+
+#[test]
+fn creates_go_to_host() {
+    let mut host = MockedHost::default();
+    host.accounts.entry(contract()).or_default().code = Bytecode::new()
+        .pushv(0)
+        .pushv(0)
+        .pushv(0)
+        .opcode(OpCode::CREATE)
+        .build()
+        .into();
+
+    let output = CallStackExecutor::new(&mut host, REVISION).execute(message());
+
+    assert_eq!(output.status_code, StatusCode::Success);
+    let r = host.recorded.lock();
+    assert_eq!(r.calls.len(), 1);
+    assert_eq!(r.calls[0].kind, CallKind::Create);
+}
+
+/// Number of `copy_code` calls fetching code of `address` in chunks of `chunk` bytes.
+fn copy_code_round_trips(host: &MockedHost, address: Address, chunk: usize) -> usize {
+    let mut buffer = vec![0; chunk];
+    let mut round_trips = 1;
+    while host.copy_code(address, (round_trips - 1) * chunk, &mut buffer) == chunk {
+        round_trips += 1;
+    }
+    round_trips
+}
+
+#[test]
+fn single_code_fetch_per_call_frame() {
+    let caller = Address::from_low_u64_be(0xbb);
+    let callee = Address::from_low_u64_be(0xcc);
+
+    let mut host = MockedHost::default();
+    host.accounts.entry(contract()).or_default().code =
+        (Bytecode::new() + CallInstruction::call(0xbb).gas(0xfffff) + OpCode::POP)
+            .build()
+            .into();
+    // Calls the callee twice, which runs a long body and stops.
+    host.accounts.entry(caller).or_default().code = (Bytecode::new()
+        + CallInstruction::call(0xcc).gas(0xffff)
+        + OpCode::POP
+        + CallInstruction::call(0xcc).gas(0xffff))
+    .build()
+    .into();
+    host.accounts.entry(callee).or_default().code = (100 * OpCode::JUMPDEST).build().into();
+
+    let output = CallStackExecutor::new(&mut host, REVISION).execute(message());
+    assert_eq!(output.status_code, StatusCode::Success);
+
+    // One `get_code` per frame, and code is never copied piecewise.
+    let fetches = host.records().code_fetches.clone();
+    assert_eq!(fetches, [contract(), caller, callee, callee]);
+
+    // Fetching the same code with `get_code_size` and `copy_code` in 32-byte chunks.
+    assert_eq!(copy_code_round_trips(&host, callee, 32), 4);
+    let chunked = fetches
+        .iter()
+        .map(|&address| 1 + copy_code_round_trips(&host, address, 32))
+        .sum::<usize>();
+    assert!(chunked > 2 * fetches.len());
+    println!(
+        "code round-trips for {} frames: get_code {}, copy_code in 32-byte chunks {}",
+        fetches.len(),
+        fetches.len(),
+        chunked
+    );
+}
+
+/// Executes callees with `code` by calling into the interpreter from `Host::call`.
+fn recursive_host(code: Bytes) -> DelegatingHost<DummyHost, Bytes> {
+    DelegatingHost::new(DummyHost, code).on_call(|host, msg| {
+        AnalyzedCode::analyze(host.state.to_vec()).execute(
+            host,
+            &mut NoopTracer,
+            None,
+            msg.clone(),
+            REVISION,
+        )
+    })
+}
+
+/// Peak resident set size of this process in kB.
+fn peak_rss() -> Option<u64> {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Run with `--ignored --nocapture` on Linux. Recursion needs a much bigger native stack than
+/// the default one, and the peak RSS grows accordingly.
+#[test]
+#[ignore]
+fn peak_rss_versus_recursion() {
+    let before = peak_rss();
+
+    let mut host = MockedHost::default();
+    host.accounts.entry(contract()).or_default().code = self_calling_code();
+    let output = CallStackExecutor::new(&mut host, REVISION).execute(message());
+    assert_eq!(output.gas_left, expected_gas_left());
+    let call_stack = peak_rss();
+
+    let output = std::thread::Builder::new()
+        .stack_size(1024 * 1024 * 1024)
+        .spawn(|| {
+            let mut host = recursive_host(self_calling_code());
+            host.call(&message())
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(output.gas_left, expected_gas_left());
+    let recursion = peak_rss();
+
+    println!(
+        "peak RSS, kB: before {:?}, call stack executor {:?}, recursion {:?}",
+        before, call_stack, recursion
+    );
+}
@@ -0,0 +1,119 @@
+use ethereum_types::{Address, U256};
+use evmodin::{opcode::*, util::*, *};
+
+fn frame(
+    kind: CallKind,
+    recipient: u64,
+    value: u64,
+    gas: i64,
+    input: &[u8],
+    result: (StatusCode, i64, &[u8]),
+    children: Vec<CallTreeNode>,
+) -> CallTreeNode {
+    let recipient = Address::from_low_u64_be(recipient);
+    let (status_code, gas_left, output_data) = result;
+    CallTreeNode {
+        message: Message {
+            kind,
+            is_static: false,
+            depth: 0,
+            gas,
+            recipient,
+            code_address: recipient,
+            sender: Address::zero(),
+            input_data: input.to_vec().into(),
+            value: U256::from(value),
+        },
+        output: Some(Output {
+            status_code,
+            gas_left,
+            gas_refund: 0,
+            output_data: output_data.to_vec().into(),
+            create_address: None,
+            stats: Default::default(),
+        }),
+        children,
+    }
+}
+
+fn nested_calls() -> Vec<CallTreeNode> {
+    let mut static_frame = frame(
+        CallKind::Call,
+        0xcc,
+        0,
+        30000,
+        &[0x01],
+        (StatusCode::Success, 29000, &[]),
+        vec![],
+    );
+    static_frame.message.is_static = true;
+
+    vec![frame(
+        CallKind::Call,
+        0xaa,
+        0,
+        100000,
+        &[0xa9, 0x05, 0x9c, 0xbb, 0x00, 0x00],
+        (StatusCode::Success, 50000, &[0x01]),
+        vec![
+            frame(
+                CallKind::Call,
+                0xbb,
+                5,
+                60000,
+                &[],
+                (StatusCode::Revert, 1000, &[0x08, 0xc3, 0x79, 0xa0, 0x00]),
+                vec![static_frame],
+            ),
+            frame(
+                CallKind::DelegateCall,
+                0xdd,
+                0,
+                5000,
+                &[],
+                (StatusCode::Success, 4000, &[]),
+                vec![],
+            ),
+        ],
+    )]
+}
+
+#[test]
+fn render_nested_calls() {
+    assert_eq!(
+        render_call_tree(&nested_calls(), 8),
+        "\
+- CALL 0x00000000000000000000000000000000000000aa value=0 gas=100000->50000 success input=0xa9059cbb.. output=0x01
+  x CALL 0x00000000000000000000000000000000000000bb value=5 gas=60000->1000 revert input=0x output=0x08c379a0..
+    x STATICCALL 0x00000000000000000000000000000000000000cc value=0 gas=30000->29000 success input=0x01 output=0x
+  - DELEGATECALL 0x00000000000000000000000000000000000000dd value=0 gas=5000->4000 success input=0x output=0x
+"
+    );
+}
+
+#[test]
+fn render_bounded_depth() {
+    assert_eq!(
+        render_call_tree(&nested_calls(), 2),
+        "\
+- CALL 0x00000000000000000000000000000000000000aa value=0 gas=100000->50000 success input=0xa9059cbb.. output=0x01
+  x CALL 0x00000000000000000000000000000000000000bb value=5 gas=60000->1000 revert input=0x output=0x08c379a0..
+    ... 1 nested frame(s)
+  - DELEGATECALL 0x00000000000000000000000000000000000000dd value=0 gas=5000->4000 success input=0x output=0x
+"
+    );
+}
+
+#[test]
+fn render_recorded_calls() {
+    EvmTester::new()
+        .code(Bytecode::new() + CallInstruction::call(0xaa).gas(100).input(0, 2) + OpCode::POP)
+        .status(StatusCode::Success)
+        .inspect_host(|host, _| {
+            assert_eq!(
+                render_call_tree(&CallTreeNode::from_recording(host), 8),
+                "- CALL 0x00000000000000000000000000000000000000aa value=0 gas=100->0 success input=0x0000 output=0x\n"
+            );
+        })
+        .check();
+}
@@ -0,0 +1,60 @@
+use evmodin::{opcode::*, util::*, *};
+
+fn without(capability: Capability) -> ExecutionOptions {
+    ExecutionOptions {
+        capabilities: CapabilityMask::default().without(capability),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn log_disabled() {
+    // LOG0(0, 0)
+    let log = Bytecode::new().pushv(0).pushv(0).opcode(OpCode::LOG0);
+
+    EvmTester::new()
+        .code(log)
+        .options(without(Capability::Log))
+        .status(StatusCode::StaticModeViolation)
+        .check();
+
+    EvmTester::new()
+        .code(Bytecode::new().sstore(1, 1).sload(1).ret_top())
+        .options(without(Capability::Log))
+        .status(StatusCode::Success)
+        .output_value(1)
+        .check();
+}
+
+#[test]
+fn static_frame_has_no_capabilities() {
+    EvmTester::new()
+        .code(Bytecode::new().pushv(0).pushv(0).opcode(OpCode::LOG0))
+        .set_static(true)
+        .options(ExecutionOptions {
+            capabilities: CapabilityMask::default().with(Capability::Sudo),
+            ..Default::default()
+        })
+        .status(StatusCode::StaticModeViolation)
+        .check();
+}
+
+#[test]
+fn mask() {
+    let mask = CapabilityMask::default();
+    assert!(mask.contains(Capability::WriteStorage));
+    assert!(!mask.contains(Capability::Sudo));
+    assert!(mask.with(Capability::Sudo).contains(Capability::Sudo));
+    assert!(!mask
+        .without(Capability::Selfdestruct)
+        .contains(Capability::Selfdestruct));
+    assert!(mask
+        .without(Capability::Selfdestruct)
+        .contains(Capability::CreateAccounts));
+    assert_eq!(
+        CapabilityMask::empty()
+            .with(Capability::Log)
+            .without(Capability::Log),
+        CapabilityMask::empty()
+    );
+}
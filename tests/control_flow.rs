@@ -0,0 +1,136 @@
+use evmodin::{opcode::*, util::*, *};
+
+/// Run code which returns a single word, checking that it is accepted by strict analysis and
+/// that the stack stays within `max_stack_depth`.
+fn run(code: Bytecode, input: &[u8], max_stack_depth: usize, expected: u64) {
+    let code = code.build();
+    AnalyzedCode::analyze_strict(code.clone()).unwrap();
+
+    let output = EvmTester::new()
+        .code(code)
+        .input(input.to_vec())
+        .status(StatusCode::Success)
+        .output_value(expected)
+        .check_and_get_result();
+    assert!(
+        output.stats.max_stack_depth <= max_stack_depth,
+        "{}",
+        output.stats.max_stack_depth
+    );
+}
+
+/// Stack: [sum, i] -> [sum + i, i - 1].
+fn add_and_decrement() -> Bytecode {
+    Bytecode::new()
+        .opcode(OpCode::DUP1)
+        .opcode(OpCode::SWAP2)
+        .opcode(OpCode::ADD)
+        .opcode(OpCode::SWAP1)
+        .pushv(1)
+        .opcode(OpCode::SWAP1)
+        .opcode(OpCode::SUB)
+}
+
+/// Stack: [acc, ..n items] -> [acc + value, ..n items].
+fn add_to(depth: usize, value: u64) -> Bytecode {
+    let swap = OpCode(OpCode::SWAP1.to_u8() + depth as u8 - 1);
+    Bytecode::new()
+        .opcode(swap)
+        .pushv(value)
+        .opcode(OpCode::ADD)
+        .opcode(swap)
+}
+
+#[test]
+fn while_loop_sum() {
+    run(
+        Bytecode::new()
+            .pushv(0)
+            .pushv(10)
+            .while_loop(OpCode::DUP1, add_and_decrement())
+            .opcode(OpCode::POP)
+            .ret_top(),
+        &[],
+        4,
+        55,
+    );
+}
+
+#[test]
+fn if_else_in_loop() {
+    // Add 1 for every even index and 100 for every odd one.
+    run(
+        Bytecode::new()
+            .pushv(0)
+            .for_range(10, |i| {
+                Bytecode::new().if_else(
+                    i.pushv(1).opcode(OpCode::AND).opcode(OpCode::ISZERO),
+                    add_to(1, 1),
+                    add_to(1, 100),
+                )
+            })
+            .ret_top(),
+        &[],
+        5,
+        505,
+    );
+}
+
+#[test]
+fn nested_three_deep() {
+    // Add 10 for every pair of equal indices and 1 for every other pair.
+    run(
+        Bytecode::new()
+            .pushv(0)
+            .for_range(3, |_| {
+                Bytecode::new().for_range(4, |j| {
+                    Bytecode::new().if_else(
+                        j.opcode(OpCode::DUP3).opcode(OpCode::EQ),
+                        add_to(2, 10),
+                        add_to(2, 1),
+                    )
+                })
+            })
+            .ret_top(),
+        &[],
+        6,
+        3 * 10 + 9,
+    );
+}
+
+#[test]
+fn separate_loops() {
+    let sum_to = |n: u64| {
+        Bytecode::new()
+            .pushv(n)
+            .while_loop(OpCode::DUP1, add_and_decrement())
+            .opcode(OpCode::POP)
+    };
+
+    run(
+        Bytecode::new().pushv(0) + sum_to(10) + sum_to(3) + Bytecode::new().ret_top(),
+        &[],
+        4,
+        55 + 6,
+    );
+}
+
+#[test]
+fn switch() {
+    let code = Bytecode::new()
+        .switch(
+            Bytecode::new().pushv(0).opcode(OpCode::CALLDATALOAD),
+            [
+                (1, Bytecode::new().pushv(0x11).ret_top()),
+                (2, Bytecode::new().pushv(0x22).ret_top()),
+            ],
+        )
+        .pushv(0xff)
+        .ret_top();
+
+    for (selector, expected) in [(1, 0x11), (2, 0x22), (3, 0xff)] {
+        let mut input = [0; 32];
+        input[31] = selector;
+        run(code.clone(), &input, 3, expected);
+    }
+}
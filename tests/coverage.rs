@@ -0,0 +1,132 @@
+use bytes::Bytes;
+use ethereum_types::{Address, U256};
+use evmodin::{
+    opcode::*,
+    tracing::*,
+    util::{mocked_host::MockedHost, *},
+    *,
+};
+use hex_literal::hex;
+
+fn run(code: &AnalyzedCode, coverage: &mut CoverageMap, input: impl Into<Bytes>) -> Output {
+    code.execute(
+        &mut MockedHost::default(),
+        coverage,
+        None,
+        Message {
+            kind: CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas: 1_000_000,
+            recipient: Address::zero(),
+            sender: Address::zero(),
+            input_data: input.into(),
+            value: U256::zero(),
+            code_address: Address::zero(),
+        },
+        Revision::latest(),
+    )
+}
+
+/// Two-function selector dispatcher with both branches jumping to `STOP`.
+fn dispatcher() -> AnalyzedCode {
+    let code = Bytecode::new()
+        .pushv(0)
+        .opcode(OpCode::CALLDATALOAD)
+        .pushv(0xe0)
+        .opcode(OpCode::SHR)
+        .opcode(OpCode::DUP1)
+        .pushb(hex!("aaaaaaaa"))
+        .opcode(OpCode::EQ)
+        .pushv(31)
+        .opcode(OpCode::JUMPI)
+        .opcode(OpCode::DUP1)
+        .pushb(hex!("bbbbbbbb"))
+        .opcode(OpCode::EQ)
+        .pushv(33)
+        .opcode(OpCode::JUMPI)
+        .revert(0, 0)
+        .opcode(OpCode::JUMPDEST)
+        .opcode(OpCode::STOP)
+        .opcode(OpCode::JUMPDEST)
+        .opcode(OpCode::STOP);
+
+    AnalyzedCode::analyze(code.build())
+}
+
+#[test]
+fn dispatcher_branch_coverage() {
+    let code = dispatcher();
+    let mut coverage = CoverageMap::default();
+
+    let output = run(&code, &mut coverage, hex!("bbbbbbbb").to_vec());
+    assert_eq!(output.status_code, StatusCode::Success);
+
+    assert_eq!(
+        coverage.branch_coverage(&code),
+        vec![
+            BranchCoverage {
+                jumpi_pc: 15,
+                target: JumpTarget::Static(31.into()),
+                taken_hit: false,
+                fallthrough_hit: true,
+            },
+            BranchCoverage {
+                jumpi_pc: 25,
+                target: JumpTarget::Static(33.into()),
+                taken_hit: true,
+                fallthrough_hit: false,
+            },
+        ]
+    );
+    assert_eq!(coverage.branch_coverage_ratio(&code), 0.5);
+    assert!(coverage.is_hit(33));
+    assert!(!coverage.is_hit(31));
+
+    // Coverage accumulates over executions of the same code.
+    let output = run(&code, &mut coverage, hex!("aaaaaaaa").to_vec());
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(coverage.branch_coverage_ratio(&code), 0.75);
+}
+
+#[test]
+fn dynamic_jump_branch_coverage() {
+    // PUSH1 1 PUSH1 0 CALLDATALOAD JUMPI STOP JUMPDEST STOP
+    let code = AnalyzedCode::analyze(hex!("600160003557005b00").to_vec());
+    let mut coverage = CoverageMap::default();
+
+    let mut input = [0; 32];
+    input[31] = 7;
+    let output = run(&code, &mut coverage, input.to_vec());
+    assert_eq!(output.status_code, StatusCode::Success);
+
+    assert_eq!(
+        coverage.branch_coverage(&code),
+        vec![BranchCoverage {
+            jumpi_pc: 5,
+            target: JumpTarget::Dynamic,
+            taken_hit: true,
+            fallthrough_hit: false,
+        }]
+    );
+}
+
+#[test]
+fn fallthrough_past_end_of_code() {
+    // PUSH1 0 PUSH1 0 JUMPI
+    let code = AnalyzedCode::analyze(hex!("6000600057").to_vec());
+    let mut coverage = CoverageMap::default();
+
+    let output = run(&code, &mut coverage, Bytes::new());
+    assert_eq!(output.status_code, StatusCode::Success);
+
+    assert_eq!(
+        coverage.branch_coverage(&code),
+        vec![BranchCoverage {
+            jumpi_pc: 4,
+            target: JumpTarget::Static(0.into()),
+            taken_hit: false,
+            fallthrough_hit: true,
+        }]
+    );
+}
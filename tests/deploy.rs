@@ -0,0 +1,228 @@
+use bytes::Bytes;
+use ethereum_types::{Address, U256};
+use evmodin::{
+    opcode::*,
+    tracing::Tracer,
+    util::{mocked_host::*, *},
+    *,
+};
+use hex_literal::hex;
+use std::{cell::RefCell, rc::Rc};
+
+#[test]
+fn create_address_derivation() {
+    let sender = Address::from(hex!("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0"));
+
+    assert_eq!(
+        create_address(sender, 0),
+        Address::from(hex!("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"))
+    );
+    assert_eq!(
+        create_address(sender, 1),
+        Address::from(hex!("343c43a37d37dff08ae8c4a11544c718abb4fcf8"))
+    );
+}
+
+#[test]
+fn deploy_and_call() {
+    let runtime = Bytecode::new().pushv(42).ret_top().build();
+
+    let mut host = MockedHost::default();
+    let (output, address) = deploy(
+        Bytecode::deploy(runtime.clone()),
+        &mut host,
+        Revision::London,
+    );
+
+    assert_eq!(output.status_code, StatusCode::Success);
+    let address = address.unwrap();
+    assert_eq!(output.create_address, Some(address));
+    assert_eq!(address, create_address(Address::zero(), 0));
+    assert_eq!(&*host.accounts[&address].code, &runtime[..]);
+
+    EvmTester::new()
+        .revision(Revision::London)
+        .destination(address)
+        .code(host.accounts[&address].code.to_vec())
+        .status(StatusCode::Success)
+        .output_value(42)
+        .check();
+
+    // Nonce of the deployer is bumped, so the next contract lands elsewhere.
+    let (_, second) = deploy(Bytecode::new().ret(0, 0), &mut host, Revision::London);
+    assert_eq!(second, Some(create_address(Address::zero(), 1)));
+}
+
+#[test]
+fn deploy_code_returns_runtime() {
+    for runtime in [
+        vec![],
+        hex!("00").to_vec(),
+        Bytecode::new().sstore(1, 2).build(),
+        vec![0xfe; 300],
+    ] {
+        EvmTester::new()
+            .code(Bytecode::deploy(runtime.clone()))
+            .status(StatusCode::Success)
+            .output_data(runtime)
+            .check();
+    }
+}
+
+/// Tracer collecting creation failure reasons of nested calls.
+#[derive(Clone, Default)]
+struct CreateFailures(Rc<RefCell<Vec<Option<CreateFailure>>>>);
+
+impl Tracer for CreateFailures {
+    fn notify_execution_start(&mut self, _: Revision, _: Message, _: Bytes) {}
+
+    fn notify_instruction_start(&mut self, _: usize, _: OpCode, _: &ExecutionState) {}
+
+    fn notify_execution_end(&mut self, _: &Output) {}
+
+    fn notify_call_end(&mut self, output: &Output) {
+        self.0.borrow_mut().push(output.stats.create_failure)
+    }
+}
+
+#[test]
+fn create_failure_reasons() {
+    for (init_code, status_code, reason) in [
+        (
+            Bytecode::new().revert(0, 0),
+            StatusCode::Revert,
+            CreateFailure::InitFailed,
+        ),
+        (
+            Bytecode::new().ret(0, MAX_CODE_SIZE + 1),
+            StatusCode::OutOfGas,
+            CreateFailure::CodeTooLarge,
+        ),
+        (
+            Bytecode::deploy(hex!("ef00")),
+            StatusCode::ContractValidationFailure,
+            CreateFailure::InvalidCodePrefix,
+        ),
+    ] {
+        let (output, address) = deploy(init_code, &mut MockedHost::default(), Revision::London);
+        assert_eq!(output.status_code, status_code);
+        assert_eq!(address, None);
+        assert_eq!(output.stats.create_failure, Some(reason));
+
+        // Creating contract gets zero address either way, its tracer can see the reason.
+        let tracer = CreateFailures::default();
+        EvmTester::new()
+            .revision(Revision::London)
+            .apply_host_fn(move |host, _| host.call_result = output.clone())
+            .code(
+                Bytecode::new()
+                    .pushv(0)
+                    .pushv(0)
+                    .pushv(0)
+                    .opcode(OpCode::CREATE)
+                    .ret_top(),
+            )
+            .tracer(tracer.clone())
+            .status(StatusCode::Success)
+            .output_value(0)
+            .check();
+        assert_eq!(*tracer.0.borrow(), [Some(reason)]);
+    }
+}
+
+#[test]
+fn create_frames_have_no_input() {
+    // Return CALLDATASIZE and CALLDATALOAD(0).
+    let init_code = Bytecode::new()
+        .opcode(OpCode::CALLDATASIZE)
+        .mstore(0)
+        .pushv(0)
+        .opcode(OpCode::CALLDATALOAD)
+        .mstore(0x20)
+        .ret(0, 0x40)
+        .build();
+
+    let message = Message::from(CreateMessage {
+        salt: None,
+        gas: 100_000,
+        depth: 1,
+        initcode: init_code.clone().into(),
+        sender: Address::zero(),
+        endowment: 0.into(),
+    });
+    assert!(!message.input_data.is_empty());
+
+    let output = AnalyzedCode::analyze(init_code).execute(
+        &mut MockedHost::default(),
+        &mut evmodin::tracing::NoopTracer,
+        None,
+        message,
+        Revision::London,
+    );
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(output.output_data, Bytes::from(vec![0; 0x40]));
+}
+
+#[test]
+fn constructor_arguments_are_part_of_code() {
+    let args = [0xaa; 4];
+    // Return CODESIZE and the last word of code.
+    let code = Bytecode::new()
+        .opcode(OpCode::CODESIZE)
+        .mstore(0)
+        .pushv(0x20)
+        .pushv(0x20)
+        .opcode(OpCode::CODESIZE)
+        .opcode(OpCode::SUB)
+        .pushv(0x20)
+        .opcode(OpCode::CODECOPY)
+        .ret(0, 0x40)
+        .append(args);
+    let code_len = code.clone().build().len();
+
+    let (output, _) = deploy(code, &mut MockedHost::default(), Revision::London);
+
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(
+        U256::from_big_endian(&output.output_data[..0x20]),
+        code_len.into()
+    );
+    assert_eq!(output.output_data[0x3c..], args);
+}
+
+#[test]
+fn deploy_warms_coinbase_in_shanghai() {
+    let coinbase = Address::from_low_u64_be(0xcb);
+    let init_code = Bytecode::new()
+        .pushb(coinbase.0)
+        .opcode(OpCode::BALANCE)
+        .opcode(OpCode::POP);
+
+    for (revision, balance_cost) in [(Revision::London, 2600), (Revision::Shanghai, 100)] {
+        let mut host = MockedHost::default();
+        host.tx_context.block_coinbase = coinbase;
+
+        let (output, _) = deploy(init_code.clone(), &mut host, revision);
+        assert_eq!(output.status_code, StatusCode::Success);
+        assert_eq!(i64::MAX - output.gas_left, 3 + balance_cost + 2);
+    }
+}
+
+#[test]
+fn ef_prefix_allowed_before_london() {
+    let (output, address) = deploy(
+        Bytecode::deploy(hex!("ef")),
+        &mut MockedHost::default(),
+        Revision::Berlin,
+    );
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(address, Some(create_address(Address::zero(), 0)));
+
+    // Only the first byte is checked.
+    let (output, _) = deploy(
+        Bytecode::deploy(hex!("00ef")),
+        &mut MockedHost::default(),
+        Revision::London,
+    );
+    assert_eq!(output.status_code, StatusCode::Success);
+}
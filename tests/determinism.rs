@@ -0,0 +1,173 @@
+use ethereum_types::{Address, U256};
+use evmodin::{
+    tracing::*,
+    util::{mocked_host::*, *},
+    *,
+};
+use proptest::prelude::*;
+
+/// Accounts are picked among a few low addresses, so that random code has a chance to hit them.
+fn address() -> impl Strategy<Value = Address> {
+    (0u64..4).prop_map(Address::from_low_u64_be)
+}
+
+fn account() -> impl Strategy<Value = Account> {
+    (
+        any::<u64>(),
+        prop::collection::vec(any::<u8>(), 0..16),
+        any::<u64>(),
+        any::<u64>(),
+        prop::collection::vec((0u64..4, any::<u64>()), 0..4),
+    )
+        .prop_map(|(nonce, code, code_hash, balance, storage)| Account {
+            nonce,
+            code: code.into(),
+            code_hash: code_hash.into(),
+            balance: balance.into(),
+            storage: storage
+                .into_iter()
+                .map(|(key, value)| {
+                    (
+                        key.into(),
+                        StorageValue {
+                            value: value.into(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+        })
+}
+
+fn host() -> impl Strategy<Value = MockedHost> {
+    (
+        prop::collection::vec((address(), account()), 0..4),
+        any::<u64>(),
+        prop::sample::select(vec![
+            StatusCode::Success,
+            StatusCode::Revert,
+            StatusCode::Failure,
+        ]),
+        0i64..1000,
+        prop::collection::vec(any::<u8>(), 0..40),
+        (any::<u64>(), any::<u64>(), address()),
+    )
+        .prop_map(
+            |(accounts, block_hash, status_code, gas_left, output_data, block)| {
+                let mut host = MockedHost::default();
+                host.accounts = accounts.into_iter().collect();
+                host.block_hash = block_hash.into();
+                host.call_result.status_code = status_code;
+                host.call_result.gas_left = gas_left;
+                host.call_result.output_data = output_data.into();
+                let (block_number, block_timestamp, block_coinbase) = block;
+                host.tx_context.block_number = block_number;
+                host.tx_context.block_timestamp = block_timestamp;
+                host.tx_context.block_coinbase = block_coinbase;
+                host
+            },
+        )
+}
+
+fn revision() -> impl Strategy<Value = Revision> {
+    prop::sample::select(Revision::iter().into_iter().collect::<Vec<_>>())
+}
+
+struct Run {
+    output: Output,
+    records: Records,
+    accounts: Vec<(Address, Account)>,
+    hits: Vec<bool>,
+    branches: Vec<BranchCoverage>,
+}
+
+fn run(
+    code: &AnalyzedCode,
+    mut host: MockedHost,
+    input: &[u8],
+    gas: i64,
+    revision: Revision,
+) -> Run {
+    let mut coverage = CoverageMap::default();
+    let output = code.execute(
+        &mut host,
+        &mut coverage,
+        None,
+        Message {
+            kind: CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas,
+            recipient: Address::zero(),
+            code_address: Address::zero(),
+            sender: Address::from_low_u64_be(1),
+            input_data: input.to_vec().into(),
+            value: U256::zero(),
+        },
+        revision,
+    );
+
+    let mut accounts = host.accounts.into_iter().collect::<Vec<_>>();
+    accounts.sort_by_key(|(address, _)| *address);
+
+    Run {
+        output,
+        records: host.recorded.lock().clone(),
+        accounts,
+        hits: (0..code.code().len())
+            .map(|pc| coverage.is_hit(pc))
+            .collect(),
+        branches: coverage.branch_coverage(code),
+    }
+}
+
+/// Execute code twice against independent clones of the same host and compare everything
+/// observable.
+fn check_deterministic(
+    code: &[u8],
+    host: &MockedHost,
+    input: &[u8],
+    gas: i64,
+    revision: Revision,
+) -> Result<(), TestCaseError> {
+    let code = AnalyzedCode::analyze(code.to_vec());
+    let a = run(&code, host.clone(), input, gas, revision);
+    let b = run(&code, host.clone(), input, gas, revision);
+
+    prop_assert_eq!(a.output, b.output);
+    prop_assert_eq!(a.records, b.records);
+    prop_assert_eq!(a.accounts, b.accounts);
+    prop_assert_eq!(a.hits, b.hits);
+    prop_assert_eq!(a.branches, b.branches);
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn execution_is_deterministic(
+        code in prop::collection::vec(any::<u8>(), 0..128),
+        host in host(),
+        input in prop::collection::vec(any::<u8>(), 0..40),
+        gas in 0i64..100_000,
+        revision in revision(),
+    ) {
+        check_deterministic(&code, &host, &input, gas, revision)?;
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20_000))]
+
+    #[test]
+    #[ignore]
+    fn execution_is_deterministic_stress(
+        code in prop::collection::vec(any::<u8>(), 0..1024),
+        host in host(),
+        input in prop::collection::vec(any::<u8>(), 0..256),
+        gas in 0i64..1_000_000,
+        revision in revision(),
+    ) {
+        check_deterministic(&code, &host, &input, gas, revision)?;
+    }
+}
@@ -0,0 +1,38 @@
+use evmodin::{opcode::*, util::*, *};
+
+fn t(disabled: &[OpCode]) -> EvmTester {
+    EvmTester::new().options(ExecutionOptions {
+        disabled_opcodes: disabled.iter().copied().collect(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn disabled_selfdestruct() {
+    let code = Bytecode::new().pushv(9).opcode(OpCode::SELFDESTRUCT);
+
+    t(&[OpCode::SELFDESTRUCT])
+        .code(code.clone())
+        .status(StatusCode::UndefinedInstruction)
+        .inspect_host(|host, _| assert!(host.recorded.lock().selfdestructs.is_empty()))
+        .check();
+
+    t(&[OpCode::CALL])
+        .code(code)
+        .status(StatusCode::Success)
+        .inspect_host(|host, _| assert_eq!(host.recorded.lock().selfdestructs.len(), 1))
+        .check();
+}
+
+#[test]
+fn requirements_are_checked_first() {
+    t(&[OpCode::ADD])
+        .code(Bytecode::new().opcode(OpCode::ADD))
+        .status(StatusCode::StackUnderflow)
+        .check();
+
+    t(&[OpCode::ADD])
+        .code(Bytecode::new().pushv(1).pushv(2).opcode(OpCode::ADD))
+        .status(StatusCode::UndefinedInstruction)
+        .check();
+}
@@ -0,0 +1,87 @@
+use ethereum_types::U256;
+use evmodin::{opcode::*, util::*, *};
+
+fn tstore(key: u64, value: u64) -> Bytecode {
+    Bytecode::new()
+        .pushv(value)
+        .pushv(key)
+        .opcode(OpCode::TSTORE)
+}
+
+fn tload(key: u64) -> Bytecode {
+    Bytecode::new().pushv(key).opcode(OpCode::TLOAD)
+}
+
+#[test]
+fn transient_storage_pre_cancun() {
+    for op in [OpCode::TLOAD, OpCode::TSTORE] {
+        EvmTester::new()
+            .revision(Revision::Shanghai)
+            .code(Bytecode::new().pushv(0).pushv(0).opcode(op))
+            .status(StatusCode::UndefinedInstruction)
+            .check()
+    }
+}
+
+#[test]
+fn write_then_read() {
+    EvmTester::new()
+        .revision(Revision::Cancun)
+        .code(tstore(1, 0xab).append_bc(tload(1)).ret_top())
+        .status(StatusCode::Success)
+        .gas_used(3 + 3 + 100 + 3 + 100 + 15)
+        .output_value(0xab)
+        .inspect_host(|host, msg| {
+            assert_eq!(
+                host.transient_storage[&(msg.recipient, U256::one())],
+                0xab.into()
+            );
+            // Persistent storage is untouched.
+            assert!(host
+                .accounts
+                .get(&msg.recipient)
+                .map_or(true, |account| account.storage.is_empty()));
+        })
+        .check();
+}
+
+#[test]
+fn unset_slot_is_zero() {
+    EvmTester::new()
+        .revision(Revision::Cancun)
+        .code(tstore(1, 0xab).append_bc(tload(2)).ret_top())
+        .status(StatusCode::Success)
+        .output_value(0)
+        .check();
+}
+
+#[test]
+fn tstore_static_mode_violation() {
+    let t = EvmTester::new().revision(Revision::Cancun).set_static(true);
+
+    t.clone()
+        .code(tstore(1, 1))
+        .status(StatusCode::StaticModeViolation)
+        .check();
+
+    t.code(tload(1).ret_top())
+        .status(StatusCode::Success)
+        .output_value(0)
+        .check();
+}
+
+#[test]
+fn cleared_between_transactions() {
+    let mut host = mocked_host::MockedHost::default();
+    host.set_transient_storage(Default::default(), 1.into(), 2.into());
+    assert_eq!(
+        host.get_transient_storage(Default::default(), 1.into()),
+        2.into()
+    );
+
+    host.clear_transient_storage();
+    assert_eq!(
+        host.get_transient_storage(Default::default(), 1.into()),
+        U256::zero()
+    );
+}
@@ -1,3 +1,4 @@
+use ethereum_types::{Address, H256};
 use evmodin::{host::*, opcode::*, util::*, *};
 use hex_literal::hex;
 
@@ -153,6 +154,56 @@ fn eip2929_extcodecopy_oog() {
         .check();
 }
 
+#[test]
+fn eip2929_extcodecopy_warm_cold() {
+    // The warm access cost from the table is the base, the cold one is charged on top of it.
+    let table = get_baseline_instruction_table(Revision::Berlin);
+    assert_eq!(table[OpCode::EXTCODECOPY.to_usize()].unwrap().gas_cost, 100);
+
+    let extcodecopy = |size: u64| {
+        Bytecode::new()
+            .pushv(size)
+            .pushv(0)
+            .pushv(0)
+            .pushv(0xaa)
+            .opcode(OpCode::EXTCODECOPY)
+    };
+    // Cold BALANCE makes the account warm.
+    let warm_up = Bytecode::new()
+        .pushv(0xaa)
+        .opcode(OpCode::BALANCE)
+        .opcode(OpCode::POP);
+    let warm_up_gas = 3 + 2600 + 2;
+
+    for size in [0, 1, 32, 64] {
+        let words = size.div_ceil(32) as i64;
+        // 4 pushes, warm access, memory expansion and copying.
+        let warm_gas = 4 * 3 + 100 + 3 * words + 3 * words;
+
+        EvmTester::new()
+            .revision(Revision::Berlin)
+            .code(extcodecopy(size))
+            .status(StatusCode::Success)
+            .gas_used(warm_gas + 2500)
+            .check();
+
+        EvmTester::new()
+            .revision(Revision::Berlin)
+            .code(warm_up.clone() + extcodecopy(size))
+            .status(StatusCode::Success)
+            .gas_used(warm_up_gas + warm_gas)
+            .check();
+
+        // The second copy of the same account is warm.
+        EvmTester::new()
+            .revision(Revision::Berlin)
+            .code(extcodecopy(size) + extcodecopy(size))
+            .status(StatusCode::Success)
+            .gas_used(warm_gas + 2500 + (warm_gas - 3 * words))
+            .check();
+    }
+}
+
 #[test]
 fn eip2929_sload_cold() {
     let key = 1.into();
@@ -378,3 +429,77 @@ fn eip2929_delegatecall_cold() {
         })
         .check();
 }
+
+#[test]
+fn derive_access_list_from_recording() {
+    let sender = Address::from_low_u64_be(0x5e);
+    let contract = Address::from_low_u64_be(0xc0);
+    let external = Address::from_low_u64_be(0xee);
+
+    let mut slot = H256::zero();
+    slot.0[31] = 5;
+
+    EvmTester::new()
+        .revision(Revision::Berlin)
+        .sender(sender)
+        .destination(contract)
+        .code(
+            Bytecode::new()
+                .pushv(0xee)
+                .opcode(OpCode::BALANCE)
+                .pushv(5)
+                .opcode(OpCode::SLOAD)
+                .pushv(1)
+                .opcode(OpCode::BALANCE),
+        )
+        .status(StatusCode::Success)
+        .inspect_host(move |host, _| {
+            assert_eq!(
+                derive_access_list(host),
+                [(sender, vec![]), (contract, vec![slot]), (external, vec![]),]
+            );
+        })
+        .check();
+}
+
+#[test]
+fn access_counts() {
+    let code = Bytecode::new()
+        .sload(1)
+        .opcode(OpCode::POP)
+        .sload(2)
+        .opcode(OpCode::POP)
+        .sload(1)
+        .opcode(OpCode::POP)
+        .sload(2)
+        .opcode(OpCode::POP);
+    let t = EvmTester::new().revision(Revision::Berlin).code(code);
+
+    let cold = t
+        .clone()
+        .status(StatusCode::Success)
+        .accesses(AccessCounts {
+            cold_storage_accesses: 2,
+            warm_storage_accesses: 2,
+            cold_surcharge_gas_total: 2 * 2000,
+            ..Default::default()
+        })
+        .check_and_get_result();
+
+    // Same program with both slots in the access list.
+    let warm = t
+        .apply_host_fn(|host, msg| {
+            let storage = &mut host.accounts.entry(msg.recipient).or_default().storage;
+            for key in [1_u64, 2] {
+                storage.entry(key.into()).or_default().access_status = AccessStatus::Warm;
+            }
+        })
+        .status(StatusCode::Success)
+        .accesses(AccessCounts {
+            warm_storage_accesses: 4,
+            ..Default::default()
+        })
+        .check_and_get_result();
+
+    assert_eq!(warm.gas_left - cold.gas_left, 2 * 2000);
+}
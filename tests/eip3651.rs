@@ -0,0 +1,44 @@
+use ethereum_types::Address;
+use evmodin::{opcode::*, util::*, *};
+use hex_literal::hex;
+
+const COINBASE: [u8; 20] = hex!("00000000000000000000000000000000000000c0");
+
+fn balance_of(address: Bytecode, revision: Revision, gas_used: i64) {
+    EvmTester::new()
+        .revision(revision)
+        .apply_host_fn(|host, _| {
+            host.tx_context.block_coinbase = Address::from(COINBASE);
+        })
+        .code(address.opcode(OpCode::BALANCE))
+        .status(StatusCode::Success)
+        .gas_used(gas_used)
+        .check();
+}
+
+#[test]
+fn coinbase_cold_before_shanghai() {
+    balance_of(
+        Bytecode::new().opcode(OpCode::COINBASE),
+        Revision::London,
+        2 + 2600,
+    );
+}
+
+#[test]
+fn coinbase_warm_in_shanghai() {
+    balance_of(
+        Bytecode::new().opcode(OpCode::COINBASE),
+        Revision::Shanghai,
+        2 + 100,
+    );
+}
+
+#[test]
+fn other_account_cold_in_shanghai() {
+    balance_of(
+        Bytecode::new().pushb(hex!("00000000000000000000000000000000000000c1")),
+        Revision::Shanghai,
+        3 + 2600,
+    );
+}
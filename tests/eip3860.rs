@@ -0,0 +1,76 @@
+use evmodin::{opcode::*, util::*, *};
+
+const GAS: i64 = 1_000_000;
+
+/// CREATE or CREATE2 with zero salt of `size` bytes of memory at offset 0.
+fn create(op: OpCode, size: usize) -> Bytecode {
+    let code = if op == OpCode::CREATE2 {
+        Bytecode::new().pushv(0)
+    } else {
+        Bytecode::new()
+    };
+    code.pushv(size).pushv(0).pushv(0).opcode(op)
+}
+
+/// Gas forwarded to init code by `create(op, 64)`.
+fn forwarded_gas(op: OpCode, revision: Revision) -> i64 {
+    let mut cost = 3 * 3 + 2 * 3 + 32000;
+    if op == OpCode::CREATE2 {
+        cost += 3 + 2 * 6;
+    }
+    if revision >= Revision::Shanghai {
+        cost += 2 * 2;
+    }
+    let gas_left = GAS - cost;
+    gas_left - gas_left / 64
+}
+
+#[test]
+fn initcode_word_cost() {
+    for op in [OpCode::CREATE, OpCode::CREATE2] {
+        for revision in [Revision::London, Revision::Shanghai] {
+            let expected = forwarded_gas(op, revision);
+            EvmTester::new()
+                .revision(revision)
+                .gas(GAS)
+                .code(create(op, 64))
+                .status(StatusCode::Success)
+                .inspect_host(move |host, _| {
+                    let r = host.recorded.lock();
+                    assert_eq!(r.calls.len(), 1);
+                    assert_eq!(r.calls[0].gas, expected, "{} at {}", op, revision);
+                })
+                .check();
+        }
+    }
+}
+
+#[test]
+fn initcode_size_limit() {
+    for op in [OpCode::CREATE, OpCode::CREATE2] {
+        EvmTester::new()
+            .revision(Revision::Shanghai)
+            .gas(GAS)
+            .code(create(op, MAX_INITCODE_SIZE))
+            .status(StatusCode::Success)
+            .inspect_host(|host, _| assert_eq!(host.recorded.lock().calls.len(), 1))
+            .check();
+
+        EvmTester::new()
+            .revision(Revision::Shanghai)
+            .gas(GAS)
+            .code(create(op, MAX_INITCODE_SIZE + 1))
+            .status(StatusCode::OutOfGas)
+            .inspect_host(|host, _| assert!(host.recorded.lock().calls.is_empty()))
+            .check();
+
+        // No limit before Shanghai.
+        EvmTester::new()
+            .revision(Revision::London)
+            .gas(GAS)
+            .code(create(op, MAX_INITCODE_SIZE + 1))
+            .status(StatusCode::Success)
+            .inspect_host(|host, _| assert_eq!(host.recorded.lock().calls.len(), 1))
+            .check();
+    }
+}
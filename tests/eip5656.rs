@@ -0,0 +1,85 @@
+use ethereum_types::U256;
+use evmodin::{opcode::*, util::*, *};
+
+/// Bytes 0 to 63.
+fn image() -> Vec<u8> {
+    (0..64).collect()
+}
+
+#[test]
+fn mcopy_pre_cancun() {
+    EvmTester::new()
+        .revision(Revision::Shanghai)
+        .code(Bytecode::new().mcopy(0, 0, 0))
+        .status(StatusCode::UndefinedInstruction)
+        .check()
+}
+
+#[test]
+fn mcopy_expands_memory() {
+    // Three pushes, MCOPY, one word copied and two words of memory.
+    EvmTester::new()
+        .revision(Revision::Cancun)
+        .code(Bytecode::new().mcopy(32, 0, 32))
+        .status(StatusCode::Success)
+        .gas_used(3 * 3 + 3 + 3 + 2 * 3)
+        .check()
+}
+
+#[test]
+fn mcopy_zero_length() {
+    // Offsets are not checked and memory is not expanded.
+    EvmTester::new()
+        .revision(Revision::Cancun)
+        .code(
+            Bytecode::new()
+                .mcopy(U256::MAX, U256::MAX, 0)
+                .opcode(OpCode::MSIZE)
+                .ret_top(),
+        )
+        .status(StatusCode::Success)
+        .output_value(0)
+        .check()
+}
+
+#[test]
+fn mcopy_overlapping_forward() {
+    let mut expected = image();
+    expected.copy_within(0..32, 1);
+    assert_eq!(expected[..4], [0, 0, 1, 2]);
+
+    EvmTester::new()
+        .revision(Revision::Cancun)
+        .seed_memory(image())
+        .code(Bytecode::new().mcopy(1, 0, 32).ret(0, 64))
+        .status(StatusCode::Success)
+        .output_data(expected)
+        .check()
+}
+
+#[test]
+fn mcopy_overlapping_backward() {
+    let mut expected = image();
+    expected.copy_within(1..33, 0);
+    assert_eq!(expected[30..34], [31, 32, 32, 33]);
+
+    EvmTester::new()
+        .revision(Revision::Cancun)
+        .seed_memory(image())
+        .code(Bytecode::new().mcopy(0, 1, 32).ret(0, 64))
+        .status(StatusCode::Success)
+        .output_data(expected)
+        .check()
+}
+
+#[test]
+fn mcopy_source_beyond_memory() {
+    // The source region is expanded with zeros, which overwrite the destination.
+    EvmTester::new()
+        .revision(Revision::Cancun)
+        .seed_memory(vec![0xff; 32])
+        .code(Bytecode::new().mcopy(0, 32, 32).ret(0, 64))
+        .status(StatusCode::Success)
+        .output_data(vec![0; 64])
+        .check()
+}
@@ -0,0 +1,29 @@
+//! Run with `--features evmc`.
+#![cfg(feature = "evmc")]
+
+use ethereum_types::{Address, U256};
+use evmc_vm::ExecutionResult;
+use evmodin::{util::*, *};
+
+#[test]
+fn storage_clear_refund() {
+    let key = U256::one();
+
+    let output = EvmTester::new()
+        .revision(Revision::London)
+        .code(Bytecode::new().sstore(key, 0))
+        .apply_host_fn(move |host, _| {
+            host.accounts
+                .entry(Address::zero())
+                .or_default()
+                .storage
+                .entry(key)
+                .or_default()
+                .value = 1.into();
+        })
+        .status(StatusCode::Success)
+        .check_and_get_result();
+
+    let result = ExecutionResult::from(output);
+    assert_eq!(result.gas_refund(), 4800);
+}
@@ -182,6 +182,34 @@ fn gas() {
         .check()
 }
 
+#[test]
+fn gas_net_of_own_cost_and_memory_expansion() {
+    for revision in [Revision::Frontier, Revision::Berlin, Revision::London] {
+        // GAS alone: 1000 - 2.
+        EvmTester::new()
+            .revision(revision)
+            .code(Bytecode::new().opcode(OpCode::GAS).ret_top())
+            .gas(1000)
+            .status(StatusCode::Success)
+            .output_value(998)
+            .check();
+
+        // MSTORE expanding memory to 3 words: 1000 - (3 + 3 + 3 + 3 * 3) - 2.
+        EvmTester::new()
+            .revision(revision)
+            .code(
+                Bytecode::new()
+                    .mstore_value(0x40, 1)
+                    .opcode(OpCode::GAS)
+                    .ret_top(),
+            )
+            .gas(1000)
+            .status(StatusCode::Success)
+            .output_value(980)
+            .check();
+    }
+}
+
 #[test]
 fn arith() {
     // x = (0 - 1) * 3
@@ -816,6 +844,27 @@ fn calldatacopy() {
         .check();
 }
 
+#[test]
+fn calldatacopy_builder() {
+    EvmTester::new()
+        .code(Bytecode::new().calldatacopy(0, 1, 10).ret(0, 10))
+        .input(&hex!("0102030405") as &[u8])
+        .status(StatusCode::Success)
+        .gas_used(24)
+        .output_data(hex!("02030405000000000000"))
+        .check();
+}
+
+#[test]
+fn gas_used_range() {
+    EvmTester::new()
+        .code(Bytecode::new().calldatacopy(0, 1, 10).ret(0, 10))
+        .input(&hex!("0102030405") as &[u8])
+        .status(StatusCode::Success)
+        .gas_used_between(20, 30)
+        .check();
+}
+
 #[test]
 fn address() {
     EvmTester::new()
@@ -980,6 +1029,29 @@ fn revert() {
         .check()
 }
 
+#[test]
+fn revert_introduced_in_byzantium() {
+    for rev in Revision::iter() {
+        let t = EvmTester::new().revision(rev).gas(100).code(
+            Bytecode::new()
+                .mstore8_value(0, 0xee)
+                .pushv(1)
+                .pushv(0)
+                .opcode(OpCode::REVERT),
+        );
+        if rev >= Revision::Byzantium {
+            t.status(StatusCode::Revert).output_data([0xee]).check();
+        } else {
+            // The failure happens before any REVERT logic, so nothing is returned.
+            let output = t
+                .status(StatusCode::UndefinedInstruction)
+                .output_data([])
+                .check_and_get_result();
+            assert_eq!(output.stats.failure_site.unwrap().pc, 9);
+        }
+    }
+}
+
 #[test]
 fn return_empty_buffer_at_offset_0() {
     EvmTester::new()
@@ -1205,6 +1277,60 @@ fn keccak256_memory_cost() {
     }
 }
 
+#[test]
+fn keccak256_word_cost_boundary() {
+    for words in [1, 2, 3, 33] {
+        let memory_cost = 3 * words + words * words / 512;
+        assert_gas_boundary!(
+            EvmTester::new().code(
+                Bytecode::new()
+                    .pushv(words * 32)
+                    .pushv(0)
+                    .opcode(OpCode::KECCAK256)
+            ),
+            3 + 3 + 30 + 6 * words + memory_cost
+        );
+    }
+}
+
+#[test]
+fn keccak256_memory_already_expanded() {
+    let keccak = Bytecode::new().pushv(64).pushv(0).opcode(OpCode::KECCAK256);
+
+    // PUSH1 PUSH1 KECCAK256 + two words hashed + expansion to two words.
+    let output = EvmTester::new()
+        .code(keccak.clone())
+        .status(StatusCode::Success)
+        .gas_used(3 + 3 + 30 + 2 * 6 + 2 * 3)
+        .check_and_get_result();
+    assert_eq!(output.stats.max_memory_bytes, 64);
+
+    // MSTORE(32, 0) pays for the expansion, so KECCAK256 only pays for hashing.
+    let output = EvmTester::new()
+        .code(Bytecode::new().mstore_value(32, 0) + keccak)
+        .status(StatusCode::Success)
+        .gas_used((3 + 3 + 3 + 2 * 3) + (3 + 3 + 30 + 2 * 6))
+        .check_and_get_result();
+    assert_eq!(output.stats.max_memory_bytes, 64);
+}
+
+#[test]
+fn keccak256_empty_region() {
+    for offset in [0, 1000] {
+        let output = EvmTester::new()
+            .code(
+                Bytecode::new()
+                    .pushv(0)
+                    .pushv(offset)
+                    .opcode(OpCode::KECCAK256),
+            )
+            .status(StatusCode::Success)
+            .gas_used(3 + 3 + 30)
+            .check_and_get_result();
+        assert_eq!(output.stats.max_memory_bytes, 0);
+    }
+}
+
 #[test]
 fn calldatacopy_memory_cost() {
     for (gas, status) in [(18, StatusCode::Success), (17, StatusCode::OutOfGas)] {
@@ -1446,3 +1572,64 @@ fn memory_access() {
         }
     }
 }
+
+#[test]
+fn labels_and_fixed_width_pushes() {
+    // Sum of 1..=5 with a loop addressed by labels.
+    fn sum(prefix: Bytecode, width: Option<usize>) -> Bytecode {
+        let push = |code: Bytecode, v: u64| match width {
+            Some(width) => code.pushv_width(v, width),
+            None => code.pushv(v),
+        };
+
+        let code = push(prefix, 0);
+        let code = push(code, 5)
+            .label("loop")
+            .opcode(OpCode::DUP1)
+            .opcode(OpCode::ISZERO)
+            .jumpi_to("end")
+            .opcode(OpCode::DUP1)
+            .opcode(OpCode::SWAP2)
+            .opcode(OpCode::ADD)
+            .opcode(OpCode::SWAP1);
+        push(code, 1)
+            .opcode(OpCode::SWAP1)
+            .opcode(OpCode::SUB)
+            .jump_to("loop")
+            .label("end")
+            .opcode(OpCode::POP)
+            .ret_top()
+    }
+
+    for prefix in [Bytecode::new(), 300 * OpCode::JUMPDEST] {
+        for width in [None, Some(1), Some(32)] {
+            EvmTester::new()
+                .code(sum(prefix.clone(), width))
+                .status(StatusCode::Success)
+                .output_value(15)
+                .check();
+        }
+    }
+}
+
+#[test]
+fn seeded_memory() {
+    let image = (1..=0x21).collect::<Vec<u8>>();
+
+    // Seeded memory is already paid for.
+    EvmTester::new()
+        .seed_memory(image.clone())
+        .code(Bytecode::new().pushv(1).opcode(OpCode::MLOAD).ret_top())
+        .status(StatusCode::Success)
+        .gas_used(3 + 3 + 3 + 3 + 3 + 3)
+        .output_data(image[1..].to_vec())
+        .check();
+
+    // It is padded to whole words.
+    EvmTester::new()
+        .seed_memory(image)
+        .code(Bytecode::new().opcode(OpCode::MSIZE).ret_top())
+        .status(StatusCode::Success)
+        .output_value(0x40)
+        .check();
+}
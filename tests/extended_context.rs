@@ -0,0 +1,136 @@
+use evmodin::{opcode::*, util::*, *};
+
+/// Unassigned in every revision.
+const OPCODE: OpCode = OpCode(0x0c);
+
+fn t() -> EvmTester {
+    EvmTester::new()
+        .options(ExecutionOptions {
+            extended_context_opcode: Some(OPCODE),
+            ..Default::default()
+        })
+        .extended_context(7, 0x1234)
+}
+
+#[test]
+fn returns_host_value() {
+    t().code(Bytecode::new().pushv(7).opcode(OPCODE).ret_top())
+        .status(StatusCode::Success)
+        .gas_used(3 + 2 + 3 + 3 + 3 + 3 + 3)
+        .output_value(0x1234)
+        .check();
+}
+
+#[test]
+fn unsupported_key() {
+    t().code(Bytecode::new().pushv(8).opcode(OPCODE))
+        .status(StatusCode::InternalError(
+            "extended context key 8 is not set".into(),
+        ))
+        .check();
+}
+
+#[test]
+fn key_out_of_range() {
+    t().code(Bytecode::new().pushv(1_u64 << 32).opcode(OPCODE))
+        .status(StatusCode::InternalError(
+            "extended context key 4294967296 is out of range".into(),
+        ))
+        .check();
+}
+
+#[test]
+fn stack_underflow() {
+    t().code(Bytecode::new().opcode(OPCODE))
+        .status(StatusCode::StackUnderflow)
+        .check();
+}
+
+#[test]
+fn disabled_extended_context_opcode() {
+    t().options(ExecutionOptions {
+        extended_context_opcode: Some(OPCODE),
+        disabled_opcodes: [OPCODE].into_iter().collect(),
+        ..Default::default()
+    })
+    .code(Bytecode::new().pushv(7).opcode(OPCODE).ret_top())
+    .status(StatusCode::UndefinedInstruction)
+    .check();
+}
+
+#[test]
+fn not_enabled_by_default() {
+    EvmTester::new()
+        .code(Bytecode::new().pushv(7).opcode(OPCODE))
+        .status(StatusCode::UndefinedInstruction)
+        .check();
+}
+
+/// Execute PUSH1 7 and the extended context opcode with `host`.
+fn run(host: &mut impl Host) -> Output {
+    let options = ExecutionOptions {
+        extended_context_opcode: Some(OPCODE),
+        ..Default::default()
+    };
+    AnalyzedCode::analyze(Bytecode::new().pushv(7).opcode(OPCODE).build())
+        .execute_resumable_with_options(
+            false,
+            Message {
+                kind: CallKind::Call,
+                is_static: false,
+                depth: 0,
+                gas: 1000,
+                recipient: Default::default(),
+                sender: Default::default(),
+                input_data: Default::default(),
+                value: Default::default(),
+                code_address: Default::default(),
+            },
+            Revision::latest(),
+            &options,
+        )
+        .run_to_completion_with_options(host, &mut tracing::NoopTracer, None, &options)
+}
+
+#[test]
+fn default_host_supports_no_keys() {
+    assert_eq!(
+        run(&mut host::DummyHost).status_code,
+        StatusCode::InternalError("extended context key 7 is not supported".into())
+    );
+}
+
+/// Host failing extended context queries with `error`.
+fn failing_host(
+    error: fn() -> anyhow::Error,
+) -> DelegatingHost<host::DummyHost, fn() -> anyhow::Error> {
+    DelegatingHost::new(host::DummyHost, error).on_extended_context(|host, _| Err((host.state)()))
+}
+
+#[test]
+fn host_status_is_preserved() {
+    let mut host = failing_host(|| StatusCode::InsufficientBalance.into());
+    assert_eq!(run(&mut host).status_code, StatusCode::InsufficientBalance);
+}
+
+#[test]
+fn host_error_message_is_preserved() {
+    let mut host = failing_host(|| anyhow::anyhow!("node unavailable"));
+    assert_eq!(
+        run(&mut host).status_code,
+        StatusCode::InternalError("node unavailable".into())
+    );
+}
+
+#[test]
+fn status_code_round_trips_through_anyhow() {
+    let error = anyhow::Error::from(StatusCode::StaticModeViolation);
+    assert_eq!(
+        error.downcast_ref::<StatusCode>(),
+        Some(&StatusCode::StaticModeViolation)
+    );
+    assert_eq!(
+        StatusCode::from_host_error(error),
+        StatusCode::StaticModeViolation
+    );
+}
@@ -0,0 +1,91 @@
+use evmodin::{opcode::*, util::*, *};
+use hex_literal::hex;
+
+fn preserving() -> ExecutionOptions {
+    ExecutionOptions {
+        preserve_gas_on_failure: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn invalid_after_prefix() {
+    // PUSH1 1 PUSH1 2 ADD INVALID
+    let t = EvmTester::new().code(hex!("6001600201fe")).gas(100);
+
+    let output = t
+        .clone()
+        .status(StatusCode::InvalidInstruction)
+        .gas_used(100)
+        .check_and_get_result();
+    assert_eq!(
+        output.stats.failure_site,
+        Some(FailureSite {
+            pc: 5,
+            gas_left: 91
+        })
+    );
+
+    t.options(preserving())
+        .status(StatusCode::InvalidInstruction)
+        .gas_used(9)
+        .check();
+}
+
+#[test]
+fn failure_sites() {
+    for (code, status, pc, gas_left) in [
+        // ADD
+        (hex!("01").to_vec(), StatusCode::StackUnderflow, 0, 100),
+        // PUSH1 0 JUMP
+        (
+            hex!("600056").to_vec(),
+            StatusCode::BadJumpDestination,
+            2,
+            97,
+        ),
+        // PUSH1 0 0x0c
+        (
+            hex!("60000c").to_vec(),
+            StatusCode::UndefinedInstruction,
+            2,
+            97,
+        ),
+    ] {
+        let output = EvmTester::new()
+            .code(code)
+            .gas(100)
+            .options(preserving())
+            .status(status)
+            .gas_left(gas_left)
+            .check_and_get_result();
+        assert_eq!(
+            output.stats.failure_site,
+            Some(FailureSite { pc, gas_left })
+        );
+    }
+
+    let mut code = Bytecode::new();
+    for _ in 0..Stack::limit() {
+        code = code.opcode(OpCode::PC);
+    }
+    EvmTester::new()
+        .code(code.opcode(OpCode::PC))
+        .gas(10000)
+        .options(preserving())
+        .status(StatusCode::StackOverflow)
+        .gas_left(10000 - 2 * Stack::limit() as i64)
+        .check();
+}
+
+#[test]
+fn out_of_gas_is_not_reported() {
+    let output = EvmTester::new()
+        .code(Bytecode::new().pushv(1).pushv(2).opcode(OpCode::ADD))
+        .gas(8)
+        .options(preserving())
+        .status(StatusCode::OutOfGas)
+        .gas_left(0)
+        .check_and_get_result();
+    assert_eq!(output.stats.failure_site, None);
+}
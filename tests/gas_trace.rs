@@ -0,0 +1,108 @@
+//! Run with `--features gas-trace`. The interpreter then also checks every execution of the
+//! other tests against its gas trace.
+#![cfg(feature = "gas-trace")]
+
+use evmodin::{
+    util::{mocked_host::StorageValue, *},
+    *,
+};
+use hex_literal::hex;
+use std::collections::HashMap;
+
+fn subtotals(output: &Output) -> HashMap<GasReason, i64> {
+    let mut subtotals = HashMap::new();
+    for charge in &output.stats.gas_charges {
+        *subtotals.entry(charge.reason).or_default() += charge.amount;
+    }
+    subtotals
+}
+
+#[test]
+fn delegatecall() {
+    let output = EvmTester::new()
+        .code(
+            Bytecode::new()
+                .append(hex!("6001600003600052")) // m[0] = 0xffffff...
+                .append(hex!("600560046003600260016103e8f4")) // DELEGATECALL(1000, 0x01, ...)
+                .append(hex!("60086000f3")),
+        )
+        .apply_host_fn(|host, _| {
+            host.call_result.output_data = (&hex!("0a0b0c") as &[u8]).into();
+            host.call_result.gas_left = 1;
+        })
+        .gas(1700)
+        .gas_used(1690)
+        .status(StatusCode::Success)
+        .check_and_get_result();
+
+    let gas_left = 1700 - 736;
+    let forwarded = gas_left - gas_left / 64;
+    assert_eq!(
+        subtotals(&output),
+        HashMap::from([
+            // 5 instructions up to MSTORE, 6 pushes, DELEGATECALL and 2 pushes.
+            (GasReason::TableBase, 5 * 3 + 6 * 3 + 700 + 2 * 3),
+            (GasReason::MemoryExpansion, 3),
+            (GasReason::CallForwarded, forwarded - 1),
+        ])
+    );
+
+    let call = output
+        .stats
+        .gas_charges
+        .iter()
+        .find(|charge| charge.reason == GasReason::CallForwarded)
+        .unwrap();
+    assert_eq!((call.pc, call.opcode), (21, OpCode::DELEGATECALL));
+}
+
+#[test]
+fn log_and_sstore() {
+    // LOG2(0, 1, 0xbb, 0xaa) SSTORE(1, 0)
+    let output = EvmTester::new()
+        .revision(Revision::London)
+        .code(hex!("60aa60bb60016000a2600060015500"))
+        .apply_host_fn(|host, msg| {
+            host.accounts
+                .entry(msg.recipient)
+                .or_default()
+                .storage
+                .insert(
+                    1.into(),
+                    StorageValue {
+                        value: 1.into(),
+                        ..Default::default()
+                    },
+                );
+        })
+        .status(StatusCode::Success)
+        .check_and_get_result();
+
+    assert_eq!(
+        subtotals(&output),
+        HashMap::from([
+            (GasReason::TableBase, 6 * 3 + 375),
+            (GasReason::MemoryExpansion, 3),
+            (GasReason::LogData, 8),
+            (GasReason::LogTopics, 2 * 375),
+            (GasReason::ColdSload, 2100),
+            (GasReason::SstoreDynamic, 5000 - 2100),
+            (
+                GasReason::Refund {
+                    source: RefundSource::Sstore
+                },
+                4800
+            ),
+        ])
+    );
+    assert_eq!(output.gas_refund, 4800);
+    assert_eq!(
+        output.stats.gas_charges[0],
+        GasCharge {
+            pc: 0,
+            opcode: OpCode::PUSH1,
+            amount: 3,
+            reason: GasReason::TableBase,
+        }
+    );
+}
@@ -0,0 +1,108 @@
+use ethereum_types::Address;
+use evmodin::{opcode::*, util::*, *};
+
+fn host_budget(max_interrupts: u64, max_bytes_returned: u64) -> ExecutionOptions {
+    ExecutionOptions {
+        host_budget: Some(HostBudget {
+            max_interrupts,
+            max_bytes_returned,
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn interrupt_budget() {
+    let output = EvmTester::new()
+        .options(host_budget(100, u64::MAX))
+        .code(1000 * Bytecode::new().sload(0).opcode(OpCode::POP))
+        .status(StatusCode::InternalError("host budget exceeded".into()))
+        .inspect_host(|host, _| {
+            assert_eq!(host.recorded.lock().account_accesses.len(), 100);
+        })
+        .check_and_get_result();
+    assert_eq!(
+        output.stats.host_budget_exceeded,
+        Some(HostBudgetExceeded::Interrupts)
+    );
+}
+
+#[test]
+fn bytes_returned_budget() {
+    let blob = Address::from_low_u64_be(0xb10b);
+
+    let t = EvmTester::new()
+        .apply_host_fn(move |host, _| {
+            host.accounts.entry(blob).or_default().code = vec![0xfe; 0x2000].into();
+        })
+        .code(
+            Bytecode::new()
+                .pushv(0x2000)
+                .pushv(0)
+                .pushv(0)
+                .pushv(0xb10b)
+                .opcode(OpCode::EXTCODECOPY),
+        );
+
+    let output = t
+        .clone()
+        .options(host_budget(u64::MAX, 0x1000))
+        .status(StatusCode::InternalError("host budget exceeded".into()))
+        .check_and_get_result();
+    assert_eq!(
+        output.stats.host_budget_exceeded,
+        Some(HostBudgetExceeded::BytesReturned)
+    );
+
+    t.options(host_budget(u64::MAX, 0x2000))
+        .status(StatusCode::Success)
+        .check();
+}
+
+#[test]
+fn call_input_size_limit() {
+    let t = EvmTester::new()
+        .options(ExecutionOptions {
+            max_call_input_size: Some(64),
+            ..Default::default()
+        })
+        .gas(1_000_000);
+
+    t.clone()
+        .code(Bytecode::new() + CallInstruction::call(0xaa).input(0, 64))
+        .status(StatusCode::Success)
+        .inspect_host(|host, _| {
+            assert_eq!(host.recorded.lock().calls.len(), 1);
+        })
+        .check();
+
+    // Only the call fails: the caller goes on and returns the call status.
+    t.clone()
+        .code(
+            Bytecode::new()
+                .append_bc(CallInstruction::call(0xaa).input(0, 65))
+                .ret_top(),
+        )
+        .status(StatusCode::Success)
+        .output_value(0)
+        .inspect_host(|host, _| {
+            assert!(host.recorded.lock().calls.is_empty());
+        })
+        .check();
+
+    // Init code is limited the same way, CREATE pushes zero.
+    t.code(
+        Bytecode::new()
+            .pushv(65)
+            .pushv(0)
+            .pushv(0)
+            .opcode(OpCode::CREATE)
+            .ret_top(),
+    )
+    .status(StatusCode::Success)
+    .output_value(0)
+    .inspect_host(|host, _| {
+        assert!(host.recorded.lock().calls.is_empty());
+    })
+    .check();
+}
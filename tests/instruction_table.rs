@@ -0,0 +1,204 @@
+use evmodin::{opcode::*, *};
+
+#[test]
+fn baseline_instruction_table() {
+    let frontier = get_baseline_instruction_table(Revision::Frontier);
+    let istanbul: &InstructionTable = get_baseline_instruction_table(Revision::Istanbul);
+
+    let add: InstructionTableEntry = istanbul[OpCode::ADD.to_usize()].unwrap();
+    assert_eq!(add.gas_cost, 3);
+    assert_eq!(add.stack_height_required, 2);
+    assert!(!add.can_overflow_stack);
+
+    let push1 = istanbul[OpCode::PUSH1.to_usize()].unwrap();
+    assert!(push1.can_overflow_stack);
+
+    assert!(frontier[OpCode::CHAINID.to_usize()].is_none());
+    assert_eq!(istanbul[OpCode::CHAINID.to_usize()].unwrap().gas_cost, 2);
+}
+
+#[test]
+fn instruction_table_per_revision() {
+    let revisions = Revision::iter().into_iter().collect::<Vec<_>>();
+    assert_eq!(revisions.len(), Revision::len());
+    assert!(revisions.windows(2).all(|w| w[0] < w[1]));
+
+    // Every revision has a table of its own, even if contents are the same.
+    for (i, a) in revisions.iter().enumerate() {
+        for b in &revisions[i + 1..] {
+            assert!(!std::ptr::eq(
+                get_baseline_instruction_table(*a),
+                get_baseline_instruction_table(*b)
+            ));
+        }
+    }
+
+    // Paris does not change instruction costs, Shanghai only adds PUSH0.
+    let gas_costs = |revision| {
+        get_baseline_instruction_table(revision)
+            .iter()
+            .map(|entry| entry.map(|entry| entry.gas_cost))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(gas_costs(Revision::Paris), gas_costs(Revision::London));
+    let mut shanghai = gas_costs(Revision::Shanghai);
+    assert_eq!(shanghai[OpCode::PUSH0.to_usize()].take(), Some(2));
+    assert_eq!(shanghai, gas_costs(Revision::Paris));
+    assert!(Revision::London < Revision::Paris && Revision::Paris < Revision::Shanghai);
+}
+
+#[test]
+fn property_names_are_canonical_mnemonics() {
+    let properties = &*instructions::PROPERTIES;
+
+    assert_eq!(
+        properties[OpCode::KECCAK256.to_usize()].unwrap().name,
+        "KECCAK256"
+    );
+
+    let mut names = std::collections::HashSet::new();
+    for (op, p) in properties.iter().enumerate() {
+        if let Some(p) = p {
+            let opcode = OpCode(op as u8);
+            assert_eq!(p.name, opcode.name(), "name of opcode {:#04x}", op);
+            assert!(names.insert(p.name), "duplicate name {}", p.name);
+        }
+    }
+}
+
+/// Every change of instruction costs since Frontier: revision, opcode, cost before and since.
+/// `None` is an undefined instruction.
+///
+/// Spurious Dragon, Petersburg and Paris have not changed any cost.
+const FORK_DIFFS: &[(Revision, OpCode, Option<u16>, Option<u16>)] = &[
+    (Revision::Homestead, OpCode::DELEGATECALL, None, Some(40)),
+    (Revision::Tangerine, OpCode::BALANCE, Some(20), Some(400)),
+    (
+        Revision::Tangerine,
+        OpCode::EXTCODESIZE,
+        Some(20),
+        Some(700),
+    ),
+    (
+        Revision::Tangerine,
+        OpCode::EXTCODECOPY,
+        Some(20),
+        Some(700),
+    ),
+    (Revision::Tangerine, OpCode::SLOAD, Some(50), Some(200)),
+    (Revision::Tangerine, OpCode::CALL, Some(40), Some(700)),
+    (Revision::Tangerine, OpCode::CALLCODE, Some(40), Some(700)),
+    (
+        Revision::Tangerine,
+        OpCode::DELEGATECALL,
+        Some(40),
+        Some(700),
+    ),
+    (
+        Revision::Tangerine,
+        OpCode::SELFDESTRUCT,
+        Some(0),
+        Some(5000),
+    ),
+    (Revision::Byzantium, OpCode::RETURNDATASIZE, None, Some(2)),
+    (Revision::Byzantium, OpCode::RETURNDATACOPY, None, Some(3)),
+    (Revision::Byzantium, OpCode::STATICCALL, None, Some(700)),
+    (Revision::Byzantium, OpCode::REVERT, None, Some(0)),
+    (Revision::Constantinople, OpCode::SHL, None, Some(3)),
+    (Revision::Constantinople, OpCode::SHR, None, Some(3)),
+    (Revision::Constantinople, OpCode::SAR, None, Some(3)),
+    (
+        Revision::Constantinople,
+        OpCode::EXTCODEHASH,
+        None,
+        Some(400),
+    ),
+    (Revision::Constantinople, OpCode::CREATE2, None, Some(32000)),
+    (Revision::Istanbul, OpCode::BALANCE, Some(400), Some(700)),
+    (Revision::Istanbul, OpCode::CHAINID, None, Some(2)),
+    (
+        Revision::Istanbul,
+        OpCode::EXTCODEHASH,
+        Some(400),
+        Some(700),
+    ),
+    (Revision::Istanbul, OpCode::SELFBALANCE, None, Some(5)),
+    (Revision::Istanbul, OpCode::SLOAD, Some(200), Some(800)),
+    (Revision::Berlin, OpCode::BALANCE, Some(700), Some(100)),
+    (Revision::Berlin, OpCode::EXTCODESIZE, Some(700), Some(100)),
+    (Revision::Berlin, OpCode::EXTCODECOPY, Some(700), Some(100)),
+    (Revision::Berlin, OpCode::EXTCODEHASH, Some(700), Some(100)),
+    (Revision::Berlin, OpCode::SLOAD, Some(800), Some(100)),
+    (Revision::Berlin, OpCode::CALL, Some(700), Some(100)),
+    (Revision::Berlin, OpCode::CALLCODE, Some(700), Some(100)),
+    (Revision::Berlin, OpCode::DELEGATECALL, Some(700), Some(100)),
+    (Revision::Berlin, OpCode::STATICCALL, Some(700), Some(100)),
+    (Revision::London, OpCode::BASEFEE, None, Some(2)),
+    (Revision::Shanghai, OpCode::PUSH0, None, Some(2)),
+    (Revision::Cancun, OpCode::TLOAD, None, Some(100)),
+    (Revision::Cancun, OpCode::TSTORE, None, Some(100)),
+    (Revision::Cancun, OpCode::MCOPY, None, Some(3)),
+];
+
+#[test]
+fn gas_costs_match_fork_history() {
+    fn cost(cost: Option<u16>) -> String {
+        cost.map_or_else(|| "-".to_string(), |cost| cost.to_string())
+    }
+
+    let revisions = Revision::iter().into_iter().collect::<Vec<_>>();
+    let mut mismatches = Vec::new();
+    for pair in revisions.windows(2) {
+        let (before, revision) = (pair[0], pair[1]);
+        let (old, new) = (
+            get_baseline_instruction_table(before),
+            get_baseline_instruction_table(revision),
+        );
+
+        let actual = (0..=u8::MAX)
+            .map(OpCode)
+            .map(|op| {
+                let gas = |table: &InstructionTable| table[op.to_usize()].map(|e| e.gas_cost);
+                (op, gas(old), gas(new))
+            })
+            .filter(|(_, old, new)| old != new)
+            .collect::<Vec<_>>();
+        let expected = FORK_DIFFS
+            .iter()
+            .filter(|(r, ..)| *r == revision)
+            .map(|&(_, op, old, new)| (op, old, new))
+            .collect::<Vec<_>>();
+
+        for (diff, kind) in expected
+            .iter()
+            .filter(|diff| !actual.contains(diff))
+            .map(|diff| (diff, "missing"))
+            .chain(
+                actual
+                    .iter()
+                    .filter(|diff| !expected.contains(diff))
+                    .map(|diff| (diff, "unexpected")),
+            )
+        {
+            let (op, old, new) = diff;
+            mismatches.push(format!(
+                "{:<15} {:<15} {:>6} -> {:<6} {}",
+                revision.to_string(),
+                op.to_string(),
+                cost(*old),
+                cost(*new),
+                kind
+            ));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "gas cost changes differ from fork history:\n{:<15} {:<15} {:>6}    {:<6}\n{}",
+        "revision",
+        "opcode",
+        "before",
+        "since",
+        mismatches.join("\n")
+    );
+}
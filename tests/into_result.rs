@@ -0,0 +1,65 @@
+use ethereum_types::U256;
+use evmodin::{opcode::*, util::*, *};
+
+/// Revert with `Error(reason)`, `reason` being at most 32 bytes.
+fn revert_with_reason(reason: &[u8]) -> Bytecode {
+    let mut padded = [0; 32];
+    padded[..reason.len()].copy_from_slice(reason);
+    Bytecode::new()
+        .mstore_value(0, U256::from(0x08c379a0) << 224)
+        .mstore_value(4, 0x20)
+        .mstore_value(36, reason.len())
+        .mstore_value(68, U256::from_big_endian(&padded))
+        .pushv(100)
+        .pushv(0)
+        .opcode(OpCode::REVERT)
+}
+
+#[test]
+fn success_returns_output_data() {
+    let output = EvmTester::new()
+        .code(Bytecode::new().pushv(0xab).ret_top())
+        .status(StatusCode::Success)
+        .check_and_get_result();
+    let data = output.into_result().unwrap();
+    assert_eq!(U256::from_big_endian(&data), 0xab.into());
+}
+
+#[test]
+fn revert_reason() {
+    let output = EvmTester::new()
+        .code(revert_with_reason(b"x"))
+        .status(StatusCode::Revert)
+        .check_and_get_result();
+    let error = output.into_result().unwrap_err();
+    assert_eq!(error.to_string(), "execution reverted: x");
+}
+
+#[test]
+fn revert_without_reason() {
+    let output = EvmTester::new()
+        .code(
+            Bytecode::new()
+                .mstore8_value(0, 0xfe)
+                .pushv(1)
+                .pushv(0)
+                .opcode(OpCode::REVERT),
+        )
+        .status(StatusCode::Revert)
+        .check_and_get_result();
+    let error = output.into_result().unwrap_err();
+    assert_eq!(error.to_string(), "execution reverted: 0xfe");
+}
+
+#[test]
+fn failure_status() {
+    let output = EvmTester::new()
+        .code(Bytecode::new().opcode(OpCode::INVALID))
+        .status(StatusCode::InvalidInstruction)
+        .check_and_get_result();
+    let error = output.into_result().unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<StatusCode>(),
+        Some(&StatusCode::InvalidInstruction)
+    );
+}
@@ -0,0 +1,60 @@
+use evmodin::{opcode::*, util::*, *};
+use hex_literal::hex;
+use std::sync::Arc;
+
+#[derive(Debug)]
+struct FixedKeccak;
+
+impl KeccakProvider for FixedKeccak {
+    fn keccak256(&self, _: &[u8]) -> [u8; 32] {
+        [0x42; 32]
+    }
+}
+
+fn options(keccak: Arc<dyn KeccakProvider>) -> ExecutionOptions {
+    ExecutionOptions {
+        keccak,
+        ..Default::default()
+    }
+}
+
+fn hash_empty() -> Bytecode {
+    Bytecode::new().pushv(0).pushv(0).opcode(OpCode::KECCAK256)
+}
+
+#[test]
+fn default_provider() {
+    EvmTester::new()
+        .code(hash_empty().ret_top())
+        .status(StatusCode::Success)
+        .output_data(hex!(
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        ))
+        .check();
+}
+
+#[test]
+fn custom_provider_is_used() {
+    EvmTester::new()
+        .options(options(Arc::new(FixedKeccak)))
+        .code(hash_empty().ret_top())
+        .status(StatusCode::Success)
+        .output_data([0x42; 32])
+        .check();
+}
+
+#[test]
+fn counting_provider() {
+    let keccak = Arc::new(CountingKeccak::default());
+
+    EvmTester::new()
+        .options(options(keccak.clone()))
+        .code(hash_empty() + hash_empty() + hash_empty().ret_top())
+        .status(StatusCode::Success)
+        .output_data(hex!(
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        ))
+        .check();
+
+    assert_eq!(keccak.count(), 3);
+}
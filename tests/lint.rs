@@ -0,0 +1,88 @@
+use evmodin::{opcode::*, util::*, *};
+use hex_literal::hex;
+
+fn lint(code: &[u8], revision: Revision) -> Vec<(usize, LintKind, i64)> {
+    AnalyzedCode::analyze(code)
+        .lint(revision)
+        .into_iter()
+        .map(|finding| (finding.pc, finding.kind, finding.estimated_savings))
+        .collect()
+}
+
+#[test]
+fn single_findings() {
+    for (code, expected) in [
+        // PUSH1 1 PUSH1 2 SWAP1 POP POP STOP
+        (
+            hex!("6001600290505000").to_vec(),
+            (4, LintKind::SwapPopPop, 3),
+        ),
+        // PUSH1 1 DUP1 POP POP STOP
+        (hex!("600180505000").to_vec(), (2, LintKind::DupPop, 5)),
+        // PUSH1 1 POP JUMPDEST STOP
+        (
+            hex!("6001505b00").to_vec(),
+            (3, LintKind::UntargetedJumpdest, 1),
+        ),
+        // KECCAK256(10, 32) twice: KECCAK256 30, one word 6, two PUSH1 3 each, less DUP1 3.
+        (
+            hex!("6020600a206020600a2000").to_vec(),
+            (5, LintKind::RepeatedKeccak, 39),
+        ),
+        // STOP PUSH1 1 POP
+        (hex!("00600150").to_vec(), (1, LintKind::Unreachable, 0)),
+    ] {
+        assert_eq!(
+            lint(&code, Revision::London),
+            [expected],
+            "{}",
+            hex::encode(&code)
+        );
+    }
+}
+
+#[test]
+fn push_zero_savings() {
+    // PUSH1 0 PUSH2 0 ADD POP STOP
+    let code = hex!("6000610000015000");
+    let revision = Revision::latest();
+    let table = get_baseline_instruction_table(revision);
+
+    let findings = lint(&code, revision);
+    assert_eq!(
+        findings.iter().map(|f| (f.0, f.1)).collect::<Vec<_>>(),
+        [(0, LintKind::PushZero), (2, LintKind::PushZero)]
+    );
+    let push_gas = table[OpCode::PUSH1.to_usize()].unwrap().gas_cost;
+    let push0_gas = table[OpCode::PUSH0.to_usize()].unwrap().gas_cost;
+    assert_eq!(
+        findings.iter().map(|f| f.2).sum::<i64>(),
+        2 * i64::from(push_gas - push0_gas)
+    );
+
+    // No PUSH0 before Shanghai.
+    assert!(lint(&code, Revision::London).is_empty());
+}
+
+#[test]
+fn clean_code() {
+    for code in [
+        Bytecode::new()
+            .pushv(2)
+            .pushv(3)
+            .opcode(OpCode::ADD)
+            .ret_top()
+            .build(),
+        // PUSH1 1 PUSH1 6 JUMPI STOP JUMPDEST STOP
+        hex!("6001600657005b00").to_vec(),
+        // Dynamic jump: every JUMPDEST may be a target.
+        // CALLDATASIZE JUMP JUMPDEST STOP
+        hex!("36565b00").to_vec(),
+    ] {
+        assert!(
+            lint(&code, Revision::London).is_empty(),
+            "{}",
+            hex::encode(&code)
+        );
+    }
+}
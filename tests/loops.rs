@@ -0,0 +1,80 @@
+use evmodin::{opcode::*, util::*, *};
+
+const GAS: i64 = 10_000_000;
+
+/// Count down from `start` to zero, running `body` in every iteration.
+fn countdown(start: u64, body: Bytecode) -> Bytecode {
+    let head = Bytecode::new().pushv(start);
+    let jumpdest = head.clone().build().len() as u64;
+    head.opcode(OpCode::JUMPDEST)
+        .append_bc(body)
+        .pushv(1)
+        .opcode(OpCode::SWAP1)
+        .opcode(OpCode::SUB)
+        .opcode(OpCode::DUP1)
+        .pushv(jumpdest)
+        .opcode(OpCode::JUMPI)
+}
+
+fn run(code: Bytecode, gas: i64, options: ExecutionOptions, status: StatusCode) -> Output {
+    EvmTester::new()
+        .revision(Revision::Istanbul)
+        .gas(gas)
+        .options(options)
+        .code(code)
+        .status(status)
+        .check_and_get_result()
+}
+
+fn loop_detection() -> ExecutionOptions {
+    ExecutionOptions {
+        loop_detection: Some(LoopDetectionConfig {
+            sample_interval: 16,
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn endless_loop_detected() {
+    let code = countdown(u64::MAX, Bytecode::new());
+    let full = run(
+        code.clone(),
+        GAS,
+        ExecutionOptions::default(),
+        StatusCode::OutOfGas,
+    );
+    assert!(!full.stats.loop_detected);
+
+    let detected = run(code, GAS, loop_detection(), StatusCode::OutOfGas);
+    assert!(detected.stats.loop_detected);
+    assert_eq!(detected.gas_left, full.gas_left);
+}
+
+#[test]
+fn finite_loop_runs_to_completion() {
+    // Stack and memory contents are not fingerprinted, so a countdown is reported, but
+    // detection does not change the result.
+    let code = countdown(1000, Bytecode::new());
+    let full = run(
+        code.clone(),
+        GAS,
+        ExecutionOptions::default(),
+        StatusCode::Success,
+    );
+
+    let detected = run(code, GAS, loop_detection(), StatusCode::Success);
+    assert!(detected.stats.loop_detected);
+    assert_eq!(detected.gas_left, full.gas_left);
+}
+
+#[test]
+fn loop_with_sload_not_detected() {
+    let output = run(
+        countdown(u64::MAX, Bytecode::new().sload(0).opcode(OpCode::POP)),
+        100_000,
+        loop_detection(),
+        StatusCode::OutOfGas,
+    );
+    assert!(!output.stats.loop_detected);
+}
@@ -0,0 +1,75 @@
+use evmodin::{opcode::*, util::*, *};
+
+#[test]
+fn no_metering_runs_past_out_of_gas() {
+    // SSTORE alone costs 20000 gas, and requires more than 2300 gas left since Istanbul.
+    let t = EvmTester::new()
+        .revision(Revision::Istanbul)
+        .code(Bytecode::new().sstore(1, 1).pushv(1).ret_top())
+        .gas(100);
+
+    t.clone().status(StatusCode::OutOfGas).check();
+
+    t.options(ExecutionOptions {
+        metering: false,
+        ..Default::default()
+    })
+    .status(StatusCode::Success)
+    .output_value(1)
+    .gas_used(2 * 3 + 20000 + 3 + 3 * 3 + 3 + 2 * 3)
+    .check();
+}
+
+#[test]
+fn no_metering_clamps_forwarded_gas() {
+    // SSTORE leaves gas negative, then CALL and CREATE request more gas than is left.
+    let t = EvmTester::new()
+        .revision(Revision::Homestead)
+        .gas(100)
+        .options(ExecutionOptions {
+            metering: false,
+            ..Default::default()
+        })
+        .inspect_host(|host, _| {
+            let r = host.recorded.lock();
+            assert_eq!(r.calls.len(), 1);
+            assert_eq!(r.calls[0].gas, 0);
+            assert_eq!(r.calls[0].validate(Revision::Homestead), Ok(()));
+        });
+
+    t.clone()
+        .code(
+            Bytecode::new()
+                .sstore(1, 1)
+                .append_bc(CallInstruction::call(0xca11).gas(1_000_000)),
+        )
+        .status(StatusCode::Success)
+        .check();
+
+    t.code(
+        Bytecode::new()
+            .sstore(1, 1)
+            .pushv(0)
+            .pushv(0)
+            .pushv(0)
+            .opcode(OpCode::CREATE),
+    )
+    .status(StatusCode::Success)
+    .check();
+}
+
+#[test]
+fn no_metering_gas_below_zero() {
+    // SSTORE leaves gas negative, GAS reports zero instead.
+    EvmTester::new()
+        .revision(Revision::Istanbul)
+        .gas(100)
+        .options(ExecutionOptions {
+            metering: false,
+            ..Default::default()
+        })
+        .code(Bytecode::new().sstore(1, 1).opcode(OpCode::GAS).ret_top())
+        .status(StatusCode::Success)
+        .output_value(0)
+        .check();
+}
@@ -0,0 +1,96 @@
+use ethereum_types::Address;
+use evmodin::{opcode::*, util::*, *};
+use hex_literal::hex;
+
+#[test]
+fn view_function() {
+    EvmTester::new()
+        .code(
+            Bytecode::from(CallInstruction::staticcall(0xaa))
+                .opcode(OpCode::POP)
+                .pushv(0)
+                .opcode(OpCode::SLOAD)
+                .pushv(3)
+                .opcode(OpCode::ADD)
+                .ret_top(),
+        )
+        .status(StatusCode::Success)
+        .output_value(3)
+        .expect_no_mutation()
+        .check();
+}
+
+#[test]
+fn conditional_sstore() {
+    // if calldataload(0) { sstore(0, 1) }
+    let code = hex!("600035600757005b600160005500");
+
+    EvmTester::new()
+        .code(code)
+        .status(StatusCode::Success)
+        .expect_no_mutation()
+        .check();
+
+    let output = EvmTester::new()
+        .code(code)
+        .input([1; 32].to_vec())
+        .status(StatusCode::Success)
+        .check_and_get_result();
+    assert_eq!(
+        output.stats.mutation_observed,
+        Some(MutationKind::SStore { pc: 12 })
+    );
+}
+
+#[test]
+fn value_call() {
+    let code = Bytecode::from(CallInstruction::call(0xaa).value(1)).build();
+
+    let output = EvmTester::new()
+        .apply_host_fn(|host, _| {
+            host.accounts.entry(Address::zero()).or_default().balance = 1.into();
+        })
+        .code(code.clone())
+        .status(StatusCode::Success)
+        .check_and_get_result();
+    assert_eq!(
+        output.stats.mutation_observed,
+        Some(MutationKind::ValueCall { pc: code.len() - 1 })
+    );
+}
+
+#[test]
+fn pure_computation() {
+    // (2 ** 10 + 6) / 10 % 7
+    for revision in [Revision::Byzantium, Revision::London] {
+        EvmTester::new()
+            .revision(revision)
+            .code(
+                Bytecode::new()
+                    .pushv(7)
+                    .pushv(10)
+                    .pushv(6)
+                    .pushv(10)
+                    .pushv(2)
+                    .opcode(OpCode::EXP)
+                    .opcode(OpCode::ADD)
+                    .opcode(OpCode::DIV)
+                    .opcode(OpCode::MOD)
+                    .ret_top(),
+            )
+            .status(StatusCode::Success)
+            .output_value(103 % 7)
+            .expect_no_host_interaction()
+            .check();
+    }
+}
+
+#[test]
+#[should_panic(expected = "Host interaction observed")]
+fn storage_read_is_host_interaction() {
+    EvmTester::new()
+        .code(Bytecode::new().pushv(0).opcode(OpCode::SLOAD).ret_top())
+        .status(StatusCode::Success)
+        .expect_no_host_interaction()
+        .check();
+}
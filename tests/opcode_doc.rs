@@ -0,0 +1,73 @@
+use evmodin::{
+    opcode::{doc::*, *},
+    *,
+};
+
+#[test]
+fn export_json_round_trip() {
+    let json = export_json();
+
+    let docs: Vec<OpCodeDoc> = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&docs).unwrap(), json);
+    assert_eq!(docs, opcodes());
+}
+
+#[test]
+fn export_covers_latest_revision() {
+    let table = get_baseline_instruction_table(Revision::latest());
+    let expected = (0..=u8::MAX)
+        .filter(|&op| table[op as usize].is_some())
+        .collect::<Vec<_>>();
+
+    let docs = opcodes();
+    assert_eq!(
+        docs.iter().map(|doc| doc.value).collect::<Vec<_>>(),
+        expected
+    );
+
+    for doc in &docs {
+        assert!(
+            !doc.description.is_empty(),
+            "{} is not documented",
+            doc.name
+        );
+        assert_eq!(doc.name, OpCode(doc.value).name());
+        assert_eq!(
+            doc.gas.last().unwrap().revision,
+            Revision::latest().to_string()
+        );
+    }
+}
+
+#[test]
+fn opcode_doc_contents() {
+    let docs = opcodes();
+    let find = |op: OpCode| docs.iter().find(|doc| doc.value == op.to_u8()).unwrap();
+
+    let add = find(OpCode::ADD);
+    assert_eq!(add.stack_in, 2);
+    assert_eq!(add.stack_out, 1);
+    assert_eq!(add.introduced_in, "Frontier");
+
+    let chainid = find(OpCode::CHAINID);
+    assert_eq!(chainid.introduced_in, "Istanbul");
+    assert_eq!(chainid.gas[0].revision, "Istanbul");
+    assert_eq!(chainid.gas[0].gas, 2);
+
+    assert_eq!(
+        introduced_in(OpCode::CREATE2),
+        Some(Revision::Constantinople)
+    );
+    assert_eq!(introduced_in(OpCode(0x0c)), None);
+    assert_eq!(description(OpCode(0x0c)), None);
+
+    let sload = find(OpCode::SLOAD);
+    assert_eq!(
+        sload
+            .gas
+            .iter()
+            .map(|g| g.gas)
+            .collect::<std::collections::BTreeSet<_>>(),
+        [50, 200, 800, 100].into_iter().collect()
+    );
+}
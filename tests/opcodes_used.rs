@@ -0,0 +1,30 @@
+use evmodin::{opcode::*, util::*};
+use hex_literal::hex;
+
+#[test]
+fn add_sstore_jump() {
+    // PUSH1 1 PUSH1 0xff ADD PUSH1 0 SSTORE PUSH1 11 JUMP JUMPDEST
+    let code = hex!("600160ff01600055600b565b");
+
+    assert_eq!(
+        opcodes_used(&code).into_iter().collect::<Vec<_>>(),
+        [
+            OpCode::ADD,
+            OpCode::SSTORE,
+            OpCode::JUMP,
+            OpCode::JUMPDEST,
+            OpCode::PUSH1,
+        ]
+    );
+}
+
+#[test]
+fn push_data_is_skipped() {
+    // PUSH2 0xfff4, truncated PUSH32 with SELFDESTRUCT in its data.
+    let code = hex!("61fff47fff");
+
+    assert_eq!(
+        opcodes_used(&code).into_iter().collect::<Vec<_>>(),
+        [OpCode::PUSH2, OpCode::PUSH32]
+    );
+}
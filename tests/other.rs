@@ -1,4 +1,8 @@
-use evmodin::{opcode::*, util::*, *};
+use evmodin::{
+    opcode::*,
+    util::{mocked_host::StorageValue, *},
+    *,
+};
 
 #[test]
 fn loop_full_of_jumpdests() {
@@ -52,3 +56,105 @@ fn jumpdest_with_high_offset() {
             .check()
     }
 }
+
+#[test]
+fn host_fns_apply_in_order() {
+    let t = EvmTester::new()
+        .code(Bytecode::new().sload(1).ret_top())
+        .apply_host_fn(|host, msg| {
+            host.accounts
+                .entry(msg.recipient)
+                .or_default()
+                .storage
+                .insert(
+                    1.into(),
+                    StorageValue {
+                        value: 1.into(),
+                        ..Default::default()
+                    },
+                );
+        })
+        .apply_host_fn(|host, msg| {
+            host.accounts
+                .get_mut(&msg.recipient)
+                .unwrap()
+                .storage
+                .get_mut(&1.into())
+                .unwrap()
+                .value += 1;
+        });
+
+    t.clone()
+        .status(StatusCode::Success)
+        .output_value(2)
+        .check();
+
+    t.clear_host_fns()
+        .status(StatusCode::Success)
+        .output_value(0)
+        .check();
+}
+
+#[test]
+fn message_validation() {
+    let message = Message {
+        kind: CallKind::Call,
+        is_static: false,
+        depth: 0,
+        gas: 1000,
+        recipient: Default::default(),
+        sender: Default::default(),
+        input_data: Default::default(),
+        value: 1.into(),
+        code_address: Default::default(),
+    };
+    assert_eq!(message.validate(Revision::latest()), Ok(()));
+
+    for (message, revision, status) in [
+        (
+            Message {
+                gas: -1,
+                ..message.clone()
+            },
+            Revision::latest(),
+            StatusCode::ArgumentOutOfRange,
+        ),
+        (
+            Message {
+                depth: 1025,
+                ..message.clone()
+            },
+            Revision::latest(),
+            StatusCode::CallDepthExceeded,
+        ),
+        (
+            Message {
+                is_static: true,
+                ..message.clone()
+            },
+            Revision::latest(),
+            StatusCode::StaticModeViolation,
+        ),
+        (
+            Message {
+                kind: CallKind::Create2 { salt: 0.into() },
+                ..message.clone()
+            },
+            Revision::Byzantium,
+            StatusCode::UndefinedInstruction,
+        ),
+    ] {
+        assert_eq!(message.validate(revision), Err(status));
+    }
+
+    // DELEGATECALL in a static frame keeps the apparent value.
+    assert_eq!(
+        Message {
+            kind: CallKind::DelegateCall,
+            is_static: true,
+            ..message
+        }
+        .validate(Revision::latest()),
+        Ok(())
+    );
+}
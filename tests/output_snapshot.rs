@@ -0,0 +1,78 @@
+use bytes::Bytes;
+use ethereum_types::{Address, U256};
+use evmodin::{opcode::*, tracing::NoopTracer, util::*, *};
+
+fn message(input_data: &'static [u8]) -> Message {
+    Message {
+        kind: CallKind::Call,
+        is_static: false,
+        depth: 0,
+        gas: 1_000_000,
+        recipient: Address::zero(),
+        sender: Address::zero(),
+        input_data: Bytes::from_static(input_data),
+        value: U256::zero(),
+        code_address: Address::zero(),
+    }
+}
+
+fn execute(code: &AnalyzedCode, host: &mut MockedHost, input_data: &'static [u8]) -> Output {
+    code.execute(
+        host,
+        &mut NoopTracer,
+        None,
+        message(input_data),
+        Revision::latest(),
+    )
+}
+
+#[test]
+fn returned_memory_is_not_reused() {
+    // Return calldata through memory.
+    let code = AnalyzedCode::analyze(
+        Bytecode::new()
+            .opcode(OpCode::CALLDATASIZE)
+            .pushv(0)
+            .pushv(0)
+            .opcode(OpCode::CALLDATACOPY)
+            .opcode(OpCode::CALLDATASIZE)
+            .pushv(0)
+            .opcode(OpCode::RETURN)
+            .build(),
+    );
+    let mut host = MockedHost::default();
+
+    let first = execute(&code, &mut host, b"first");
+    let second = execute(&code, &mut host, b"second output");
+
+    assert_eq!(first.output_data, Bytes::from_static(b"first"));
+    assert_eq!(second.output_data, Bytes::from_static(b"second output"));
+}
+
+#[test]
+fn callee_output_is_not_reused() {
+    // Call and return what the callee has returned.
+    let code = AnalyzedCode::analyze(
+        (Bytecode::new()
+            + CallInstruction::call(0xaa)
+            + Bytecode::new()
+                .opcode(OpCode::POP)
+                .opcode(OpCode::RETURNDATASIZE)
+                .pushv(0)
+                .pushv(0)
+                .opcode(OpCode::RETURNDATACOPY)
+                .opcode(OpCode::RETURNDATASIZE)
+                .pushv(0)
+                .opcode(OpCode::RETURN))
+        .build(),
+    );
+    let mut host = MockedHost::default();
+
+    host.call_result.output_data = Bytes::from_static(b"first");
+    let first = execute(&code, &mut host, b"");
+    host.call_result.output_data = Bytes::from_static(b"second output");
+    let second = execute(&code, &mut host, b"");
+
+    assert_eq!(first.output_data, Bytes::from_static(b"first"));
+    assert_eq!(second.output_data, Bytes::from_static(b"second output"));
+}
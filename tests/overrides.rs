@@ -0,0 +1,116 @@
+use ethereum_types::{Address, H256, U256};
+use evmodin::{opcode::*, util::*, *};
+use hex_literal::hex;
+
+#[test]
+fn balance_and_storage_overrides() {
+    let address = Address::from(hex!("00000000000000000000000000000000000000aa"));
+
+    let mut options = ExecutionOptions::default();
+    options.overrides.balance.insert(address, 7.into());
+    options.overrides.storage.insert(
+        (address, H256::from_low_u64_be(1)),
+        H256::from_low_u64_be(0x42),
+    );
+
+    let mut expected = Vec::new();
+    for v in [7, 0x42, 5] {
+        expected.extend_from_slice(&<[u8; 32]>::from(U256::from(v)));
+    }
+
+    EvmTester::new()
+        .revision(Revision::Istanbul)
+        .destination(address)
+        .apply_host_fn(move |host, _| {
+            let account = host.accounts.entry(address).or_default();
+            account.balance = 1000.into();
+            account.storage.entry(2.into()).or_default().value = 5.into();
+        })
+        .options(options)
+        .code(
+            Bytecode::new()
+                .opcode(OpCode::SELFBALANCE)
+                .mstore(0)
+                .sload(1)
+                .mstore(0x20)
+                .sload(2)
+                .mstore(0x40)
+                .ret(0, 0x60),
+        )
+        .status(StatusCode::Success)
+        .output_data(expected)
+        .inspect_host(move |host, _| {
+            // Only the non-overridden storage slot reaches the host.
+            assert_eq!(host.recorded.lock().account_accesses, [address]);
+        })
+        .check()
+}
+
+#[test]
+fn overridden_storage_slot_is_writable() {
+    let address = Address::zero();
+
+    let mut options = ExecutionOptions::default();
+    options.overrides.storage.insert(
+        (address, H256::from_low_u64_be(1)),
+        H256::from_low_u64_be(0x42),
+    );
+
+    EvmTester::new()
+        .revision(Revision::Istanbul)
+        .options(options)
+        .code(Bytecode::new().sstore(1, 3).sload(1).ret_top())
+        .status(StatusCode::Success)
+        .output_value(3)
+        .check()
+}
+
+#[test]
+fn sstore_gas_follows_overridden_value() {
+    // The host has no value in the slot, the override is seen as its original value.
+    let mut options = ExecutionOptions::default();
+    options.overrides.storage.insert(
+        (Address::zero(), H256::from_low_u64_be(1)),
+        H256::from_low_u64_be(0x42),
+    );
+    let t = EvmTester::new()
+        .revision(Revision::Istanbul)
+        .options(options)
+        .status(StatusCode::Success);
+
+    // Writing the overridden value back leaves it unchanged.
+    t.clone()
+        .code(Bytecode::new().sstore(1, 0x42))
+        .gas_used(3 + 3 + 800)
+        .check();
+
+    // Writing another value modifies it, then writing it again is a dirty write.
+    t.code(Bytecode::new().sstore(1, 3).sstore(1, 4))
+        .gas_used(3 + 3 + 5000 + 3 + 3 + 800)
+        .check();
+}
+
+#[test]
+fn code_overrides() {
+    let address = Address::from(hex!("00000000000000000000000000000000000000bb"));
+    let code = hex!("6001600201");
+
+    let mut options = ExecutionOptions::default();
+    options.overrides.code.insert(address, code.to_vec().into());
+
+    EvmTester::new()
+        .revision(Revision::Istanbul)
+        .options(options)
+        .code(
+            Bytecode::new()
+                .pushv(address.to_low_u64_be())
+                .opcode(OpCode::EXTCODESIZE)
+                .ret_top(),
+        )
+        .status(StatusCode::Success)
+        .output_value(code.len())
+        .inspect_host(|host, _| {
+            assert!(host.recorded.lock().account_accesses.is_empty());
+        })
+        .check()
+}
@@ -0,0 +1,120 @@
+use ethereum_types::{Address, H256, U256};
+use evmodin::{
+    opcode::*,
+    util::{mocked_host::*, *},
+    *,
+};
+
+const KEYS: [u64; 5] = [1, 2, 3, 4, 5];
+
+fn contract() -> Address {
+    Address::from_low_u64_be(0xaa)
+}
+
+/// Read all `KEYS` and return the sum of their values.
+fn code() -> Bytecode {
+    let mut code = Bytecode::new().pushv(0);
+    for key in KEYS {
+        code = code.pushv(key).opcode(OpCode::SLOAD).opcode(OpCode::ADD);
+    }
+    code.ret_top()
+}
+
+fn t(prefetch: Option<PrefetchPlan>) -> EvmTester {
+    EvmTester::new()
+        .destination(contract())
+        .apply_host_fn(|host, msg| {
+            let storage = &mut host.accounts.entry(msg.recipient).or_default().storage;
+            for key in KEYS {
+                storage.insert(
+                    key.into(),
+                    StorageValue {
+                        value: (key * 0x100).into(),
+                        ..Default::default()
+                    },
+                );
+            }
+        })
+        .options(ExecutionOptions {
+            prefetch,
+            ..Default::default()
+        })
+        .code(code())
+}
+
+#[test]
+fn prefetched_slots_skip_host() {
+    let planned = vec![
+        H256::from_low_u64_be(1),
+        H256::from_low_u64_be(3),
+        H256::from_low_u64_be(5),
+    ];
+    let unbatched = t(None).check_and_get_result();
+
+    let output = t(Some(PrefetchPlan {
+        storage: vec![(contract(), planned.clone())],
+        ..Default::default()
+    }))
+    .inspect_host(move |host, _| {
+        let r = host.recorded.lock();
+        assert_eq!(r.storage_batches, [(contract(), planned.clone())]);
+        assert_eq!(
+            r.storage_reads,
+            [(contract(), U256::from(2)), (contract(), U256::from(4))]
+        );
+    })
+    .check_and_get_result();
+
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(output.output_data, unbatched.output_data);
+    assert_eq!(output.gas_left, unbatched.gas_left);
+    assert_eq!(
+        U256::from_big_endian(&output.output_data),
+        (0x100 * KEYS.iter().sum::<u64>()).into()
+    );
+}
+
+#[test]
+fn write_invalidates_prefetched_slot() {
+    // SSTORE(1, 7) SLOAD(1)
+    let code = Bytecode::new()
+        .pushv(7)
+        .pushv(1)
+        .opcode(OpCode::SSTORE)
+        .pushv(1)
+        .opcode(OpCode::SLOAD)
+        .ret_top();
+
+    t(Some(PrefetchPlan {
+        storage: vec![(contract(), vec![H256::from_low_u64_be(1)])],
+        ..Default::default()
+    }))
+    .revision(Revision::Berlin)
+    .code(code)
+    .output_value(7)
+    .inspect_host(|host, _| {
+        assert_eq!(
+            host.recorded.lock().storage_reads,
+            [(contract(), U256::one())]
+        );
+    })
+    .check();
+}
+
+#[test]
+fn prefetched_balances() {
+    let other = Address::from_low_u64_be(0xbb);
+    t(Some(PrefetchPlan {
+        balances: vec![other],
+        ..Default::default()
+    }))
+    .apply_host_fn(move |host, _| host.accounts.entry(other).or_default().balance = 42.into())
+    .code(
+        Bytecode::new()
+            .pushb(other.0)
+            .opcode(OpCode::BALANCE)
+            .ret_top(),
+    )
+    .output_value(42)
+    .check();
+}
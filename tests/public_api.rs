@@ -0,0 +1,39 @@
+//! Hand-maintained list of the public API. Removing or moving any of these paths breaks
+//! downstream crates, so changes here must be deliberate.
+#![allow(unused_imports)]
+
+use evmodin::{
+    continuation::{interrupt::InterruptVariant, interrupt_data::InterruptDataVariant, CallFrame},
+    get_baseline_instruction_table,
+    host::{AccessStatus, DummyHost, Host, StorageStatus, TxContext},
+    opcode::OpCode,
+    tracing::{
+        CollectingTracer, CoverageMap, GasLedger, NoopTracer, OnFailureTracer, StdoutTracer,
+        TraceStep, Tracer,
+    },
+    util::{
+        bench_opcode, boundary_probe, create_address, deploy, derive_access_list, diff_executions,
+        execute_transaction, mocked_host::MockedHost, opcodes_used, render_call_tree, AccountDiff,
+        Bytecode, CallInstruction, CallStackExecutor, CallTreeNode, CountingKeccak, DelegatingHost,
+        EvmTester, Receipt, SharedHost, TransactionEnv,
+    },
+    AccessCounts, AnalysisError, AnalysisStats, AnalyzedCode, CallKind, Capability, CapabilityMask,
+    CreateFailure, CreateMessage, DecodeError, EofError, ExecutionOptions, ExecutionOverrides,
+    ExecutionState, ExecutionStats, FailureSite, GasCharge, GasReason, HostBudget,
+    HostBudgetExceeded, InstructionTable, InstructionTableEntry, InstructionTables, KeccakProvider,
+    LintFinding, LintKind, LoopDetectionConfig, Message, MutationKind, Output, PrefetchPlan,
+    RefundSource, Revision, Sha3Keccak, Stack, StatusCode, SuccessfulOutput, VerboseExecutionState,
+    MAX_CODE_SIZE, MAX_INITCODE_SIZE,
+};
+
+#[test]
+fn prelude_is_enough_to_run_code() {
+    use evmodin::prelude::*;
+
+    EvmTester::new()
+        .revision(Revision::latest())
+        .code(Bytecode::new().pushv(1).ret_top())
+        .status(StatusCode::Success)
+        .output_value(1)
+        .check();
+}
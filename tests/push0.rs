@@ -0,0 +1,65 @@
+use evmodin::{opcode::*, util::*, *};
+
+#[test]
+fn push0_pre_shanghai() {
+    EvmTester::new()
+        .revision(Revision::Berlin)
+        .code(Bytecode::new().opcode(OpCode::PUSH0))
+        .status(StatusCode::UndefinedInstruction)
+        .check()
+}
+
+#[test]
+fn push0_nominal_case() {
+    let t = EvmTester::new().revision(Revision::Shanghai);
+    t.clone()
+        .code(Bytecode::new().opcode(OpCode::PUSH0).opcode(OpCode::STOP))
+        .status(StatusCode::Success)
+        .gas_used(2)
+        .check();
+
+    t.code(
+        Bytecode::new()
+            .pushv(1)
+            .opcode(OpCode::PUSH0)
+            .opcode(OpCode::MSTORE)
+            .pushv(32)
+            .opcode(OpCode::PUSH0)
+            .opcode(OpCode::RETURN),
+    )
+    .status(StatusCode::Success)
+    .gas_used(3 + 2 + 3 + 3 + 3 + 2)
+    .output_value(1)
+    .check()
+}
+
+#[test]
+fn pushv_zero_targets_revision() {
+    let code = |revision| {
+        Bytecode::new()
+            .revision(revision)
+            .pushv(0)
+            .opcode(OpCode::STOP)
+    };
+
+    EvmTester::new()
+        .revision(Revision::Shanghai)
+        .code(code(Revision::Shanghai))
+        .status(StatusCode::Success)
+        .gas_used(2)
+        .check();
+
+    // Code targeting Shanghai does not run on London.
+    EvmTester::new()
+        .revision(Revision::London)
+        .code(code(Revision::Shanghai))
+        .status(StatusCode::UndefinedInstruction)
+        .check();
+
+    EvmTester::new()
+        .revision(Revision::London)
+        .code(code(Revision::London))
+        .status(StatusCode::Success)
+        .gas_used(3)
+        .check();
+}
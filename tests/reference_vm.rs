@@ -0,0 +1,261 @@
+use bytes::Bytes;
+use ethereum_types::{Address, U256};
+use evmodin::{
+    host::DummyHost,
+    opcode::*,
+    tracing::*,
+    util::{reference_vm::*, *},
+    *,
+};
+use proptest::prelude::*;
+
+/// Tracer remembering the stack seen by the last executed instruction.
+#[derive(Default)]
+struct LastStack(Vec<U256>);
+
+impl Tracer for LastStack {
+    fn notify_execution_start(&mut self, _: Revision, _: Message, _: Bytes) {}
+
+    fn notify_instruction_start(&mut self, _: usize, _: OpCode, state: &ExecutionState) {
+        self.0 = state.stack().0.to_vec();
+    }
+
+    fn notify_execution_end(&mut self, _: &Output) {}
+}
+
+fn execute<T: Tracer>(code: &AnalyzedCode, tracer: &mut T, input: &[u8], gas: i64) -> Output {
+    code.execute(
+        &mut DummyHost,
+        tracer,
+        None,
+        Message {
+            kind: CallKind::Call,
+            is_static: false,
+            depth: 0,
+            gas,
+            recipient: Address::zero(),
+            sender: Address::zero(),
+            input_data: input.to_vec().into(),
+            value: U256::zero(),
+            code_address: Address::zero(),
+        },
+        Revision::latest(),
+    )
+}
+
+/// Run code on both interpreters and compare the results.
+///
+/// Code must end with an explicit terminating instruction, so that the tracer observes the final stack.
+fn compare(code: &[u8], input: &[u8], gas: i64) -> Result<(), TestCaseError> {
+    let reference = execute_pure(code, input, gas);
+
+    let analyzed = AnalyzedCode::analyze(code.to_vec());
+    let output = execute(&analyzed, &mut NoopTracer, input, gas);
+    let mut last_stack = LastStack::default();
+    let traced = execute(&analyzed, &mut last_stack, input, gas);
+    prop_assert_eq!(&output, &traced, "tracing changed the result");
+
+    match reference {
+        Ok(outcome) => {
+            let expected_status = if outcome.reverted {
+                StatusCode::Revert
+            } else {
+                StatusCode::Success
+            };
+            prop_assert_eq!(output.status_code, expected_status);
+            prop_assert_eq!(output.gas_left, outcome.gas_left);
+            prop_assert_eq!(&*output.output_data, &outcome.output[..]);
+            prop_assert_eq!(last_stack.0, outcome.stack);
+        }
+        Err(status_code) => prop_assert_eq!(output.status_code, status_code),
+    }
+
+    Ok(())
+}
+
+const PURE_OPCODES: &[OpCode] = &[
+    OpCode::STOP,
+    OpCode::ADD,
+    OpCode::MUL,
+    OpCode::SUB,
+    OpCode::DIV,
+    OpCode::SDIV,
+    OpCode::MOD,
+    OpCode::SMOD,
+    OpCode::ADDMOD,
+    OpCode::MULMOD,
+    OpCode::EXP,
+    OpCode::SIGNEXTEND,
+    OpCode::LT,
+    OpCode::GT,
+    OpCode::SLT,
+    OpCode::SGT,
+    OpCode::EQ,
+    OpCode::ISZERO,
+    OpCode::AND,
+    OpCode::OR,
+    OpCode::XOR,
+    OpCode::NOT,
+    OpCode::BYTE,
+    OpCode::SHL,
+    OpCode::SHR,
+    OpCode::SAR,
+    OpCode::KECCAK256,
+    OpCode::CALLDATALOAD,
+    OpCode::CALLDATASIZE,
+    OpCode::CALLDATACOPY,
+    OpCode::CODESIZE,
+    OpCode::CODECOPY,
+    OpCode::POP,
+    OpCode::MLOAD,
+    OpCode::MSTORE,
+    OpCode::MSTORE8,
+    OpCode::JUMP,
+    OpCode::JUMPI,
+    OpCode::PC,
+    OpCode::MSIZE,
+    OpCode::GAS,
+    OpCode::JUMPDEST,
+    OpCode::MCOPY,
+    OpCode::PUSH0,
+    OpCode::DUP1,
+    OpCode::DUP2,
+    OpCode::DUP3,
+    OpCode::DUP16,
+    OpCode::SWAP1,
+    OpCode::SWAP2,
+    OpCode::SWAP3,
+    OpCode::SWAP16,
+    OpCode::RETURN,
+    OpCode::REVERT,
+    OpCode::INVALID,
+];
+
+fn neg(v: u64) -> U256 {
+    (!U256::from(v)).overflowing_add(U256::one()).0
+}
+
+fn push_value() -> impl Strategy<Value = U256> {
+    prop_oneof![
+        (0u64..64).prop_map(U256::from),
+        prop::sample::select(vec![
+            U256::from(31),
+            U256::from(32),
+            U256::from(255),
+            U256::from(256),
+            U256::one() << 255,
+            (U256::one() << 255) - 1,
+            U256::MAX,
+            neg(2),
+        ]),
+        any::<[u8; 32]>().prop_map(|b| U256::from_big_endian(&b)),
+    ]
+}
+
+#[derive(Clone, Debug)]
+enum Item {
+    Push(U256),
+    Op(OpCode),
+}
+
+fn program() -> impl Strategy<Value = Vec<u8>> {
+    let item = prop_oneof![
+        3 => push_value().prop_map(Item::Push),
+        2 => prop::sample::select(PURE_OPCODES).prop_map(Item::Op),
+    ];
+    (prop::collection::vec(item, 0..48), 0..3).prop_map(|(items, end)| {
+        let mut code = Bytecode::new();
+        for item in items {
+            code = match item {
+                Item::Push(v) => code.pushv(v),
+                Item::Op(op) => code.opcode(op),
+            };
+        }
+        match end {
+            0 => code.opcode(OpCode::STOP),
+            1 => code.ret(0, 32),
+            _ => code.pushv(32).pushv(0).opcode(OpCode::REVERT),
+        }
+        .build()
+    })
+}
+
+proptest! {
+    #[test]
+    fn reference_vm_agrees(
+        code in program(),
+        input in prop::collection::vec(any::<u8>(), 0..48),
+        gas in prop_oneof![0i64..300, Just(100_000i64)],
+    ) {
+        compare(&code, &input, gas)?;
+    }
+}
+
+fn binop(op: OpCode, a: impl Into<U256>, b: impl Into<U256>) -> Bytecode {
+    Bytecode::new().pushv(b).pushv(a).opcode(op)
+}
+
+fn ternop(op: OpCode, a: impl Into<U256>, b: impl Into<U256>, n: impl Into<U256>) -> Bytecode {
+    Bytecode::new().pushv(n).pushv(b).pushv(a).opcode(op)
+}
+
+#[test]
+fn reference_vm_regressions() {
+    let min = U256::one() << 255;
+    for code in [
+        // Signed division overflow.
+        binop(OpCode::SDIV, min, U256::MAX),
+        binop(OpCode::SDIV, neg(7), 2),
+        binop(OpCode::SMOD, neg(8), 3),
+        binop(OpCode::SMOD, 8, neg(3)),
+        binop(OpCode::SDIV, 1, 0),
+        binop(OpCode::SMOD, 1, 0),
+        // SIGNEXTEND at and beyond the last byte.
+        binop(OpCode::SIGNEXTEND, 0, 0xff),
+        binop(OpCode::SIGNEXTEND, 0, 0x7f),
+        binop(OpCode::SIGNEXTEND, 30, min),
+        binop(OpCode::SIGNEXTEND, 31, 0xff),
+        binop(OpCode::SIGNEXTEND, 32, 0xff),
+        binop(OpCode::SIGNEXTEND, U256::MAX, 0xff),
+        // Shifts at and beyond the word size.
+        binop(OpCode::SAR, 255, min),
+        binop(OpCode::SAR, 256, min),
+        binop(OpCode::SAR, 256, 1),
+        binop(OpCode::SAR, 1, neg(3)),
+        binop(OpCode::SAR, U256::MAX, neg(1)),
+        binop(OpCode::SHL, 255, 1),
+        binop(OpCode::SHL, 256, 1),
+        binop(OpCode::SHR, 255, U256::MAX),
+        binop(OpCode::SHR, 256, U256::MAX),
+        // BYTE index bounds.
+        binop(OpCode::BYTE, 0, U256::MAX),
+        binop(OpCode::BYTE, 31, 0x1234),
+        binop(OpCode::BYTE, 32, U256::MAX),
+        // EXP edge cases and gas.
+        binop(OpCode::EXP, 0, 0),
+        binop(OpCode::EXP, 2, 255),
+        binop(OpCode::EXP, 2, 256),
+        binop(OpCode::EXP, U256::MAX, U256::MAX),
+        // Modular arithmetic with intermediate overflow.
+        ternop(OpCode::ADDMOD, U256::MAX, U256::MAX, 7),
+        ternop(OpCode::MULMOD, U256::MAX, U256::MAX, 12),
+        ternop(OpCode::ADDMOD, 1, 2, 0),
+        ternop(OpCode::MULMOD, 1, 2, 0),
+        // Signed comparisons around zero.
+        binop(OpCode::SLT, neg(1), 0),
+        binop(OpCode::SGT, neg(1), 0),
+        binop(OpCode::SLT, min, min - 1),
+    ] {
+        compare(&code.opcode(OpCode::STOP).build(), &[], 100_000).unwrap();
+    }
+
+    // Memory at huge offsets.
+    for op in [OpCode::MLOAD, OpCode::MSTORE, OpCode::MSTORE8] {
+        let code = Bytecode::new()
+            .pushv(1)
+            .pushv(u64::from(u32::MAX) + 1)
+            .opcode(op)
+            .opcode(OpCode::STOP);
+        compare(&code.build(), &[], 100_000).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use bytes::Bytes;
+use ethereum_types::{Address, U256};
+use evmodin::{
+    host::*,
+    tracing::NoopTracer,
+    util::{mocked_host::*, *},
+    *,
+};
+use std::{panic, thread};
+
+fn message(recipient: Address) -> Message {
+    Message {
+        kind: CallKind::Call,
+        is_static: false,
+        depth: 0,
+        gas: 1_000_000,
+        recipient,
+        code_address: recipient,
+        sender: Address::zero(),
+        input_data: Bytes::new(),
+        value: U256::zero(),
+    }
+}
+
+#[test]
+fn concurrent_executions() {
+    let shared = SharedHost::new(MockedHost::default());
+
+    let handles = (0..2)
+        .map(|i| {
+            let mut host = shared.clone();
+            thread::spawn(move || {
+                let code = Bytecode::new().sstore(1, 1 + i) + CallInstruction::call(0xa0 + i);
+                AnalyzedCode::analyze(code.build()).execute(
+                    &mut host,
+                    &mut NoopTracer,
+                    None,
+                    message(Address::from_low_u64_be(i)),
+                    Revision::Byzantium,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap().status_code, StatusCode::Success);
+    }
+
+    let host = shared.read();
+    let mut recipients = host
+        .records()
+        .calls
+        .iter()
+        .map(|call| call.recipient)
+        .collect::<Vec<_>>();
+    recipients.sort();
+    assert_eq!(
+        recipients,
+        [0xa0, 0xa1].map(Address::from_low_u64_be).to_vec()
+    );
+    for i in 0..2 {
+        assert_eq!(
+            host.get_storage(Address::from_low_u64_be(i), 1.into()),
+            U256::from(1 + i)
+        );
+    }
+}
+
+/// State of a host whose `call` calls back into the `SharedHost` wrapping it.
+#[derive(Default)]
+struct Recursion {
+    this: Option<SharedHost<Recursive>>,
+    nested: Vec<StatusCode>,
+    panic: bool,
+    /// Read the balance through the wrapping `SharedHost` instead of calling it.
+    read: bool,
+}
+
+type Recursive = DelegatingHost<DummyHost, Recursion>;
+
+fn recursive(recursion: Recursion) -> SharedHost<Recursive> {
+    let shared = SharedHost::new(
+        DelegatingHost::new(DummyHost, recursion).on_call(|host, msg| {
+            let recursion = &mut host.state;
+            if recursion.panic {
+                panic!("host failure");
+            }
+            if recursion.read {
+                recursion.this.as_ref().unwrap().get_balance(msg.recipient);
+            }
+            let nested = recursion.this.clone().unwrap().call(msg);
+            recursion.nested.push(nested.status_code);
+
+            Output {
+                status_code: StatusCode::Success,
+                gas_left: msg.gas,
+                gas_refund: 0,
+                output_data: Bytes::new(),
+                create_address: None,
+                stats: Default::default(),
+            }
+        }),
+    );
+    // The reference cycle leaks the host, which is fine for a test.
+    shared.write().state.this = Some(shared.clone());
+    shared
+}
+
+#[test]
+fn reentrant_call_does_not_deadlock() {
+    let shared = recursive(Recursion::default());
+
+    let output = shared.clone().call(&message(Address::zero()));
+
+    assert_eq!(output.status_code, StatusCode::Success);
+    let host = shared.read();
+    assert_eq!(host.state.nested.len(), 1);
+    assert!(matches!(host.state.nested[0], StatusCode::InternalError(_)));
+}
+
+#[test]
+fn panicking_call_releases_host() {
+    let shared = recursive(Recursion {
+        panic: true,
+        ..Default::default()
+    });
+
+    let mut host = shared.clone();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(move || {
+        host.call(&message(Address::zero()))
+    }));
+    assert!(result.is_err());
+
+    // Neither the lock nor the re-entrancy marker is left behind.
+    assert!(shared.read().state.nested.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "SharedHost accessed from within its own call")]
+fn reentrant_read_panics() {
+    let shared = recursive(Recursion {
+        read: true,
+        ..Default::default()
+    });
+
+    shared.clone().call(&message(Address::zero()));
+}
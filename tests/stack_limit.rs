@@ -0,0 +1,77 @@
+use evmodin::{opcode::*, util::*, *};
+
+const LIMIT: usize = 1024;
+const REVISIONS: [Revision; 2] = [Revision::Byzantium, Revision::London];
+
+/// `depth` items on the stack, then `op`. PC is a single byte, so `op` is at pc `depth`.
+fn at_depth(depth: usize, op: impl Into<Bytecode>) -> Bytecode {
+    (depth * OpCode::PC).append_bc(op)
+}
+
+fn t(revision: Revision, code: Bytecode) -> EvmTester {
+    EvmTester::new()
+        .revision(revision)
+        .gas(1_000_000)
+        .code(code)
+}
+
+fn succeeds(depth: usize, op: impl Into<Bytecode> + Clone) {
+    for revision in REVISIONS {
+        t(revision, at_depth(depth, op.clone()))
+            .status(StatusCode::Success)
+            .check();
+    }
+}
+
+fn overflows(depth: usize, op: impl Into<Bytecode> + Clone) {
+    for revision in REVISIONS {
+        let output = t(revision, at_depth(depth, op.clone()))
+            .status(StatusCode::StackOverflow)
+            .check_and_get_result();
+        assert_eq!(output.stats.failure_site.unwrap().pc, depth);
+    }
+}
+
+#[test]
+fn push() {
+    succeeds(LIMIT - 1, Bytecode::new().pushv(1));
+    overflows(LIMIT, Bytecode::new().pushv(1));
+}
+
+#[test]
+fn dup() {
+    succeeds(LIMIT - 1, OpCode::DUP1);
+    overflows(LIMIT, OpCode::DUP1);
+}
+
+#[test]
+fn swap_on_full_stack() {
+    succeeds(LIMIT - 1, OpCode::SWAP1);
+    succeeds(LIMIT, OpCode::SWAP1);
+}
+
+#[test]
+fn add_on_full_stack() {
+    succeeds(LIMIT - 1, OpCode::ADD);
+    succeeds(LIMIT, OpCode::ADD);
+}
+
+#[test]
+fn call_on_full_stack() {
+    // CALL pops seven items and pushes one, so it cannot overflow.
+    succeeds(LIMIT - 1, OpCode::CALL);
+    succeeds(LIMIT, OpCode::CALL);
+}
+
+#[test]
+fn growing_instructions_are_flagged() {
+    for revision in REVISIONS {
+        let table = get_baseline_instruction_table(revision);
+        for op in [OpCode::PUSH1, OpCode::DUP1, OpCode::PC] {
+            assert!(table[op.to_usize()].unwrap().can_overflow_stack, "{}", op);
+        }
+        for op in [OpCode::SWAP1, OpCode::ADD, OpCode::CALL, OpCode::POP] {
+            assert!(!table[op.to_usize()].unwrap().can_overflow_stack, "{}", op);
+        }
+    }
+}
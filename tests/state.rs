@@ -252,6 +252,36 @@ fn sstore_cost() {
     }
 }
 
+#[test]
+fn sstore_net_metering_reverted_in_petersburg() {
+    assert!(!Revision::Byzantium.sstore_gas_metering());
+    assert!(Revision::Constantinople.sstore_gas_metering());
+    assert!(!Revision::Petersburg.sstore_gas_metering());
+    assert!(Revision::Istanbul.sstore_gas_metering());
+
+    // Modify a stored value twice in a row: the second write is cheap only with net metering.
+    for (revision, gas_used) in [
+        (Revision::Constantinople, 5212),
+        (Revision::Petersburg, 10012),
+    ] {
+        EvmTester::new()
+            .revision(revision)
+            .code(Bytecode::new().sstore(1, 2).sstore(1, 3))
+            .apply_host_fn(|host, _| {
+                host.accounts
+                    .entry(Address::zero())
+                    .or_default()
+                    .storage
+                    .entry(1.into())
+                    .or_default()
+                    .value = 1.into();
+            })
+            .status(StatusCode::Success)
+            .gas_used(gas_used)
+            .check();
+    }
+}
+
 #[test]
 fn sstore_below_stipend() {
     let code = Bytecode::new().sstore(0, 0);
@@ -276,6 +306,137 @@ fn sstore_below_stipend() {
         .check()
 }
 
+#[test]
+fn sstore_clear_refund() {
+    let key = U256::one();
+
+    for (revision, refund) in [
+        (Revision::Byzantium, 15000),
+        (Revision::Istanbul, 15000),
+        (Revision::Berlin, 15000),
+        (Revision::London, 4800),
+    ] {
+        let output = EvmTester::new()
+            .revision(revision)
+            .code(Bytecode::new().sstore(key, 0))
+            .apply_host_fn(move |host, _| {
+                host.accounts
+                    .entry(Address::zero())
+                    .or_default()
+                    .storage
+                    .entry(key)
+                    .or_default()
+                    .value = 1.into();
+            })
+            .status(StatusCode::Success)
+            .check_and_get_result();
+        assert_eq!(output.gas_refund, refund);
+
+        let output = EvmTester::new()
+            .revision(revision)
+            .code(Bytecode::new().sstore(key, 1))
+            .status(StatusCode::Success)
+            .check_and_get_result();
+        assert_eq!(output.gas_refund, 0);
+    }
+}
+
+#[test]
+fn sstore_restore_refund() {
+    let key = U256::one();
+
+    for (revision, refund) in [
+        (Revision::Petersburg, 0),
+        (Revision::Istanbul, 5000 - 800),
+        (Revision::Berlin, 2900 - 100),
+        (Revision::London, 2900 - 100),
+    ] {
+        let output = EvmTester::new()
+            .revision(revision)
+            .code(Bytecode::new().sstore(key, 2).sstore(key, 1))
+            .apply_host_fn(move |host, _| {
+                host.accounts
+                    .entry(Address::zero())
+                    .or_default()
+                    .storage
+                    .entry(key)
+                    .or_default()
+                    .value = 1.into();
+            })
+            .status(StatusCode::Success)
+            .check_and_get_result();
+        assert_eq!(output.gas_refund, refund);
+    }
+}
+
+#[test]
+fn sstore_clear_refund_reverted() {
+    let key = U256::one();
+
+    let output = EvmTester::new()
+        .revision(Revision::London)
+        .code(Bytecode::new().sstore(key, 0).revert(0, 0))
+        .apply_host_fn(move |host, _| {
+            host.accounts
+                .entry(Address::zero())
+                .or_default()
+                .storage
+                .entry(key)
+                .or_default()
+                .value = 1.into();
+        })
+        .status(StatusCode::Revert)
+        .check_and_get_result();
+    assert_eq!(output.gas_refund, 0);
+}
+
+/// Refund of a parent which clears a slot and calls a child clearing another one.
+fn parent_refund(child_code: Bytecode) -> i64 {
+    let parent = Address::from_low_u64_be(0xaa);
+    let child = Address::from_low_u64_be(0xbb);
+
+    let mut host = MockedHost::default();
+    for (address, code) in [
+        (
+            parent,
+            Bytecode::new()
+                .sstore(1, 0)
+                .append_bc(CallInstruction::call(child.to_low_u64_be()).gas(100_000))
+                .opcode(OpCode::POP),
+        ),
+        (child, Bytecode::new().sstore(1, 0).append_bc(child_code)),
+    ] {
+        let account = host.accounts.entry(address).or_default();
+        account.code = code.build().into();
+        account.storage.entry(1.into()).or_default().value = 1.into();
+    }
+
+    let output = CallStackExecutor::new(&mut host, Revision::London).execute(Message {
+        kind: CallKind::Call,
+        is_static: false,
+        depth: 0,
+        gas: 1_000_000,
+        recipient: parent,
+        sender: Address::zero(),
+        input_data: Default::default(),
+        value: U256::zero(),
+        code_address: parent,
+    });
+    assert_eq!(output.status_code, StatusCode::Success);
+    output.gas_refund
+}
+
+#[test]
+fn callee_refund() {
+    assert_eq!(
+        parent_refund(Bytecode::new().opcode(OpCode::STOP)),
+        2 * 4800
+    );
+    // The refund of a reverted child is reverted with its state changes.
+    assert_eq!(parent_refund(Bytecode::new().revert(0, 0)), 4800);
+    assert_eq!(parent_refund(Bytecode::new().opcode(OpCode::INVALID)), 4800);
+}
+
 #[test]
 fn tx_context() {
     EvmTester::new()
@@ -328,6 +489,36 @@ fn tx_context() {
         .check()
 }
 
+#[test]
+fn chainid() {
+    let code = Bytecode::new().opcode(OpCode::CHAINID).ret_top();
+
+    for revision in [Revision::Istanbul, Revision::Berlin, Revision::London] {
+        EvmTester::new()
+            .code(code.clone())
+            .revision(revision)
+            .status(StatusCode::Success)
+            .output_value(1)
+            .check();
+
+        EvmTester::new()
+            .code(code.clone())
+            .revision(revision)
+            .apply_host_fn(|host, _| {
+                host.tx_context.chain_id = 5.into();
+            })
+            .status(StatusCode::Success)
+            .output_value(5)
+            .check();
+    }
+
+    EvmTester::new()
+        .code(code)
+        .revision(Revision::Petersburg)
+        .status(StatusCode::UndefinedInstruction)
+        .check();
+}
+
 #[test]
 fn balance() {
     EvmTester::new()
@@ -347,6 +538,34 @@ fn balance() {
         .check()
 }
 
+#[test]
+fn access_recording_order() {
+    let a = Address::from_low_u64_be(0xa);
+    let b = Address::from_low_u64_be(0xb);
+    let c = Address::from_low_u64_be(0xc);
+
+    EvmTester::new()
+        .code(
+            Bytecode::new()
+                .pushv(0xa)
+                .opcode(OpCode::BALANCE)
+                .opcode(OpCode::POP)
+                .append_bc(CallInstruction::call(0xb))
+                .opcode(OpCode::POP)
+                .pushv(0xc)
+                .opcode(OpCode::BALANCE)
+                .opcode(OpCode::POP),
+        )
+        .status(StatusCode::Success)
+        .inspect_host(move |host, _| {
+            let r = host.records();
+            assert_eq!(r.account_accesses, [a, b, c]);
+            assert_eq!(r.calls.len(), 1);
+            assert_eq!(r.calls[0].recipient, b);
+        })
+        .check()
+}
+
 #[test]
 fn account_info_homestead() {
     let t = EvmTester::new()
@@ -511,6 +730,18 @@ fn log_data_cost() {
     }
 }
 
+#[test]
+fn log_data_cost_boundary() {
+    for size in [1, 32, 100] {
+        let words = (size + 31) / 32;
+        let memory_cost = 3 * words + words * words / 512;
+        assert_gas_boundary!(
+            EvmTester::new().code(Bytecode::new().pushv(size).pushv(0).opcode(OpCode::LOG0)),
+            3 + 3 + 375 + 8 * size + memory_cost
+        );
+    }
+}
+
 #[test]
 fn selfdestruct() {
     EvmTester::new()
@@ -571,6 +802,38 @@ fn selfdestruct() {
         .check();
 }
 
+#[test]
+fn selfdestruct_refund() {
+    let t = EvmTester::new().code(hex!("6009ff"));
+
+    for (revision, refund) in [
+        (Revision::Istanbul, 24000),
+        (Revision::Berlin, 24000),
+        (Revision::London, 0),
+    ] {
+        let output = t
+            .clone()
+            .revision(revision)
+            .status(StatusCode::Success)
+            .check_and_get_result();
+        assert_eq!(output.gas_refund, refund);
+
+        // Only the first self-destruct of an account in a transaction is refunded.
+        let output = t
+            .clone()
+            .revision(revision)
+            .apply_host_fn(|host, msg| {
+                host.recorded.lock().selfdestructs.push(SelfdestructRecord {
+                    selfdestructed: msg.recipient,
+                    beneficiary: Address::zero(),
+                });
+            })
+            .status(StatusCode::Success)
+            .check_and_get_result();
+        assert_eq!(output.gas_refund, 0);
+    }
+}
+
 #[test]
 fn selfdestruct_with_balance() {
     let beneficiary = Address::zero();
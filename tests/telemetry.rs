@@ -0,0 +1,80 @@
+//! Run with `--features telemetry`.
+#![cfg(feature = "telemetry")]
+
+use evmodin::{tracing::TracingSubscriberTracer, util::*, *};
+use std::sync::{Arc, Mutex};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer,
+};
+
+#[derive(Default)]
+struct Depth(Option<i64>);
+
+impl Visit for Depth {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == "depth" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+}
+
+#[derive(Clone, Default)]
+struct Recorded {
+    /// Depth of every span and of its parent, in the order they were opened.
+    spans: Arc<Mutex<Vec<(i64, Option<i64>)>>>,
+    /// Depth of the span of every event.
+    events: Arc<Mutex<Vec<Option<i64>>>>,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorded {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut depth = Depth::default();
+        attrs.record(&mut depth);
+        let depth = depth.0.unwrap();
+
+        let span = ctx.span(id).unwrap();
+        let parent = span
+            .parent()
+            .map(|parent| parent.extensions().get::<i64>().copied().unwrap());
+        span.extensions_mut().insert(depth);
+        self.spans.lock().unwrap().push((depth, parent));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let depth = ctx
+            .event_span(event)
+            .and_then(|span| span.extensions().get::<i64>().copied());
+        self.events.lock().unwrap().push(depth);
+    }
+}
+
+#[test]
+fn spans_follow_call_tree() {
+    let recorded = Recorded::default();
+    let subscriber = tracing_subscriber::registry().with(recorded.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        EvmTester::new()
+            .code(Bytecode::new() + CallInstruction::call(0xaa) + CallInstruction::call(0xbb))
+            .tracer(TracingSubscriberTracer::default())
+            .status(StatusCode::Success)
+            .check()
+    });
+
+    assert_eq!(
+        *recorded.spans.lock().unwrap(),
+        [(0, None), (1, Some(0)), (1, Some(0))]
+    );
+    let events = recorded.events.lock().unwrap();
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|depth| *depth == Some(0)));
+}
@@ -0,0 +1,103 @@
+use evmodin::{opcode::*, tracing::*, util::*, *};
+
+#[test]
+fn collecting_tracer_records_steps() {
+    let tracer = CollectingTracer::default();
+
+    EvmTester::new()
+        .code(Bytecode::new().pushv(1).pushv(2).opcode(OpCode::ADD))
+        .tracer(tracer.clone())
+        .gas(100)
+        .status(StatusCode::Success)
+        .gas_used(9)
+        .check();
+
+    let step = |pc, opcode, gas_left, stack_height| TraceStep {
+        depth: 0,
+        pc,
+        opcode,
+        gas_left,
+        stack_height,
+    };
+    assert_eq!(
+        tracer.steps(),
+        [
+            step(0, OpCode::PUSH1, 100, 0),
+            step(2, OpCode::PUSH1, 97, 1),
+            step(4, OpCode::ADD, 94, 2),
+        ]
+    );
+}
+
+fn trace(code: Bytecode) -> Vec<TraceStep> {
+    let tracer = CollectingTracer::default();
+    EvmTester::new()
+        .code(code)
+        .tracer(tracer.clone())
+        .gas(100)
+        .check();
+    tracer.steps()
+}
+
+#[test]
+fn diff_executions_reports_first_divergence() {
+    let add = trace(
+        Bytecode::new()
+            .pushv(1)
+            .pushv(2)
+            .opcode(OpCode::ADD)
+            .opcode(OpCode::POP),
+    );
+    let mul = trace(
+        Bytecode::new()
+            .pushv(1)
+            .pushv(2)
+            .opcode(OpCode::MUL)
+            .opcode(OpCode::POP),
+    );
+
+    assert_eq!(diff_executions(&add, &add), None);
+    assert_eq!(
+        diff_executions(&add, &mul),
+        Some(Divergence {
+            step: 2,
+            field: TraceField::Opcode,
+        })
+    );
+    assert_eq!(
+        diff_executions(&add, &add[..3]),
+        Some(Divergence {
+            step: 3,
+            field: TraceField::Length,
+        })
+    );
+}
+
+#[test]
+fn on_failure_tracer_is_quiet_on_success() {
+    let inner = CollectingTracer::default();
+
+    EvmTester::new()
+        .code(Bytecode::new().pushv(1).pushv(2).opcode(OpCode::ADD))
+        .tracer(OnFailureTracer::new(inner.clone()))
+        .status(StatusCode::Success)
+        .check();
+
+    assert_eq!(inner.steps(), []);
+}
+
+#[test]
+fn on_failure_tracer_forwards_on_revert() {
+    let inner = CollectingTracer::default();
+
+    EvmTester::new()
+        .code(Bytecode::new().pushv(0).pushv(0).opcode(OpCode::REVERT))
+        .tracer(OnFailureTracer::new(inner.clone()))
+        .gas(100)
+        .status(StatusCode::Revert)
+        .check();
+
+    let opcodes = inner.steps().iter().map(|s| s.opcode).collect::<Vec<_>>();
+    assert_eq!(opcodes, [OpCode::PUSH1, OpCode::PUSH1, OpCode::REVERT]);
+    assert_eq!(inner.steps()[2].gas_left, 94);
+}
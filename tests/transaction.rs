@@ -0,0 +1,353 @@
+use ethereum_types::{Address, BloomInput, U256};
+use evmodin::{
+    opcode::*,
+    util::{mocked_host::*, *},
+    *,
+};
+use hex_literal::hex;
+
+const SENDER: Address = Address::repeat_byte(0x01);
+const RECIPIENT: Address = Address::repeat_byte(0x02);
+const COINBASE: Address = Address::repeat_byte(0xcb);
+
+fn host_with_sender(balance: u64) -> MockedHost {
+    let mut host = MockedHost::default();
+    host.tx_context.block_coinbase = COINBASE;
+    host.tx_context.block_base_fee = 1.into();
+    host.accounts.entry(SENDER).or_default().balance = balance.into();
+    host
+}
+
+#[test]
+fn value_transfer() {
+    let mut host = host_with_sender(1_000_000);
+
+    let receipt = execute_transaction(
+        &mut host,
+        TransactionEnv {
+            sender: SENDER,
+            to: Some(RECIPIENT),
+            value: 1000.into(),
+            gas_limit: 30000,
+            gas_price: 2.into(),
+            ..Default::default()
+        },
+        Revision::London,
+    )
+    .unwrap();
+
+    assert_eq!(receipt.status_code, StatusCode::Success);
+    assert_eq!(receipt.gas_used, 21000);
+    assert!(receipt.logs.is_empty());
+    assert!(receipt.logs_bloom.is_zero());
+    assert_eq!(receipt.create_address, None);
+
+    assert_eq!(
+        host.accounts[&SENDER].balance,
+        U256::from(1_000_000 - 1000 - 21000 * 2)
+    );
+    assert_eq!(host.accounts[&SENDER].nonce, 1);
+    assert_eq!(host.accounts[&RECIPIENT].balance, U256::from(1000));
+    // Base fee is burnt.
+    assert_eq!(host.accounts[&COINBASE].balance, U256::from(21000));
+
+    assert_eq!(
+        receipt.state_diff,
+        [
+            (
+                SENDER,
+                AccountDiff {
+                    balance: Some((1_000_000.into(), (1_000_000 - 1000 - 21000 * 2).into())),
+                    nonce: Some((0, 1)),
+                    ..Default::default()
+                }
+            ),
+            (
+                RECIPIENT,
+                AccountDiff {
+                    balance: Some((0.into(), 1000.into())),
+                    ..Default::default()
+                }
+            ),
+            (
+                COINBASE,
+                AccountDiff {
+                    balance: Some((0.into(), 21000.into())),
+                    ..Default::default()
+                }
+            ),
+        ]
+        .into_iter()
+        .collect()
+    );
+}
+
+/// The first transaction on mainnet, in block 46147:
+/// 0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060.
+/// Its receipt has 21000 gas used and no logs.
+///
+/// The sender's historical balance is not embedded: it is given exactly the cost of the
+/// transaction, and the coinbase is arbitrary.
+#[test]
+fn first_mainnet_transaction() {
+    let sender = Address::from(hex!("a1e4380a3b1f749673e270229993ee55f35663b4"));
+    let recipient = Address::from(hex!("5df9b87991262f6ba471f09758cde1c0fc1de734"));
+    let gas_price = U256::from(50_000_000_000_000_u64);
+    let value = U256::from(31337);
+    let cost = gas_price * 21000 + value;
+
+    let mut host = MockedHost::default();
+    host.tx_context.block_coinbase = COINBASE;
+    host.accounts.entry(sender).or_default().balance = cost;
+
+    let receipt = execute_transaction(
+        &mut host,
+        TransactionEnv {
+            sender,
+            to: Some(recipient),
+            value,
+            gas_limit: 21000,
+            gas_price,
+            ..Default::default()
+        },
+        Revision::Frontier,
+    )
+    .unwrap();
+
+    assert_eq!(receipt.status_code, StatusCode::Success);
+    assert_eq!(receipt.gas_used, 21000);
+    assert!(receipt.logs.is_empty());
+    assert!(receipt.logs_bloom.is_zero());
+    assert_eq!(
+        receipt.state_diff[&sender],
+        AccountDiff {
+            balance: Some((cost, U256::zero())),
+            nonce: Some((0, 1)),
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        receipt.state_diff[&recipient].balance,
+        Some((0.into(), value))
+    );
+    // 1.05 ether.
+    assert_eq!(
+        receipt.state_diff[&COINBASE].balance,
+        Some((0.into(), U256::from(1_050_000_000_000_000_000_u64)))
+    );
+}
+
+#[test]
+fn call_emitting_log() {
+    let topic = U256::from_big_endian(&hex!(
+        "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+    ));
+    let code = Bytecode::new()
+        .mstore8_value(0, 0xff)
+        .pushv(topic)
+        .pushv(1)
+        .pushv(0)
+        .opcode(OpCode::LOG1)
+        .opcode(OpCode::STOP)
+        .build();
+
+    let mut host = host_with_sender(1_000_000);
+    host.accounts.entry(RECIPIENT).or_default().code = code.into();
+
+    let receipt = execute_transaction(
+        &mut host,
+        TransactionEnv {
+            sender: SENDER,
+            to: Some(RECIPIENT),
+            input: vec![0x00, 0x01].into(),
+            gas_limit: 30000,
+            gas_price: 1.into(),
+            ..Default::default()
+        },
+        Revision::London,
+    )
+    .unwrap();
+
+    assert_eq!(receipt.status_code, StatusCode::Success);
+    // Intrinsic: 21000 + 4 (zero byte) + 16 (non-zero byte).
+    // Execution: MSTORE8 with memory expansion 12, three pushes 9, LOG1 with one byte 758.
+    assert_eq!(receipt.gas_used, 21020 + 12 + 9 + 758);
+    assert_eq!(
+        receipt.logs,
+        vec![LogRecord {
+            creator: RECIPIENT,
+            data: vec![0xff].into(),
+            topics: vec![topic],
+        }]
+    );
+    assert!(receipt
+        .logs_bloom
+        .contains_input(BloomInput::Raw(RECIPIENT.as_bytes())));
+    assert!(receipt.logs_bloom.contains_input(BloomInput::Raw(&hex!(
+        "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+    ))));
+    assert!(!receipt
+        .logs_bloom
+        .contains_input(BloomInput::Raw(SENDER.as_bytes())));
+}
+
+#[test]
+fn contract_creation() {
+    let runtime = Bytecode::new().pushv(42).ret_top().build();
+    let init_code = Bytecode::deploy(runtime.clone()).build();
+
+    let mut host = host_with_sender(1_000_000);
+    host.accounts.entry(SENDER).or_default().nonce = 5;
+
+    let receipt = execute_transaction(
+        &mut host,
+        TransactionEnv {
+            sender: SENDER,
+            to: None,
+            value: 7.into(),
+            input: init_code.clone().into(),
+            gas_limit: 100_000,
+            gas_price: 1.into(),
+            ..Default::default()
+        },
+        Revision::London,
+    )
+    .unwrap();
+
+    let address = create_address(SENDER, 5);
+    assert_eq!(receipt.status_code, StatusCode::Success);
+    assert_eq!(receipt.create_address, Some(address));
+    assert_eq!(host.accounts[&address].code, runtime);
+    assert_eq!(host.accounts[&address].balance, U256::from(7));
+    assert_eq!(host.accounts[&SENDER].nonce, 6);
+    assert_eq!(
+        receipt.state_diff[&address].code,
+        Some((Default::default(), runtime.clone().into()))
+    );
+
+    let input_gas = init_code
+        .iter()
+        .map(|&b| if b == 0 { 4 } else { 16 })
+        .sum::<i64>();
+    // Init code: CODECOPY with three pushes and memory expansion 18, RETURN with two pushes 6.
+    let deposit = 200 * runtime.len() as i64;
+    assert_eq!(receipt.gas_used, 53000 + input_gas + 24 + deposit);
+}
+
+#[test]
+fn failed_call_keeps_gas_payment_only() {
+    let mut host = host_with_sender(1_000_000);
+    host.accounts.entry(RECIPIENT).or_default().code = Bytecode::new().revert(0, 0).build().into();
+
+    let receipt = execute_transaction(
+        &mut host,
+        TransactionEnv {
+            sender: SENDER,
+            to: Some(RECIPIENT),
+            value: 1000.into(),
+            gas_limit: 30000,
+            gas_price: 1.into(),
+            ..Default::default()
+        },
+        Revision::London,
+    )
+    .unwrap();
+
+    assert_eq!(receipt.status_code, StatusCode::Revert);
+    assert_eq!(receipt.gas_used, 21006);
+    assert_eq!(
+        host.accounts[&SENDER].balance,
+        U256::from(1_000_000 - 21006)
+    );
+    assert_eq!(host.accounts[&SENDER].nonce, 1);
+    assert_eq!(host.accounts[&RECIPIENT].balance, U256::zero());
+    // Only the gas payment is left of a failed transaction.
+    assert_eq!(
+        receipt.state_diff.keys().copied().collect::<Vec<_>>(),
+        [SENDER, COINBASE]
+    );
+}
+
+#[test]
+fn rejected_transactions() {
+    let tx = TransactionEnv {
+        sender: SENDER,
+        to: Some(RECIPIENT),
+        value: 1000.into(),
+        gas_limit: 21000,
+        gas_price: 1.into(),
+        ..Default::default()
+    };
+
+    let mut host = host_with_sender(21999);
+    assert_eq!(
+        execute_transaction(&mut host, tx.clone(), Revision::London),
+        Err(StatusCode::InsufficientBalance)
+    );
+    assert_eq!(host.accounts[&SENDER].nonce, 0);
+
+    let mut host = host_with_sender(1_000_000);
+    assert_eq!(
+        execute_transaction(
+            &mut host,
+            TransactionEnv {
+                gas_limit: 20999,
+                ..tx
+            },
+            Revision::London
+        ),
+        Err(StatusCode::OutOfGas)
+    );
+    assert_eq!(host.accounts[&SENDER].balance, U256::from(1_000_000));
+}
+
+#[test]
+fn free_transaction_from_absent_sender() {
+    let mut host = MockedHost::default();
+
+    let receipt = execute_transaction(
+        &mut host,
+        TransactionEnv {
+            sender: SENDER,
+            to: Some(RECIPIENT),
+            gas_limit: 21000,
+            ..Default::default()
+        },
+        Revision::London,
+    )
+    .unwrap();
+
+    assert_eq!(receipt.status_code, StatusCode::Success);
+    assert_eq!(host.accounts[&SENDER].nonce, 1);
+    assert_eq!(host.accounts[&SENDER].balance, U256::zero());
+}
+
+#[test]
+fn overflowing_cost_is_rejected() {
+    let mut host = host_with_sender(1_000_000);
+
+    for tx in [
+        TransactionEnv {
+            sender: SENDER,
+            to: Some(RECIPIENT),
+            value: U256::MAX,
+            gas_limit: 21000,
+            gas_price: 1.into(),
+            ..Default::default()
+        },
+        TransactionEnv {
+            sender: SENDER,
+            to: Some(RECIPIENT),
+            gas_limit: 21000,
+            gas_price: U256::MAX,
+            ..Default::default()
+        },
+    ] {
+        assert_eq!(
+            execute_transaction(&mut host, tx, Revision::London),
+            Err(StatusCode::InsufficientBalance)
+        );
+    }
+    assert_eq!(host.accounts[&SENDER].balance, U256::from(1_000_000));
+    assert_eq!(host.accounts[&SENDER].nonce, 0);
+}
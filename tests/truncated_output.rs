@@ -0,0 +1,73 @@
+use evmodin::{opcode::*, util::*, *};
+
+/// MSTORE(0, 0xaa) RETURN(16, 32): 21 gas with memory expansion to two words, 18 without.
+fn tester(op: OpCode, gas: i64) -> EvmTester {
+    EvmTester::new()
+        .code(
+            Bytecode::new()
+                .mstore_value(0, 0xaa)
+                .pushv(32)
+                .pushv(16)
+                .opcode(op),
+        )
+        .gas(gas)
+}
+
+fn truncating() -> ExecutionOptions {
+    ExecutionOptions {
+        truncate_output_on_oog_return: true,
+        ..Default::default()
+    }
+}
+
+fn expected_output(truncated: bool) -> Vec<u8> {
+    let mut output = vec![0; if truncated { 16 } else { 32 }];
+    output[15] = 0xaa;
+    output
+}
+
+#[test]
+fn default_fails() {
+    let output = tester(OpCode::RETURN, 20)
+        .status(StatusCode::OutOfGas)
+        .gas_used(20)
+        .output_data(Vec::new())
+        .check_and_get_result();
+    assert!(!output.stats.output_truncated);
+}
+
+#[test]
+fn truncated() {
+    for (op, status) in [
+        (OpCode::RETURN, StatusCode::Success),
+        (OpCode::REVERT, StatusCode::Revert),
+    ] {
+        for gas in [18, 20] {
+            let output = tester(op, gas)
+                .options(truncating())
+                .status(status)
+                .gas_used(18)
+                .output_data(expected_output(true))
+                .check_and_get_result();
+            assert!(output.stats.output_truncated);
+        }
+    }
+
+    tester(OpCode::RETURN, 17)
+        .options(truncating())
+        .status(StatusCode::OutOfGas)
+        .check();
+}
+
+#[test]
+fn affordable() {
+    for options in [ExecutionOptions::default(), truncating()] {
+        let output = tester(OpCode::RETURN, 21)
+            .options(options)
+            .status(StatusCode::Success)
+            .gas_used(21)
+            .output_data(expected_output(false))
+            .check_and_get_result();
+        assert!(!output.stats.output_truncated);
+    }
+}
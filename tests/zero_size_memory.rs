@@ -0,0 +1,173 @@
+//! Zero-size memory regions never charge expansion nor fail, whatever their offset.
+
+use ethereum_types::U256;
+use evmodin::{opcode::*, util::*, *};
+
+const GAS: i64 = 1_000_000;
+
+fn huge_offset() -> U256 {
+    U256::one() << 255
+}
+
+/// Gas forwarded to CREATE after charging `gas_used` so far.
+fn create_gas(gas_used: i64) -> i64 {
+    let gas_left = GAS - gas_used;
+    gas_left - gas_left / 64
+}
+
+fn cases(revision: Revision) -> Vec<(String, Bytecode, StatusCode, i64)> {
+    let london = revision >= Revision::London;
+    // Base cost of an access to a cold account.
+    let account_access = if london { 2600 } else { 700 };
+
+    let mut cases = vec![
+        (
+            "KECCAK256".to_string(),
+            Bytecode::new()
+                .pushv(0)
+                .pushv(huge_offset())
+                .opcode(OpCode::KECCAK256),
+            StatusCode::Success,
+            36,
+        ),
+        (
+            "RETURN".to_string(),
+            Bytecode::new().ret(huge_offset(), 0),
+            StatusCode::Success,
+            6,
+        ),
+        (
+            "REVERT".to_string(),
+            Bytecode::new()
+                .pushv(0)
+                .pushv(huge_offset())
+                .opcode(OpCode::REVERT),
+            StatusCode::Revert,
+            6,
+        ),
+        (
+            "CALLDATACOPY".to_string(),
+            Bytecode::new().calldatacopy(huge_offset(), 0, 0),
+            StatusCode::Success,
+            12,
+        ),
+        (
+            "CODECOPY".to_string(),
+            Bytecode::new().codecopy(huge_offset(), 0, 0),
+            StatusCode::Success,
+            12,
+        ),
+        (
+            "RETURNDATACOPY".to_string(),
+            Bytecode::new().returndatacopy(huge_offset(), 0, 0),
+            StatusCode::Success,
+            12,
+        ),
+        (
+            "EXTCODECOPY".to_string(),
+            Bytecode::new()
+                .pushv(0)
+                .pushv(0)
+                .pushv(huge_offset())
+                .pushv(0xaa)
+                .opcode(OpCode::EXTCODECOPY),
+            StatusCode::Success,
+            12 + account_access,
+        ),
+        (
+            "CREATE".to_string(),
+            Bytecode::new()
+                .pushv(0)
+                .pushv(huge_offset())
+                .pushv(0)
+                .opcode(OpCode::CREATE),
+            StatusCode::Success,
+            9 + 32000 + create_gas(9 + 32000),
+        ),
+    ];
+
+    cases.push(if london {
+        (
+            "CREATE2".to_string(),
+            Bytecode::new()
+                .pushv(0)
+                .pushv(0)
+                .pushv(huge_offset())
+                .pushv(0)
+                .opcode(OpCode::CREATE2),
+            StatusCode::Success,
+            12 + 32000 + create_gas(12 + 32000),
+        )
+    } else {
+        (
+            "CREATE2".to_string(),
+            Bytecode::new().opcode(OpCode::CREATE2),
+            StatusCode::UndefinedInstruction,
+            GAS,
+        )
+    });
+
+    for n in 0..=4 {
+        cases.push((
+            format!("LOG{}", n),
+            Bytecode::new()
+                .append_bc(n * Bytecode::new().pushv(0))
+                .pushv(0)
+                .pushv(huge_offset())
+                .opcode(OpCode(OpCode::LOG0.to_u8() + n as u8)),
+            StatusCode::Success,
+            6 + 3 * n as i64 + 375 * (n as i64 + 1),
+        ));
+    }
+
+    for op in [
+        OpCode::CALL,
+        OpCode::CALLCODE,
+        OpCode::DELEGATECALL,
+        OpCode::STATICCALL,
+    ] {
+        let call = |address: u64| match op {
+            OpCode::CALL => CallInstruction::call(address),
+            OpCode::CALLCODE => CallInstruction::callcode(address),
+            OpCode::DELEGATECALL => CallInstruction::delegatecall(address),
+            _ => CallInstruction::staticcall(address),
+        };
+        let pushes = if op == OpCode::CALL || op == OpCode::CALLCODE {
+            7
+        } else {
+            6
+        };
+
+        cases.push((
+            format!("{} input", op),
+            Bytecode::new().append_bc(call(0xaa).input(huge_offset(), 0)),
+            StatusCode::Success,
+            3 * pushes + account_access,
+        ));
+        cases.push((
+            format!("{} output", op),
+            Bytecode::new().append_bc(call(0xaa).output(huge_offset(), 0)),
+            StatusCode::Success,
+            3 * pushes + account_access,
+        ));
+    }
+
+    cases
+}
+
+#[test]
+fn zero_size_memory_regions_with_huge_offset() {
+    for revision in [Revision::Byzantium, Revision::London] {
+        for (name, code, status, gas_used) in cases(revision) {
+            let output = EvmTester::new()
+                .revision(revision)
+                .gas(GAS)
+                .code(code)
+                .check_and_get_result();
+
+            assert_eq!(output.status_code, status, "{} at {}", name, revision);
+            assert_eq!(GAS - output.gas_left, gas_used, "{} at {}", name, revision);
+            assert!(output.output_data.is_empty(), "{} at {}", name, revision);
+        }
+    }
+}